@@ -1,6 +1,35 @@
 //! Extractors for axum that supports configuration.
 //!
 //! This primarily explores a possible solution to <https://github.com/tokio-rs/axum/issues/1116>.
+//!
+//! # Feature flags
+//!
+//! The following optional features are available:
+//!
+//! Name | Description | Default?
+//! ---|---|---
+//! `axum-06` | Enables [`via_state`] for configuring extractors via axum 0.6 router state, and using [`via_extensions::Query`] with axum 0.6 | No
+//! `axum-07` | Enables using [`via_extensions::Query`] with axum 0.7 | No
+//! `br` | Enables decompressing `br` bodies with [`Decompression`](via_extensions::Decompression) | No
+//! `charset` | Enables transcoding form bodies with a `charset` other than UTF-8 | No
+//! `deflate` | Enables decompressing `deflate` bodies with [`Decompression`](via_extensions::Decompression) | No
+//! `dev-errors` | Enables [`presets::dev_errors`](via_extensions::presets::dev_errors) for showing where JSON bodies failed to parse during development | No
+//! `garde` | Enables [`Garde`](via_extensions::Garde) for validating values with the [`garde`](https://docs.rs/garde) crate, with the context obtained from the request extensions | No
+//! `gzip` | Enables decompressing `gzip` bodies with [`Decompression`](via_extensions::Decompression) | No
+//! `hmac` | Enables [`Verified`](via_extensions::Verified) for checking HMAC signatures of request bodies, such as those of webhooks | No
+//! `html` | Enables [`presets::html`](via_extensions::presets::html) for responding to rejections with HTML error pages | No
+//! `html-form` | Enables [`QueryParser::HtmlForm`](via_extensions::QueryParser::HtmlForm) for parsing query strings and forms with repeated keys | No
+//! `metrics` | Enables counting extractions and rejections with the [`metrics`](https://docs.rs/metrics) crate, as `axum_extractor_config_extractions_total` and `axum_extractor_config_rejections_total`. The counters are labeled with `extractor` and `route`, the matched path, and rejections also with `kind` | No
+//! `msgpack` | Enables [`ResponseFormat::MessagePack`](via_extensions::ResponseFormat) for responding with MessagePack from [`Negotiate`](via_extensions::Negotiate) | No
+//! `multipart` | Enables [`Multipart`](via_extensions::Multipart) and [`TypedMultipart`](via_extensions::TypedMultipart) for `multipart/form-data` requests, with per-field size and content type limits | No
+//! `otel` | Enables recording rejections on the active [OpenTelemetry](https://docs.rs/opentelemetry) span, with the extractor, kind, target type, and status as attributes and an event with the rejection's message | No
+//! `testing` | Enables [`via_extensions::testing`] with helpers for running extractors and rejection handlers in tests without a `Router` | No
+//! `tracing` | Enables a warning, logged once per extractor, when an extractor rejects a request without a config being added, and [`JsonConfig::trace_rejections`](via_extensions::JsonConfig::trace_rejections) for emitting events for rejections | No
+//! `schemars` | Enables [`JsonConfig::schema`](via_extensions::JsonConfig::schema) for including the expected JSON Schema in rejections | No
+//! `qs` | Enables [`QueryParser::Qs`](via_extensions::QueryParser::Qs) for parsing nested query strings and forms | No
+//! `validator` | Implements [`Validate`](via_extensions::Validate) for types that implement the [`validator`](https://docs.rs/validator) crate's `Validate`, so they can be used with [`Valid`](via_extensions::Valid) | No
+//! `yaml` | Enables [`ResponseFormat::Yaml`](via_extensions::ResponseFormat) for responding with YAML from [`Negotiate`](via_extensions::Negotiate) | No
+//! `zstd` | Enables decompressing `zstd` bodies with [`Decompression`](via_extensions::Decompression) | No
 
 #![warn(
     clippy::all,
@@ -44,4 +73,20 @@
 #![cfg_attr(test, allow(clippy::float_cmp))]
 
 pub mod via_extensions;
+#[cfg(feature = "axum-06")]
+pub mod via_state;
 pub mod via_types;
+
+#[doc(hidden)]
+pub mod __private {
+    // used by the macros
+    pub use crate::via_extensions::custom::{get_config, reject_custom, CustomRejectionHandler};
+    pub use axum::{
+        async_trait,
+        extract::{FromRequest, RequestParts},
+        http::StatusCode,
+        response::{IntoResponse, Response},
+    };
+    pub use serde_json;
+    pub use tower_layer::Layer;
+}
@@ -0,0 +1,180 @@
+use super::{json, RejectionInfo};
+use axum::{
+    body::Bytes,
+    extract::rejection::JsonRejection,
+    extract::{MatchedPath, RequestParts},
+    http::Method,
+};
+use serde_json::Value;
+use std::{fmt, sync::Arc};
+
+/// Audit hook for rejected JSON bodies, set with
+/// [`JsonConfig::audit`](super::JsonConfig::audit).
+///
+/// The hook receives an [`AuditRecord`] for every rejection, with an excerpt of the body that has
+/// the configured fields redacted. It's intended for security audit sinks, which should record
+/// rejected payloads without storing secrets such as passwords.
+///
+/// The redaction rules are separate from [`JsonConfig::redact`](super::JsonConfig::redact), so
+/// audit logs can redact more, or less, than what rejection handlers see.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{Audit, JsonConfig};
+///
+/// let audit = Audit::new(|record| {
+///     eprintln!(
+///         "rejected {} {:?}: {} body={:?}",
+///         record.method(),
+///         record.route(),
+///         record.info().message(),
+///         record.excerpt(),
+///     );
+/// })
+/// .redact(["password", "/card/number"])
+/// .max_excerpt_len(512);
+///
+/// let config = JsonConfig::new().audit(audit);
+/// # let _: JsonConfig<axum::body::Body> = config;
+/// ```
+#[derive(Clone)]
+pub struct Audit {
+    hook: Arc<dyn Fn(&AuditRecord<'_>) + Send + Sync>,
+    redact: Arc<Vec<String>>,
+    max_excerpt_len: usize,
+}
+
+impl Audit {
+    /// Create a new `Audit` that calls `f` for every rejection.
+    pub fn new<F>(f: F) -> Self
+    where
+        F: Fn(&AuditRecord<'_>) + Send + Sync + 'static,
+    {
+        Self {
+            hook: Arc::new(f),
+            redact: Arc::default(),
+            max_excerpt_len: 1024,
+        }
+    }
+
+    /// Set fields whose values are replaced with `"[REDACTED]"` in the excerpt.
+    ///
+    /// Fields are names or JSON Pointers, like for
+    /// [`JsonConfig::redact`](super::JsonConfig::redact). If fields are set, bodies that aren't
+    /// valid JSON cannot be redacted so the record has no excerpt.
+    ///
+    /// Defaults to no fields.
+    pub fn redact<I, S>(mut self, fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.redact = Arc::new(fields.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Set the maximum length of the excerpt, in bytes.
+    ///
+    /// Defaults to 1024.
+    pub fn max_excerpt_len(mut self, max_excerpt_len: usize) -> Self {
+        self.max_excerpt_len = max_excerpt_len;
+        self
+    }
+
+    pub(crate) fn record<B>(
+        &self,
+        info: &RejectionInfo,
+        rejection: &JsonRejection,
+        req: &RequestParts<B>,
+        bytes: Option<&Bytes>,
+    ) {
+        let excerpt = bytes.and_then(|bytes| self.excerpt(bytes));
+        let record = AuditRecord {
+            info,
+            rejection,
+            method: req.method(),
+            route: req
+                .extensions()
+                .get::<MatchedPath>()
+                .map(MatchedPath::as_str),
+            excerpt: excerpt.as_ref().map(|(excerpt, _)| excerpt.as_str()),
+            truncated: excerpt.as_ref().is_some_and(|(_, truncated)| *truncated),
+        };
+        (self.hook)(&record);
+    }
+
+    // The redacted body, truncated to the maximum length, and whether it was truncated.
+    fn excerpt(&self, bytes: &Bytes) -> Option<(String, bool)> {
+        let mut excerpt = if self.redact.is_empty() {
+            String::from_utf8_lossy(bytes).into_owned()
+        } else {
+            let mut value = serde_json::from_slice::<Value>(bytes).ok()?;
+            json::redact(&self.redact, &mut value, &mut String::new());
+            value.to_string()
+        };
+
+        let truncated = excerpt.len() > self.max_excerpt_len;
+        if truncated {
+            let mut len = self.max_excerpt_len;
+            while !excerpt.is_char_boundary(len) {
+                len -= 1;
+            }
+            excerpt.truncate(len);
+        }
+        Some((excerpt, truncated))
+    }
+}
+
+impl fmt::Debug for Audit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Audit")
+            .field("redact", &self.redact)
+            .field("max_excerpt_len", &self.max_excerpt_len)
+            .finish()
+    }
+}
+
+/// A rejection given to the [`Audit`] hook.
+#[derive(Debug)]
+pub struct AuditRecord<'a> {
+    info: &'a RejectionInfo,
+    rejection: &'a JsonRejection,
+    method: &'a Method,
+    route: Option<&'a str>,
+    excerpt: Option<&'a str>,
+    truncated: bool,
+}
+
+impl<'a> AuditRecord<'a> {
+    /// Information about the rejection.
+    ///
+    /// This formats the rejection's message, if it hasn't been already.
+    pub fn info(&self) -> &'a RejectionInfo {
+        self.info.format_message(self.rejection);
+        self.info
+    }
+
+    /// The request's method.
+    pub fn method(&self) -> &'a Method {
+        self.method
+    }
+
+    /// The path of the route that matched the request, if any.
+    pub fn route(&self) -> Option<&'a str> {
+        self.route
+    }
+
+    /// The redacted excerpt of the body.
+    ///
+    /// This is `None` if the rejection happened before the body was buffered, or if the body had
+    /// to be redacted but isn't valid JSON. Invalid UTF-8 is replaced with `U+FFFD`.
+    pub fn excerpt(&self) -> Option<&'a str> {
+        self.excerpt
+    }
+
+    /// Whether the excerpt was longer than the maximum length and has been truncated.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+}
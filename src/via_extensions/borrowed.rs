@@ -0,0 +1,119 @@
+use super::{
+    combined, json,
+    rejection::{ConfiguredRejection, JsonDeserializeError},
+    transform_body, ExtractError, GlobalRejectionConfig, JsonConfig,
+};
+use axum::{
+    async_trait,
+    body::{Bytes, HttpBody},
+    extract::{
+        rejection::{JsonRejection, MissingJsonContentType},
+        FromRequest, RequestParts,
+    },
+    BoxError,
+};
+use serde::Deserialize;
+
+/// Extractor that buffers a JSON body so it can be deserialized into types that borrow from it.
+///
+/// Types with `&str` fields, or `Cow<str>` fields with `#[serde(borrow)]`, can be deserialized
+/// from the body with [`BorrowedJson::deserialize`] without allocating owned strings, which
+/// helps with large string-heavy payloads. Since the value borrows from the body it cannot be
+/// returned by the extractor itself, which is why it's deserialized in the handler.
+///
+/// The body is checked and buffered like [`Json`](super::Json) does, so missing content types,
+/// [`max_body_size`](JsonConfig::max_body_size), and
+/// [`transform_body`](JsonConfig::transform_body) are handled by the [`JsonConfig`]. Errors from
+/// deserializing are returned by [`BorrowedJson::deserialize`] instead.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{rejection::JsonDeserializeError, BorrowedJson};
+/// use axum::{routing::post, Router};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Event<'a> {
+///     kind: &'a str,
+///     message: &'a str,
+/// }
+///
+/// async fn ingest(body: BorrowedJson) -> Result<(), JsonDeserializeError> {
+///     let event = body.deserialize::<Event<'_>>()?;
+///     // ...
+///     Ok(())
+/// }
+///
+/// let app = Router::new().route("/events", post(ingest));
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone)]
+pub struct BorrowedJson {
+    bytes: Bytes,
+}
+
+impl BorrowedJson {
+    /// Deserialize the body into `T`, borrowing from it where `T` allows.
+    pub fn deserialize<'a, T>(&'a self) -> Result<T, JsonDeserializeError>
+    where
+        T: Deserialize<'a>,
+    {
+        let mut deserializer = serde_json::Deserializer::from_slice(&self.bytes);
+        let value = serde_path_to_error::deserialize(&mut deserializer).map_err(|err| {
+            JsonDeserializeError::new(json::pointer(err.path()), err.into_inner())
+        })?;
+        // reject trailing characters, like `serde_json::from_slice` does
+        deserializer
+            .end()
+            .map_err(|err| JsonDeserializeError::new(String::new(), err))?;
+        Ok(value)
+    }
+
+    /// The body.
+    pub fn bytes(&self) -> &Bytes {
+        &self.bytes
+    }
+}
+
+#[async_trait]
+impl<B> FromRequest<B> for BorrowedJson
+where
+    B: HttpBody<Data = Bytes> + Send + 'static,
+    B::Error: Into<BoxError>,
+{
+    type Rejection = ConfiguredRejection<JsonRejection>;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let config = combined::get::<JsonConfig<B>, B>(req)
+            .unwrap_or_default()
+            .for_request_method(req.method());
+
+        match extract(&config, req).await {
+            Ok(bytes) => Ok(Self { bytes }),
+            Err(ExtractError::Hook(response)) => {
+                Err(config.hook_rejection::<Self>(*response, "transform_body", req))
+            }
+            Err(ExtractError::Rejection(rejection)) => {
+                let global = combined::get::<GlobalRejectionConfig<B>, B>(req);
+                Err(config.reject::<Self>(rejection, req, global.as_deref()))
+            }
+        }
+    }
+}
+
+async fn extract<B>(
+    config: &JsonConfig<B>,
+    req: &mut RequestParts<B>,
+) -> Result<Bytes, ExtractError<JsonRejection>>
+where
+    B: HttpBody<Data = Bytes> + Send + 'static,
+    B::Error: Into<BoxError>,
+{
+    if !json::has_json_content_type(req) {
+        return Err(JsonRejection::from(MissingJsonContentType::default()).into());
+    }
+    let bytes = json::buffer(config, req).await?;
+    let bytes = transform_body(config.transform_body.as_ref(), bytes)?;
+    Ok(json::apply_utf8_policy(config, bytes).await?)
+}
@@ -0,0 +1,193 @@
+use axum::{
+    async_trait,
+    body::{Bytes, HttpBody},
+    extract::{FromRequest, RequestParts},
+    http::{Request, StatusCode},
+    response::{IntoResponse, Response},
+    BoxError,
+};
+use futures_util::future::BoxFuture;
+use std::task::{Context, Poll};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Layer that buffers request bodies so they can be extracted more than once.
+///
+/// The body is read into memory before the request reaches the inner service, and replaced with a
+/// body that yields the same bytes. The bytes are also kept in the request extensions, which
+/// allows middleware to extract the body with [`Peek`] while the handler can still extract it
+/// afterwards.
+///
+/// Requests whose body cannot be read are rejected with the rejection of [`Bytes`].
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{BufferBody, Json, JsonConfig, Peek};
+/// use axum::{
+///     body::Body,
+///     extract::RequestParts,
+///     http::Request,
+///     middleware::{self, Next},
+///     response::{IntoResponse, Response},
+///     routing::post,
+///     Router,
+/// };
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Payload {
+///     id: u32,
+/// }
+///
+/// // check the payload in middleware, rejecting with the `JsonConfig` of the router
+/// async fn validate(req: Request<Body>, next: Next<Body>) -> Response {
+///     let mut parts = RequestParts::new(req);
+///     if let Err(rejection) = parts.extract::<Peek<Json<Payload>>>().await {
+///         return rejection;
+///     }
+///     match parts.try_into_request() {
+///         Ok(req) => next.run(req).await,
+///         Err(err) => err.into_response(),
+///     }
+/// }
+///
+/// async fn handler(Json(payload): Json<Payload>) {}
+///
+/// let app = Router::new()
+///     .route("/", post(handler))
+///     .layer(middleware::from_fn(validate))
+///     .layer(BufferBody::new())
+///     .layer(JsonConfig::new());
+/// # let _: Router<Body> = app;
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BufferBody {
+    _priv: (),
+}
+
+impl BufferBody {
+    /// Create a new `BufferBody`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<S> Layer<S> for BufferBody {
+    type Service = BufferBodyService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        BufferBodyService { inner }
+    }
+}
+
+#[allow(unreachable_pub)]
+#[derive(Debug, Clone)]
+pub struct BufferBodyService<S> {
+    inner: S,
+}
+
+impl<S, B> Service<Request<B>> for BufferBodyService<S>
+where
+    S: Service<Request<B>> + Clone + Send + 'static,
+    S::Response: IntoResponse,
+    S::Future: Send,
+    B: HttpBody + From<Bytes> + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Response, S::Error>>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<B>) -> Self::Future {
+        // take the service that was driven to readiness
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        Box::pin(async move {
+            let (parts, body) = req.into_parts();
+            let bytes = match Bytes::from_request(&mut RequestParts::new(Request::new(body))).await
+            {
+                Ok(bytes) => bytes,
+                Err(rejection) => return Ok(rejection.into_response()),
+            };
+
+            let mut req = Request::from_parts(parts, B::from(bytes.clone()));
+            req.extensions_mut().insert(BufferedBody(bytes));
+            Ok(inner.call(req).await?.into_response())
+        })
+    }
+}
+
+// the bytes buffered by `BufferBody`
+#[derive(Clone)]
+struct BufferedBody(Bytes);
+
+/// Extractor that runs another extractor on a copy of the request body.
+///
+/// The body of the request is left in place, so it can be extracted again afterwards. This makes
+/// it possible for middleware to run a configured extractor, such as [`Json`](super::Json), and
+/// still have the handler extract the same body. The extractor sees the configs and other request
+/// extensions as usual.
+///
+/// Requires the [`BufferBody`] layer. Without it requests are rejected with `500 Internal Server
+/// Error`. See [`BufferBody`] for an example.
+#[derive(Debug, Clone, Copy)]
+pub struct Peek<E>(pub E);
+
+#[async_trait]
+impl<E, B> FromRequest<B> for Peek<E>
+where
+    E: FromRequest<B>,
+    B: From<Bytes> + Send,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let bytes =
+            match req.extensions().get::<BufferedBody>() {
+                Some(BufferedBody(bytes)) => bytes.clone(),
+                None => return Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Missing `BufferBody` layer. `Peek` requires the request body to be buffered",
+                )
+                    .into_response()),
+            };
+
+        extract_from_copy::<E, B>(req, bytes)
+            .await
+            .map(Self)
+            .map_err(IntoResponse::into_response)
+    }
+}
+
+// Run `E` on a copy of the request with `bytes` as the body, leaving the body of `req` in place.
+pub(super) async fn extract_from_copy<E, B>(
+    req: &mut RequestParts<B>,
+    bytes: Bytes,
+) -> Result<E, E::Rejection>
+where
+    E: FromRequest<B>,
+    B: From<Bytes> + Send,
+{
+    let mut copy = Request::new(B::from(bytes));
+    *copy.method_mut() = req.method().clone();
+    *copy.uri_mut() = req.uri().clone();
+    *copy.version_mut() = req.version();
+    *copy.headers_mut() = req.headers().clone();
+    *copy.extensions_mut() = std::mem::take(req.extensions_mut());
+
+    let mut copy = RequestParts::new(copy);
+    let result = E::from_request(&mut copy).await;
+
+    // give back the extensions the extractor might have changed
+    *req.extensions_mut() = std::mem::take(copy.extensions_mut());
+
+    result
+}
@@ -0,0 +1,82 @@
+use axum::{
+    async_trait,
+    extract::{FromRequest, RequestParts},
+};
+
+/// Extractor that caches the value of another extractor in the request extensions.
+///
+/// The first time `Cached<E>` is extracted it runs `E` and stores a clone of the value. Later
+/// extractions of `Cached<E>` for the same request return the stored value, without running `E`
+/// again. This allows middleware and the handler to both extract a [`Json`](super::Json) body,
+/// which can otherwise only be read once.
+///
+/// Values are stored by type, so `Cached<Json<A>>` and `Cached<Json<B>>` don't share values.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{Cached, Json};
+/// use axum::{
+///     body::Body,
+///     extract::RequestParts,
+///     http::Request,
+///     middleware::{self, Next},
+///     response::{IntoResponse, Response},
+///     routing::post,
+///     Router,
+/// };
+/// use serde::Deserialize;
+///
+/// #[derive(Clone, Deserialize)]
+/// struct Payload {
+///     tenant: String,
+/// }
+///
+/// async fn check_tenant(req: Request<Body>, next: Next<Body>) -> Response {
+///     let mut parts = RequestParts::new(req);
+///     let Cached(Json(payload)) = match parts.extract::<Cached<Json<Payload>>>().await {
+///         Ok(payload) => payload,
+///         Err(rejection) => return rejection.into_response(),
+///     };
+///     // ...
+///
+///     // the body has been read, so give the request an empty one
+///     *parts.body_mut() = Some(Body::empty());
+///     match parts.try_into_request() {
+///         Ok(req) => next.run(req).await,
+///         Err(err) => err.into_response(),
+///     }
+/// }
+///
+/// async fn handler(Cached(Json(payload)): Cached<Json<Payload>>) {}
+///
+/// let app = Router::new()
+///     .route("/", post(handler))
+///     .layer(middleware::from_fn(check_tenant));
+/// # let _: Router<Body> = app;
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Cached<E>(pub E);
+
+// the value stored by `Cached`
+#[derive(Clone)]
+struct CachedValue<E>(E);
+
+#[async_trait]
+impl<E, B> FromRequest<B> for Cached<E>
+where
+    E: FromRequest<B> + Clone + Send + Sync + 'static,
+    B: Send,
+{
+    type Rejection = E::Rejection;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        if let Some(CachedValue(value)) = req.extensions().get::<CachedValue<E>>() {
+            return Ok(Self(value.clone()));
+        }
+
+        let value = E::from_request(req).await?;
+        req.extensions_mut().insert(CachedValue(value.clone()));
+        Ok(Self(value))
+    }
+}
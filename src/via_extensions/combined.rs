@@ -0,0 +1,231 @@
+use super::{
+    config, Config, FirstOfConfig, FormConfig, GlobalRejectionConfig, JsonConfig, QueryConfig,
+};
+use axum::extract::RequestParts;
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    fmt,
+    sync::Arc,
+};
+
+/// Builder for a layer that adds several configs at once.
+///
+/// Adding [`JsonConfig`], [`QueryConfig`], [`FormConfig`], [`FirstOfConfig`], and
+/// [`GlobalRejectionConfig`] as separate layers wraps the router in a service per config, each of
+/// which adds its config to every request. The layer built here keeps all of them in a single
+/// shared map, which is added to requests at once.
+///
+/// Configs added with their own layer take precedence over the ones added here.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{
+///     ExtractorConfigBuilder,
+///     FormConfig,
+///     JsonConfig,
+///     QueryConfig,
+/// };
+/// use axum::{Router, routing::post, http::StatusCode};
+///
+/// let configs = ExtractorConfigBuilder::new()
+///     .json(JsonConfig::new().override_status(StatusCode::BAD_REQUEST))
+///     .query(QueryConfig::new().override_status(StatusCode::BAD_REQUEST))
+///     .form(FormConfig::new().override_status(StatusCode::BAD_REQUEST))
+///     .build_layer();
+///
+/// let app = Router::new()
+///     .route("/", post(|| async {}))
+///     .layer(configs);
+/// # let _: Router = app;
+/// ```
+pub struct ExtractorConfigBuilder<B> {
+    configs: Configs<B>,
+}
+
+impl<B> ExtractorConfigBuilder<B> {
+    /// Create a new `ExtractorConfigBuilder`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the config used by [`Json`](super::Json).
+    pub fn json(self, config: JsonConfig<B>) -> Self
+    where
+        B: 'static,
+    {
+        self.insert(config)
+    }
+
+    /// Set the config used by [`Query`](super::Query).
+    pub fn query(self, config: QueryConfig<B>) -> Self
+    where
+        B: 'static,
+    {
+        self.insert(config)
+    }
+
+    /// Set the config used by [`Form`](super::Form).
+    pub fn form(self, config: FormConfig<B>) -> Self
+    where
+        B: 'static,
+    {
+        self.insert(config)
+    }
+
+    /// Set the config used by [`FirstOf`](super::FirstOf).
+    pub fn first_of(self, config: FirstOfConfig<B>) -> Self
+    where
+        B: 'static,
+    {
+        self.insert(config)
+    }
+
+    /// Set the config used by all extractors.
+    pub fn global(self, config: GlobalRejectionConfig<B>) -> Self
+    where
+        B: 'static,
+    {
+        self.insert(config)
+    }
+
+    fn insert<T>(mut self, config: T) -> Self
+    where
+        T: CombinedConfig<B>,
+    {
+        self.configs.map.insert(
+            TypeId::of::<T>(),
+            (std::any::type_name::<T>(), Arc::new(config)),
+        );
+        self
+    }
+
+    /// Build the layer.
+    pub fn build_layer(self) -> ExtractorConfigLayer<B> {
+        ExtractorConfigLayer {
+            configs: Arc::new(self.configs),
+        }
+    }
+}
+
+impl<B> Default for ExtractorConfigBuilder<B> {
+    fn default() -> Self {
+        Self {
+            configs: Configs {
+                map: HashMap::new(),
+                _marker: std::marker::PhantomData,
+            },
+        }
+    }
+}
+
+impl<B> Clone for ExtractorConfigBuilder<B> {
+    fn clone(&self) -> Self {
+        Self {
+            configs: self.configs.clone(),
+        }
+    }
+}
+
+impl<B> fmt::Debug for ExtractorConfigBuilder<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExtractorConfigBuilder")
+            .field("configs", &self.configs)
+            .finish()
+    }
+}
+
+/// Layer that adds several configs at once.
+///
+/// Created with [`ExtractorConfigBuilder::build_layer`].
+pub struct ExtractorConfigLayer<B> {
+    configs: Arc<Configs<B>>,
+}
+
+impl<B> Clone for ExtractorConfigLayer<B> {
+    fn clone(&self) -> Self {
+        Self {
+            configs: self.configs.clone(),
+        }
+    }
+}
+
+impl<B> fmt::Debug for ExtractorConfigLayer<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExtractorConfigLayer")
+            .field("configs", &self.configs)
+            .finish()
+    }
+}
+
+impl<S, B> tower_layer::Layer<S> for ExtractorConfigLayer<B> {
+    type Service = <Config<Self, B> as tower_layer::Layer<S>>::Service;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        let config: Config<_, B> = Config::new(self.clone());
+        config.layer(inner)
+    }
+}
+
+// The configs, keyed by their type.
+#[allow(unreachable_pub)]
+pub struct Configs<B> {
+    map: HashMap<TypeId, (&'static str, Arc<dyn Any + Send + Sync>)>,
+    _marker: std::marker::PhantomData<fn() -> B>,
+}
+
+impl<B> Configs<B> {
+    fn get<T>(&self) -> Option<Arc<T>>
+    where
+        T: CombinedConfig<B>,
+    {
+        let (_, config) = self.map.get(&TypeId::of::<T>())?;
+        config.clone().downcast().ok()
+    }
+}
+
+impl<B> Clone for Configs<B> {
+    fn clone(&self) -> Self {
+        Self {
+            map: self.map.clone(),
+            _marker: self._marker,
+        }
+    }
+}
+
+impl<B> fmt::Debug for Configs<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.map.values().map(|(name, _)| name))
+            .finish()
+    }
+}
+
+// Config types that can be added with `ExtractorConfigBuilder` and `configured`.
+#[allow(unreachable_pub)]
+pub trait CombinedConfig<B>: Clone + Send + Sync + 'static {}
+
+impl<B: 'static> CombinedConfig<B> for JsonConfig<B> {}
+
+impl<B: 'static> CombinedConfig<B> for QueryConfig<B> {}
+
+impl<B: 'static> CombinedConfig<B> for FormConfig<B> {}
+
+impl<B: 'static> CombinedConfig<B> for FirstOfConfig<B> {}
+
+impl<B: 'static> CombinedConfig<B> for GlobalRejectionConfig<B> {}
+
+// Get a config added either with its own layer or with `ExtractorConfigBuilder`. The config is
+// shared with other requests rather than cloned.
+pub(super) fn get<T, B>(req: &RequestParts<B>) -> Option<Arc<T>>
+where
+    T: CombinedConfig<B>,
+    B: 'static,
+{
+    let extensions = req.extensions();
+    config::get_shared::<T, B>(extensions).or_else(|| {
+        config::get::<ExtractorConfigLayer<B>, B>(extensions)
+            .and_then(|layer| layer.get_ref().configs.get::<T>())
+    })
+}
@@ -0,0 +1,93 @@
+//! Support for axum 0.6.
+//!
+//! axum 0.5 and 0.6 use the same `http` and `hyper` versions, so the extractors run the axum 0.5
+//! implementation on a request rebuilt from the axum 0.6 request parts. Configs are added as
+//! layers the same way, with `hyper::Body` as the body type.
+
+use super::{
+    rejection::{ConfiguredRejection, QueryRejection},
+    Query,
+};
+use axum::{
+    body::Body,
+    extract::{FromRequest, RequestParts},
+    http::{request::Parts, Request},
+    response::IntoResponse,
+};
+use axum_06::{async_trait, extract::FromRequestParts};
+use serde::de::DeserializeOwned;
+
+#[async_trait]
+impl<T, S> FromRequestParts<S> for Query<T>
+where
+    T: DeserializeOwned + Send + 'static,
+    S: Send + Sync,
+{
+    type Rejection = ConfiguredRejection<QueryRejection>;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let mut req = Request::new(Body::empty());
+        *req.method_mut() = parts.method.clone();
+        *req.uri_mut() = parts.uri.clone();
+        *req.version_mut() = parts.version;
+        *req.headers_mut() = std::mem::take(&mut parts.headers);
+        *req.extensions_mut() = std::mem::take(&mut parts.extensions);
+
+        let mut req = RequestParts::new(req);
+        let result = <Self as FromRequest<Body>>::from_request(&mut req).await;
+
+        // give back the parts the extractor might have changed
+        parts.headers = std::mem::take(req.headers_mut());
+        parts.extensions = std::mem::take(req.extensions_mut());
+
+        result
+    }
+}
+
+impl<R> axum_06::response::IntoResponse for ConfiguredRejection<R>
+where
+    R: IntoResponse + std::fmt::Display,
+{
+    fn into_response(self) -> axum_06::response::Response {
+        IntoResponse::into_response(self).map(axum_06::body::boxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::via_extensions::{Query, QueryConfig};
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use axum_06::{routing::get, Router};
+    use serde::Deserialize;
+    use tower::ServiceExt;
+
+    #[derive(Deserialize)]
+    struct Params {
+        #[allow(dead_code)]
+        id: u32,
+    }
+
+    #[tokio::test]
+    async fn query_from_request_parts() {
+        async fn handler(Query(_): Query<Params>) {}
+
+        let app = Router::new()
+            .route("/", get(handler))
+            .layer(QueryConfig::<Body>::new().rejection_handler(|_, _| StatusCode::IM_A_TEAPOT));
+
+        for (uri, status) in [
+            ("/?id=1", StatusCode::OK),
+            ("/?id=foo", StatusCode::IM_A_TEAPOT),
+        ] {
+            let res = app
+                .clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(res.status(), status);
+        }
+    }
+}
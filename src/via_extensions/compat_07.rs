@@ -0,0 +1,289 @@
+//! Support for axum 0.7.
+//!
+//! axum 0.7 uses `http` 1.0 and `http-body` 1.0, while the extractors are implemented with axum
+//! 0.5. This module adapts between the two, so the configs are the same for both versions:
+//!
+//! - Config layers insert their configs into `http` 1.0 requests.
+//! - The extractors copy the method, URI, headers, and configs into an axum 0.5 request, run the
+//!   axum 0.5 implementation, and convert the rejection response back.
+
+use super::{
+    config::{
+        AddConfig, ConfigError, ConfigErrorBody, ConfigErrorResponse, DefaultConfigErrorResponse,
+        RequestConfigs,
+    },
+    rejection::{ConfiguredRejection, QueryRejection},
+    Config, Query, RejectionInfo,
+};
+use axum::{
+    body::Bytes,
+    extract::{FromRequest, RequestParts},
+    http,
+    response::IntoResponse,
+};
+use axum_07::{async_trait, extract::FromRequestParts};
+use futures_util::{future::Either, FutureExt};
+use serde::de::DeserializeOwned;
+use std::{
+    future::{ready, Ready},
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+use tower_service::Service;
+
+/// The body type of configs used with axum 0.7.
+///
+/// The extractors run their axum 0.5 implementation, so configs added to an axum 0.7 router are
+/// looked up with this body type, for example `QueryConfig::<CompatBody>::new()`.
+///
+/// Requires the `axum-07` feature.
+pub type CompatBody = axum::body::Body;
+
+impl<S, T, B, E, ReqBody> Service<http_1::Request<ReqBody>> for AddConfig<S, T, B, E>
+where
+    S: Service<http_1::Request<ReqBody>>,
+    T: Send + Sync + 'static,
+    B: Send + 'static,
+    E: ConfigErrorResponse<S::Response>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Either<S::Future, Ready<Result<S::Response, S::Error>>>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner_mut().poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: http_1::Request<ReqBody>) -> Self::Future {
+        let existing = if self.checks_existing() {
+            req.extensions()
+                .get::<RequestConfigs>()
+                .and_then(RequestConfigs::get::<Config<T, B>>)
+        } else {
+            None
+        };
+        match self.check_existing(existing) {
+            Ok(()) => {
+                if let Some(configs) = self.add_config(req.extensions_mut().get_mut()) {
+                    req.extensions_mut().insert(configs);
+                }
+                self.inner_mut().call(req).left_future()
+            }
+            Err(err) => ready(Ok(self.on_error().respond(err))).right_future(),
+        }
+    }
+}
+
+impl<B> ConfigErrorResponse<http_1::Response<B>> for DefaultConfigErrorResponse
+where
+    B: ConfigErrorBody,
+{
+    fn respond(&self, err: ConfigError) -> http_1::Response<B> {
+        let mut res = http_1::Response::new(B::from_message(err.to_string()));
+        *res.status_mut() = http_1::StatusCode::INTERNAL_SERVER_ERROR;
+        res.headers_mut().insert(
+            http_1::header::CONTENT_TYPE,
+            http_1::HeaderValue::from_static("text/plain; charset=utf-8"),
+        );
+        res
+    }
+}
+
+impl ConfigErrorBody for axum_07::body::Body {
+    fn from_message(message: String) -> Self {
+        axum_07::body::Body::from(message)
+    }
+}
+
+#[async_trait]
+impl<T, S> FromRequestParts<S> for Query<T>
+where
+    T: DeserializeOwned + Send + 'static,
+    S: Send + Sync,
+{
+    type Rejection = ConfiguredRejection<QueryRejection>;
+
+    async fn from_request_parts(
+        parts: &mut http_1::request::Parts,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let mut req = RequestParts::new(compat_request(parts));
+        <Self as FromRequest<CompatBody>>::from_request(&mut req).await
+    }
+}
+
+impl<R> axum_07::response::IntoResponse for ConfiguredRejection<R>
+where
+    R: IntoResponse + std::fmt::Display,
+{
+    fn into_response(self) -> axum_07::response::Response {
+        let (parts, body) = IntoResponse::into_response(self).into_parts();
+
+        let mut res = http_1::Response::new(axum_07::body::Body::new(BodyAdapter::new(body)));
+        *res.status_mut() = http_1::StatusCode::from_u16(parts.status.as_u16())
+            .expect("status codes are valid in both versions");
+        *res.headers_mut() = convert_headers(&parts.headers);
+        if let Some(info) = parts.extensions.get::<RejectionInfo>() {
+            res.extensions_mut().insert(info.clone());
+        }
+        res
+    }
+}
+
+// Build an axum 0.5 request from the parts of an axum 0.7 request, with the configs added by
+// layers.
+fn compat_request(parts: &http_1::request::Parts) -> http::Request<CompatBody> {
+    let mut req = http::Request::new(CompatBody::empty());
+    *req.method_mut() = http::Method::from_bytes(parts.method.as_str().as_bytes())
+        .expect("methods are valid in both versions");
+    *req.uri_mut() = parts
+        .uri
+        .to_string()
+        .parse()
+        .expect("URIs are valid in both versions");
+    *req.version_mut() = match parts.version {
+        http_1::Version::HTTP_09 => http::Version::HTTP_09,
+        http_1::Version::HTTP_10 => http::Version::HTTP_10,
+        http_1::Version::HTTP_2 => http::Version::HTTP_2,
+        http_1::Version::HTTP_3 => http::Version::HTTP_3,
+        _ => http::Version::HTTP_11,
+    };
+    for (name, value) in &parts.headers {
+        if let (Ok(name), Ok(value)) = (
+            http::HeaderName::from_bytes(name.as_str().as_bytes()),
+            http::HeaderValue::from_bytes(value.as_bytes()),
+        ) {
+            req.headers_mut().append(name, value);
+        }
+    }
+
+    copy_extension::<RequestConfigs>(parts, &mut req);
+
+    req
+}
+
+fn copy_extension<T>(parts: &http_1::request::Parts, req: &mut http::Request<CompatBody>)
+where
+    T: Clone + Send + Sync + 'static,
+{
+    if let Some(value) = parts.extensions.get::<T>() {
+        req.extensions_mut().insert(value.clone());
+    }
+}
+
+fn convert_headers(headers: &http::HeaderMap) -> http_1::HeaderMap {
+    let mut converted = http_1::HeaderMap::with_capacity(headers.len());
+    for (name, value) in headers {
+        if let (Ok(name), Ok(value)) = (
+            http_1::HeaderName::from_bytes(name.as_str().as_bytes()),
+            http_1::HeaderValue::from_bytes(value.as_bytes()),
+        ) {
+            converted.append(name, value);
+        }
+    }
+    converted
+}
+
+// Implements `http-body` 1.0 for an `http-body` 0.4 body.
+struct BodyAdapter<B> {
+    body: B,
+    state: BodyState,
+}
+
+enum BodyState {
+    Data,
+    Trailers,
+    Done,
+}
+
+impl<B> BodyAdapter<B> {
+    fn new(body: B) -> Self {
+        Self {
+            body,
+            state: BodyState::Data,
+        }
+    }
+}
+
+impl<B> http_body_1::Body for BodyAdapter<B>
+where
+    B: http_body::Body<Data = Bytes> + Unpin,
+{
+    type Data = Bytes;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<http_body_1::Frame<Bytes>, Self::Error>>> {
+        let this = self.get_mut();
+        loop {
+            match this.state {
+                BodyState::Data => match ready!(Pin::new(&mut this.body).poll_data(cx)) {
+                    Some(data) => return Poll::Ready(Some(data.map(http_body_1::Frame::data))),
+                    None => this.state = BodyState::Trailers,
+                },
+                BodyState::Trailers => {
+                    let trailers = ready!(Pin::new(&mut this.body).poll_trailers(cx));
+                    this.state = BodyState::Done;
+                    match trailers {
+                        Ok(Some(trailers)) => {
+                            let trailers = convert_headers(&trailers);
+                            return Poll::Ready(Some(Ok(http_body_1::Frame::trailers(trailers))));
+                        }
+                        Ok(None) => return Poll::Ready(None),
+                        Err(err) => return Poll::Ready(Some(Err(err))),
+                    }
+                }
+                BodyState::Done => return Poll::Ready(None),
+            }
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        matches!(self.state, BodyState::Done)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CompatBody;
+    use crate::via_extensions::{Query, QueryConfig};
+    use axum_07::{body::Body, routing::get, Router};
+    use http_1::{Request, StatusCode};
+    use serde::Deserialize;
+    use tower::ServiceExt;
+
+    #[derive(Deserialize)]
+    struct Params {
+        #[allow(dead_code)]
+        id: u32,
+    }
+
+    #[tokio::test]
+    async fn query_from_request_parts() {
+        async fn handler(Query(_): Query<Params>) {}
+
+        let app = Router::new().route("/", get(handler)).layer(
+            QueryConfig::<CompatBody>::new()
+                .rejection_handler(|_, _| (axum::http::StatusCode::IM_A_TEAPOT, "teapot")),
+        );
+
+        for (uri, status, body) in [
+            ("/?id=1", StatusCode::OK, ""),
+            ("/?id=foo", StatusCode::IM_A_TEAPOT, "teapot"),
+        ] {
+            let res = app
+                .clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(res.status(), status);
+            let bytes = axum_07::body::to_bytes(res.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            assert_eq!(bytes, body);
+        }
+    }
+}
@@ -1,29 +1,91 @@
+use super::{combined::CombinedConfig, rejection::DetailLevel, RejectionContext};
 use axum::{
     async_trait,
+    body::{Body, Bytes, Full, HttpBody},
     extract::{FromRequest, RequestParts},
-    http::{Request, StatusCode},
-    response::{IntoResponse, Response},
+    handler::Handler,
+    http::{self, header, HeaderValue, Request, StatusCode},
+    response::IntoResponse,
     Extension,
 };
-use futures_util::{
-    future::{Either, MapOk, TryFutureExt},
-    FutureExt,
-};
+use futures_util::{future::Either, FutureExt};
+use http_body::combinators::UnsyncBoxBody;
 use std::{
+    any::Any,
     fmt,
     future::{ready, Ready},
     marker::PhantomData,
+    sync::{Arc, OnceLock},
     task::{Context, Poll},
 };
 use tower_layer::Layer;
 use tower_service::Service;
 
+/// Settings shared by [`JsonConfig`], [`QueryConfig`], and [`FormConfig`].
+///
+/// This allows writing helpers that work with any of the configs.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{
+///     rejection::DetailLevel,
+///     ExtractorConfig,
+///     JsonConfig,
+///     QueryConfig,
+/// };
+/// use axum::{body::Body, http::StatusCode, Router};
+///
+/// fn install<C>(router: Router, config: C) -> Router
+/// where
+///     C: ExtractorConfig<Body>,
+/// {
+///     let config = config
+///         .override_status(StatusCode::BAD_REQUEST)
+///         .detail_level(DetailLevel::Message);
+///
+///     router.layer(config.into_config())
+/// }
+///
+/// let app = install(Router::new(), JsonConfig::new());
+/// let app = install(app, QueryConfig::new());
+/// # let _: Router = app;
+/// ```
+///
+/// [`JsonConfig`]: super::JsonConfig
+/// [`QueryConfig`]: super::QueryConfig
+/// [`FormConfig`]: super::FormConfig
+pub trait ExtractorConfig<B>: Clone + fmt::Debug + Send + Sync + 'static {
+    /// The rejection the extractor fails with.
+    type Rejection;
+
+    /// Set the rejection handler function.
+    fn rejection_handler<F, R>(self, f: F) -> Self
+    where
+        F: Fn(Self::Rejection, RejectionContext<'_, B>) -> R + Send + Sync + 'static,
+        R: IntoResponse;
+
+    /// Set the status code of the default rejection responses.
+    fn override_status(self, status: StatusCode) -> Self;
+
+    /// Set how much detail the default rejection responses include.
+    fn detail_level(self, detail_level: DetailLevel) -> Self;
+
+    /// Convert into a [`Config`], which can be added as a layer.
+    fn into_config(self) -> Config<Self, B>;
+}
+
 /// Configuration [extractor] and [layer].
 ///
+/// The config is kept behind an `Arc`, so adding it to requests doesn't clone it.
+///
 /// [extractor]: FromRequest
 /// [layer]: Layer
 pub struct Config<T, B> {
-    config: T,
+    config: Arc<T>,
+    allow_override: bool,
+    replace_existing: bool,
+    checked: bool,
     _marker: PhantomData<fn() -> B>,
 }
 
@@ -31,14 +93,64 @@ impl<T, B> Config<T, B> {
     /// Create a new `Config`.
     pub fn new(config: T) -> Self {
         Self {
-            config,
+            config: Arc::new(config),
+            allow_override: false,
+            replace_existing: false,
+            checked: true,
             _marker: PhantomData,
         }
     }
 
+    /// Allow layers further in to override this config.
+    ///
+    /// By default adding a config of a type that was already added by a layer further out, for
+    /// example in a router passed to `Router::nest`, results in a `500 Internal Server Error`.
+    /// With this the innermost config is used instead, so routers from other crates can bring
+    /// their own config.
+    pub fn allow_override(mut self) -> Self {
+        self.allow_override = true;
+        self
+    }
+
+    // Replace configs added further out, even if they don't allow it.
+    fn replace_existing(mut self) -> Self {
+        self.replace_existing = true;
+        self
+    }
+
+    /// Skip checking whether the config was already added, in release builds.
+    ///
+    /// Checking requires looking up the config in the request extensions on every request. With
+    /// this, release builds skip the check and the innermost config is used if the config is added
+    /// more than once, while debug builds still catch mistakes.
+    ///
+    /// The configs can be converted into a `Config` with
+    /// [`ExtractorConfig::into_config`].
+    pub fn unchecked(mut self) -> Self {
+        self.checked = false;
+        self
+    }
+
+    /// Get a reference to the inner value.
+    pub fn get_ref(&self) -> &T {
+        &self.config
+    }
+
+    // Get the inner value, shared rather than cloned.
+    pub(crate) fn shared(&self) -> Arc<T> {
+        self.config.clone()
+    }
+}
+
+impl<T, B> Config<T, B>
+where
+    T: Clone,
+{
     /// Consume the config and get the inner value.
+    ///
+    /// The value is cloned if it's shared with other requests.
     pub fn into_inner(self) -> T {
-        self.config
+        Arc::try_unwrap(self.config).unwrap_or_else(|config| T::clone(&config))
     }
 }
 
@@ -49,6 +161,9 @@ where
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Config")
             .field("config", &self.config)
+            .field("allow_override", &self.allow_override)
+            .field("replace_existing", &self.replace_existing)
+            .field("checked", &self.checked)
             .field("_marker", &self._marker)
             .finish()
     }
@@ -60,21 +175,22 @@ where
 {
     fn default() -> Self {
         Self {
-            config: Default::default(),
+            config: Arc::default(),
+            allow_override: false,
+            replace_existing: false,
+            checked: true,
             _marker: Default::default(),
         }
     }
 }
 
-impl<T, B> Copy for Config<T, B> where T: Copy {}
-
-impl<T, B> Clone for Config<T, B>
-where
-    T: Clone,
-{
+impl<T, B> Clone for Config<T, B> {
     fn clone(&self) -> Self {
         Self {
             config: self.config.clone(),
+            allow_override: self.allow_override,
+            replace_existing: self.replace_existing,
+            checked: self.checked,
             _marker: self._marker,
         }
     }
@@ -83,40 +199,257 @@ where
 #[async_trait]
 impl<T, B> FromRequest<B> for Config<T, B>
 where
-    T: Clone + Send + Sync + 'static,
+    T: Send + Sync + 'static,
     B: Send + 'static,
 {
     type Rejection = <Extension<Self> as FromRequest<B>>::Rejection;
 
     async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        if let Some(config) = get::<T, B>(req.extensions()) {
+            return Ok(config.clone());
+        }
+
+        // go through `Extension` to get its rejection
         let Extension(config) = req.extract::<Extension<Self>>().await?;
         Ok(config)
     }
 }
 
-impl<S, T, B> Layer<S> for Config<T, B>
+// The configs added to a request by config layers.
+//
+// Every layer adds its config to this, so requests have a single extension however many configs
+// are added. The configs are kept in a list from the innermost layer out, whose entries are shared
+// by the requests that pass through the same layers.
+#[derive(Clone)]
+pub(crate) struct RequestConfigs {
+    innermost: Arc<Entry<dyn Any + Send + Sync>>,
+}
+
+struct Entry<T: ?Sized> {
+    outer: Option<Arc<Entry<dyn Any + Send + Sync>>>,
+    config: T,
+}
+
+impl RequestConfigs {
+    // `configs` with `config` added further in than the others.
+    pub(crate) fn with_config<T, B>(configs: Option<Self>, config: Config<T, B>) -> Self
+    where
+        T: Send + Sync + 'static,
+        B: 'static,
+    {
+        Self {
+            innermost: Arc::new(Entry {
+                outer: configs.map(|configs| configs.innermost),
+                config,
+            }),
+        }
+    }
+
+    pub(crate) fn get<T>(&self) -> Option<&T>
+    where
+        T: 'static,
+    {
+        std::iter::successors(Some(&*self.innermost), |entry| entry.outer.as_deref())
+            .find_map(|entry| entry.config.downcast_ref())
+    }
+}
+
+// Get the config of type `T` added to a request, by a layer or directly as an extension.
+pub(crate) fn get<T, B>(extensions: &http::Extensions) -> Option<&Config<T, B>>
 where
-    T: Clone,
+    T: Send + Sync + 'static,
+    B: 'static,
+{
+    extensions
+        .get::<RequestConfigs>()
+        .and_then(RequestConfigs::get)
+        .or_else(|| extensions.get())
+}
+
+// Like `get`, for the inner value, which is shared rather than cloned.
+pub(crate) fn get_shared<T, B>(extensions: &http::Extensions) -> Option<Arc<T>>
+where
+    T: Send + Sync + 'static,
+    B: 'static,
 {
+    get::<T, B>(extensions).map(Config::shared)
+}
+
+impl<S, T, B> Layer<S> for Config<T, B> {
     type Service = AddConfig<S, T, B>;
 
     fn layer(&self, inner: S) -> Self::Service {
+        self.add_config(inner, DefaultConfigErrorResponse)
+    }
+}
+
+impl<T, B> Config<T, B> {
+    /// Respond with `f` if the config was already added to a request.
+    ///
+    /// By default the response body is created with [`ConfigErrorBody`]. With this the layer can
+    /// wrap services whose response body doesn't implement it, such as bodies from other crates.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use axum_extractor_config::via_extensions::{ConfigError, ExtractorConfig, JsonConfig};
+    /// use axum::{
+    ///     body::{Body, Bytes},
+    ///     http::{Request, Response, StatusCode},
+    /// };
+    /// use http_body::{combinators::BoxBody, Body as _, Full};
+    /// use std::convert::Infallible;
+    /// use tower::ServiceBuilder;
+    ///
+    /// fn respond(err: ConfigError) -> Response<BoxBody<Bytes, Infallible>> {
+    ///     let mut res = Response::new(Full::from(err.to_string()).boxed());
+    ///     *res.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+    ///     res
+    /// }
+    ///
+    /// let svc = ServiceBuilder::new()
+    ///     .layer(JsonConfig::<Body>::new().into_config().on_config_error(respond))
+    ///     .service_fn(|_: Request<Body>| async {
+    ///         Ok::<_, Infallible>(Response::new(Full::<Bytes>::from("ok").boxed()))
+    ///     });
+    /// # let _ = svc;
+    /// ```
+    pub fn on_config_error<F, Res>(self, f: F) -> OnConfigError<T, B, F>
+    where
+        F: Fn(ConfigError) -> Res,
+    {
+        OnConfigError { config: self, f }
+    }
+
+    fn add_config<S, E>(&self, inner: S, on_error: E) -> AddConfig<S, T, B, E> {
         AddConfig {
             inner,
             config: self.config.clone(),
+            allow_override: self.allow_override,
+            replace_existing: self.replace_existing,
+            checked: self.checked,
+            entry: Arc::default(),
+            on_error,
             _marker: self._marker,
         }
     }
 }
 
+/// Config [layer] that responds with a function if the config was already added to a request.
+///
+/// Created with [`Config::on_config_error`].
+///
+/// [layer]: Layer
+pub struct OnConfigError<T, B, F> {
+    config: Config<T, B>,
+    f: F,
+}
+
+impl<T, B, F> Clone for OnConfigError<T, B, F>
+where
+    F: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            config: self.config.clone(),
+            f: self.f.clone(),
+        }
+    }
+}
+
+impl<T, B, F> fmt::Debug for OnConfigError<T, B, F>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OnConfigError")
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl<S, T, B, F> Layer<S> for OnConfigError<T, B, F>
+where
+    F: Clone,
+{
+    type Service = AddConfig<S, T, B, F>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        self.config.add_config(inner, self.f.clone())
+    }
+}
+
+/// The error of a config layer whose config was already added to a request.
+///
+/// Config layers respond with `500 Internal Server Error` and this error's message, or with the
+/// function given to [`Config::on_config_error`].
+#[derive(Debug, Clone)]
+pub struct ConfigError {
+    type_name: &'static str,
+}
+
+impl ConfigError {
+    /// The name of the config's type, as returned by [`std::any::type_name`].
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Config of type {:?} was already added. Configs can you be added once",
+            self.type_name
+        )
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+// How config layers respond to a `ConfigError`.
 #[allow(unreachable_pub)]
-pub struct AddConfig<S, T, B> {
+pub trait ConfigErrorResponse<Res> {
+    fn respond(&self, err: ConfigError) -> Res;
+}
+
+impl<F, Res> ConfigErrorResponse<Res> for F
+where
+    F: Fn(ConfigError) -> Res,
+{
+    fn respond(&self, err: ConfigError) -> Res {
+        self(err)
+    }
+}
+
+// Respond with a body created with `ConfigErrorBody`.
+#[allow(unreachable_pub)]
+#[derive(Debug, Clone, Copy)]
+pub struct DefaultConfigErrorResponse;
+
+impl<B> ConfigErrorResponse<http::Response<B>> for DefaultConfigErrorResponse
+where
+    B: ConfigErrorBody,
+{
+    fn respond(&self, err: ConfigError) -> http::Response<B> {
+        config_error_response(err.to_string())
+    }
+}
+
+#[allow(unreachable_pub)]
+pub struct AddConfig<S, T, B, E = DefaultConfigErrorResponse> {
     inner: S,
-    config: T,
+    config: Arc<T>,
+    allow_override: bool,
+    replace_existing: bool,
+    checked: bool,
+    // the entry added to the first request, reused for requests with the same configs further out
+    entry: Arc<OnceLock<Arc<Entry<dyn Any + Send + Sync>>>>,
+    on_error: E,
     _marker: PhantomData<fn() -> B>,
 }
 
-impl<S, T, B> fmt::Debug for AddConfig<S, T, B>
+impl<S, T, B, E> fmt::Debug for AddConfig<S, T, B, E>
 where
     S: fmt::Debug,
     T: fmt::Debug,
@@ -125,42 +458,42 @@ where
         f.debug_struct("AddConfig")
             .field("inner", &self.inner)
             .field("config", &self.config)
+            .field("allow_override", &self.allow_override)
+            .field("replace_existing", &self.replace_existing)
+            .field("checked", &self.checked)
             .finish()
     }
 }
 
-impl<S, T, B> Copy for AddConfig<S, T, B>
-where
-    S: Copy,
-    T: Copy,
-{
-}
-
-impl<S, T, B> Clone for AddConfig<S, T, B>
+impl<S, T, B, E> Clone for AddConfig<S, T, B, E>
 where
     S: Clone,
-    T: Clone,
+    E: Clone,
 {
     fn clone(&self) -> Self {
         Self {
             inner: self.inner.clone(),
             config: self.config.clone(),
+            allow_override: self.allow_override,
+            replace_existing: self.replace_existing,
+            checked: self.checked,
+            entry: self.entry.clone(),
+            on_error: self.on_error.clone(),
             _marker: self._marker,
         }
     }
 }
 
-impl<S, T, B> Service<Request<B>> for AddConfig<S, T, B>
+impl<S, T, B, E> Service<Request<B>> for AddConfig<S, T, B, E>
 where
     S: Service<Request<B>>,
-    S::Response: IntoResponse,
-    T: Clone + Send + Sync + 'static,
+    T: Send + Sync + 'static,
     B: Send + 'static,
+    E: ConfigErrorResponse<S::Response>,
 {
-    type Response = Response;
+    type Response = S::Response;
     type Error = S::Error;
-    type Future =
-        Either<MapOk<S::Future, fn(S::Response) -> Response>, Ready<Result<Response, S::Error>>>;
+    type Future = Either<S::Future, Ready<Result<S::Response, S::Error>>>;
 
     #[inline]
     fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
@@ -168,25 +501,352 @@ where
     }
 
     fn call(&mut self, mut req: Request<B>) -> Self::Future {
-        if req.extensions().get::<Config<T, B>>().is_some() {
-            ready(Ok((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!(
-                    "Config of type {:?} was already added. Configs can you be added once",
-                    std::any::type_name::<T>()
-                ),
-            )
-                .into_response()))
-            .right_future()
+        let existing = if self.checks_existing() {
+            get::<T, B>(req.extensions())
         } else {
-            req.extensions_mut().insert(Config::<_, B> {
+            None
+        };
+        if let Err(err) = self.check_existing(existing) {
+            return ready(Ok(self.on_error.respond(err))).right_future();
+        }
+
+        if let Some(configs) = self.add_config(req.extensions_mut().get_mut()) {
+            req.extensions_mut().insert(configs);
+        }
+        self.inner.call(req).left_future()
+    }
+}
+
+impl<S, T, B, E> AddConfig<S, T, B, E> {
+    // Whether to look up the config in the request to check it wasn't already added. Unchecked
+    // configs skip the lookup entirely in release builds.
+    pub(super) fn checks_existing(&self) -> bool {
+        (self.checked || cfg!(debug_assertions)) && !self.replace_existing
+    }
+
+    // Check whether the config can be added to a request that has `existing`.
+    pub(super) fn check_existing(
+        &self,
+        existing: Option<&Config<T, B>>,
+    ) -> Result<(), ConfigError> {
+        if self.checks_existing() && matches!(existing, Some(config) if !config.allow_override) {
+            return Err(ConfigError {
+                type_name: std::any::type_name::<T>(),
+            });
+        }
+        Ok(())
+    }
+
+    // Add the config to the configs of a request. Returns the configs to insert into the request
+    // if it has none yet.
+    pub(super) fn add_config(&self, configs: Option<&mut RequestConfigs>) -> Option<RequestConfigs>
+    where
+        T: Send + Sync + 'static,
+        B: 'static,
+    {
+        match configs {
+            Some(configs) => {
+                configs.innermost = self.entry(Some(&configs.innermost));
+                None
+            }
+            None => Some(RequestConfigs {
+                innermost: self.entry(None),
+            }),
+        }
+    }
+
+    // The entry for this layer's config, in front of the configs added further out. The entry of
+    // the first request is reused while the configs further out are the same, which they are for
+    // all requests unless they're added conditionally.
+    fn entry(
+        &self,
+        outer: Option<&Arc<Entry<dyn Any + Send + Sync>>>,
+    ) -> Arc<Entry<dyn Any + Send + Sync>>
+    where
+        T: Send + Sync + 'static,
+        B: 'static,
+    {
+        if let Some(entry) = self.entry.get() {
+            let same_outer = match (&entry.outer, outer) {
+                (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+                (a, b) => a.is_none() && b.is_none(),
+            };
+            if same_outer {
+                return entry.clone();
+            }
+        }
+
+        let entry: Arc<Entry<dyn Any + Send + Sync>> = Arc::new(Entry {
+            outer: outer.cloned(),
+            config: Config::<T, B> {
                 config: self.config.clone(),
+                allow_override: self.allow_override,
+                replace_existing: false,
+                checked: self.checked,
                 _marker: PhantomData,
+            },
+        });
+        let _ = self.entry.set(entry.clone());
+        entry
+    }
+
+    #[cfg(feature = "axum-07")]
+    pub(super) fn inner_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    #[cfg(feature = "axum-07")]
+    pub(super) fn on_error(&self) -> &E {
+        &self.on_error
+    }
+}
+
+// The `500 Internal Server Error` response for a config that was added more than once.
+fn config_error_response<B>(message: String) -> http::Response<B>
+where
+    B: ConfigErrorBody,
+{
+    let mut res = http::Response::new(B::from_message(message));
+    *res.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+    res.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("text/plain; charset=utf-8"),
+    );
+    res
+}
+
+/// Response bodies that [`Config`] layers can respond with.
+///
+/// The layers pass the responses of the inner service through unchanged, except when a config is
+/// added more than once. They then respond with `500 Internal Server Error` and a body containing
+/// the error message, so the layers can wrap any service whose response body implements this
+/// trait. Implement it for your own body types to use the layers with them, or respond with
+/// [`Config::on_config_error`] instead.
+pub trait ConfigErrorBody {
+    /// Create a body containing `message`.
+    fn from_message(message: String) -> Self;
+}
+
+impl<E> ConfigErrorBody for UnsyncBoxBody<Bytes, E> {
+    fn from_message(message: String) -> Self {
+        Full::from(message)
+            .map_err(|never| match never {})
+            .boxed_unsync()
+    }
+}
+
+impl ConfigErrorBody for Body {
+    fn from_message(message: String) -> Self {
+        Body::from(message)
+    }
+}
+
+impl ConfigErrorBody for Full<Bytes> {
+    fn from_message(message: String) -> Self {
+        Full::from(message)
+    }
+}
+
+impl ConfigErrorBody for String {
+    fn from_message(message: String) -> Self {
+        message
+    }
+}
+
+/// Apply a config to a single handler.
+///
+/// This is useful when one route needs a different config than the rest of the router. The
+/// config replaces one of the same type added to the router, even if that one doesn't
+/// [allow overrides](Config::allow_override).
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{configured, Json, JsonConfig};
+/// use axum::{Router, routing::post, http::StatusCode};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Payload {}
+///
+/// async fn handler(Json(payload): Json<Payload>) {}
+///
+/// async fn legacy_handler(Json(payload): Json<Payload>) {}
+///
+/// let legacy_config = JsonConfig::new().override_status(StatusCode::BAD_REQUEST);
+///
+/// let app = Router::new()
+///     .route("/", post(handler))
+///     .route("/legacy", post(configured(legacy_handler, legacy_config)))
+///     .layer(JsonConfig::new());
+/// # let _: Router = app;
+/// ```
+pub fn configured<H, T, C, B>(handler: H, config: C) -> impl Handler<T, B>
+where
+    H: Handler<T, B>,
+    T: 'static,
+    C: CombinedConfig<B>,
+    B: Send + 'static,
+{
+    handler.layer(Config::<_, B>::new(config).replace_existing())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::via_extensions::{
+        ExtractorConfig, ExtractorConfigBuilder, FormConfig, JsonConfig, QueryConfig,
+    };
+    use std::convert::Infallible;
+    use tower::{ServiceBuilder, ServiceExt};
+    use tower_http::map_response_body::MapResponseBodyLayer;
+
+    #[tokio::test]
+    async fn configs_added_with_one_extension() {
+        let svc = ServiceBuilder::new()
+            .layer(ExtractorConfigBuilder::<Body>::new().build_layer())
+            .layer(JsonConfig::<Body>::new())
+            .layer(QueryConfig::<Body>::new())
+            .layer(FormConfig::<Body>::new())
+            .service_fn(|req: Request<Body>| async move {
+                let extensions = req.extensions();
+                assert_eq!(extensions.len(), 1);
+                assert!(get::<JsonConfig<Body>, Body>(extensions).is_some());
+                assert!(get::<QueryConfig<Body>, Body>(extensions).is_some());
+                assert!(get::<FormConfig<Body>, Body>(extensions).is_some());
+                let mut res = http::Response::new(Body::empty());
+                res.extensions_mut()
+                    .insert(extensions.get::<RequestConfigs>().unwrap().clone());
+                Ok::<_, std::convert::Infallible>(res)
             });
-            self.inner
-                .call(req)
-                .map_ok(IntoResponse::into_response as _)
-                .left_future()
-        }
+
+        let first = svc
+            .clone()
+            .oneshot(Request::new(Body::empty()))
+            .await
+            .unwrap();
+        let second = svc.oneshot(Request::new(Body::empty())).await.unwrap();
+        // requests through the same layers share the configs
+        let configs = |res: &http::Response<Body>| {
+            res.extensions()
+                .get::<RequestConfigs>()
+                .unwrap()
+                .innermost
+                .clone()
+        };
+        assert!(Arc::ptr_eq(&configs(&first), &configs(&second)));
+    }
+
+    #[tokio::test]
+    async fn on_config_error_with_other_body() {
+        // a body that doesn't implement `ConfigErrorBody`
+        struct OtherBody(String);
+
+        let respond = |err: ConfigError| {
+            let mut res = http::Response::new(OtherBody(err.to_string()));
+            *res.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+            res
+        };
+        let handler = ServiceBuilder::new()
+            .layer(MapResponseBodyLayer::new(OtherBody))
+            .service_fn(|_: Request<Body>| async {
+                Ok::<_, Infallible>(http::Response::new(String::from("ok")))
+            });
+
+        let res = ServiceBuilder::new()
+            .layer(
+                JsonConfig::<Body>::new()
+                    .into_config()
+                    .on_config_error(respond),
+            )
+            .service(handler.clone())
+            .oneshot(Request::new(Body::empty()))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.body().0, "ok");
+
+        let res = ServiceBuilder::new()
+            .layer(
+                JsonConfig::<Body>::new()
+                    .into_config()
+                    .on_config_error(respond),
+            )
+            .layer(
+                JsonConfig::<Body>::new()
+                    .into_config()
+                    .on_config_error(respond),
+            )
+            .service(handler)
+            .oneshot(Request::new(Body::empty()))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(
+            res.body().0,
+            ConfigError {
+                type_name: std::any::type_name::<JsonConfig<Body>>(),
+            }
+            .to_string(),
+        );
+    }
+
+    #[tokio::test]
+    async fn config_from_request() {
+        let config = JsonConfig::<Body>::new().into_config();
+
+        // added by a layer, found in `RequestConfigs`
+        let svc = ServiceBuilder::new().layer(config.clone()).service_fn(
+            |req: Request<Body>| async move {
+                let mut req = RequestParts::new(req);
+                let config = Config::<JsonConfig<Body>, Body>::from_request(&mut req)
+                    .await
+                    .unwrap();
+                let mut res = http::Response::new(Body::empty());
+                res.extensions_mut().insert(config);
+                Ok::<_, Infallible>(res)
+            },
+        );
+        let res = svc.oneshot(Request::new(Body::empty())).await.unwrap();
+        let found = res
+            .extensions()
+            .get::<Config<JsonConfig<Body>, Body>>()
+            .unwrap();
+        assert!(Arc::ptr_eq(&found.config, &config.config));
+
+        // inserted directly as an extension
+        let mut req = Request::new(Body::empty());
+        req.extensions_mut().insert(config.clone());
+        let mut req = RequestParts::new(req);
+        let found = Config::<JsonConfig<Body>, Body>::from_request(&mut req)
+            .await
+            .unwrap();
+        assert!(Arc::ptr_eq(&found.config, &config.config));
+
+        // missing, rejected by `Extension`
+        let mut req = RequestParts::new(Request::new(Body::empty()));
+        let rejection = Config::<JsonConfig<Body>, Body>::from_request(&mut req)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            rejection.into_response().status(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[test]
+    fn unchecked_skips_lookup_in_release_builds() {
+        assert!(Config::<_, ()>::new(1).layer(()).checks_existing());
+        assert_eq!(
+            Config::<_, ()>::new(1)
+                .unchecked()
+                .layer(())
+                .checks_existing(),
+            cfg!(debug_assertions)
+        );
+        assert!(!Config::<_, ()>::new(1)
+            .replace_existing()
+            .layer(())
+            .checks_existing());
     }
 }
@@ -0,0 +1,537 @@
+use super::{
+    i18n::{self, MessageCatalog},
+    rejection::{ErrorCode, RejectionKind},
+};
+use axum::{
+    body::Bytes,
+    extract::{MatchedPath, RequestParts},
+    http::{Extensions, HeaderMap, Method, Uri},
+};
+use std::{
+    any::Any,
+    fmt,
+    sync::{Arc, OnceLock},
+    time::Duration,
+};
+
+/// Information about the request given to rejection handlers.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{JsonConfig, RejectionContext};
+/// use axum::extract::rejection::JsonRejection;
+///
+/// fn rejection_handler<B>(rejection: JsonRejection, ctx: RejectionContext<'_, B>) -> String {
+///     format!(
+///         "{} {}: failed to parse request as {}: {}",
+///         ctx.method(),
+///         ctx.uri(),
+///         ctx.target_type_name(),
+///         rejection,
+///     )
+/// }
+///
+/// let config = JsonConfig::new().rejection_handler(rejection_handler);
+/// # let _: JsonConfig<axum::body::Body> = config;
+/// ```
+pub struct RejectionContext<'a, B> {
+    req: &'a RequestParts<B>,
+    target_type_name: &'static str,
+    kind: RejectionKind,
+    error_code: Option<&'a ErrorCode>,
+    message_catalog: Option<&'a dyn MessageCatalog>,
+    docs: Option<(&'a str, Option<&'a str>)>,
+    details_hidden: bool,
+    request_id: Option<&'a str>,
+}
+
+impl<'a, B> RejectionContext<'a, B> {
+    pub(crate) fn new<T>(
+        req: &'a RequestParts<B>,
+        kind: RejectionKind,
+        error_code: Option<&'a ErrorCode>,
+        message_catalog: Option<&'a dyn MessageCatalog>,
+        docs: Option<(&'a str, Option<&'a str>)>,
+    ) -> Self {
+        Self {
+            req,
+            target_type_name: std::any::type_name::<T>(),
+            kind,
+            error_code,
+            message_catalog,
+            docs,
+            details_hidden: false,
+            request_id: None,
+        }
+    }
+
+    pub(crate) fn details_hidden(mut self, details_hidden: bool) -> Self {
+        self.details_hidden = details_hidden;
+        self
+    }
+
+    pub(crate) fn with_request_id(mut self, request_id: Option<&'a str>) -> Self {
+        self.request_id = request_id;
+        self
+    }
+
+    /// The request's method.
+    pub fn method(&self) -> &'a Method {
+        self.req.method()
+    }
+
+    /// The request's URI.
+    pub fn uri(&self) -> &'a Uri {
+        self.req.uri()
+    }
+
+    /// The path of the route that matched the request, if any.
+    pub fn matched_path(&self) -> Option<&'a str> {
+        self.req
+            .extensions()
+            .get::<MatchedPath>()
+            .map(MatchedPath::as_str)
+    }
+
+    /// The request's headers.
+    pub fn headers(&self) -> &'a HeaderMap {
+        self.req.headers()
+    }
+
+    /// The request's extensions.
+    pub fn extensions(&self) -> &'a Extensions {
+        self.req.extensions()
+    }
+
+    /// The name of the type that failed to extract, as returned by [`std::any::type_name`].
+    ///
+    /// This allows a single generic rejection handler to say which type the request failed to
+    /// parse as.
+    pub fn target_type_name(&self) -> &'static str {
+        self.target_type_name
+    }
+
+    /// The kind of the rejection.
+    pub fn kind(&self) -> RejectionKind {
+        self.kind
+    }
+
+    /// The application error code configured for the kind of the rejection, if any.
+    ///
+    /// See [`JsonConfig::error_code`](super::JsonConfig::error_code).
+    pub fn error_code(&self) -> Option<&'a ErrorCode> {
+        self.error_code
+    }
+
+    /// The ID of the request, if a request ID header is configured and the request has it.
+    ///
+    /// See [`JsonConfig::request_id_header`](super::JsonConfig::request_id_header).
+    pub fn request_id(&self) -> Option<&'a str> {
+        self.request_id
+    }
+
+    /// The link to the documentation of the kind of the rejection, if a documentation URL is
+    /// configured.
+    ///
+    /// See [`JsonConfig::docs_url`](super::JsonConfig::docs_url).
+    pub fn docs_url(&self) -> Option<String> {
+        let (base, slug) = self.docs?;
+        Some(format!(
+            "{}{}",
+            base,
+            slug.unwrap_or_else(|| self.kind.as_str())
+        ))
+    }
+
+    /// The message for the kind of the rejection, in the language the client prefers.
+    ///
+    /// Looked up in the configured [`MessageCatalog`] using the request's `Accept-Language`
+    /// header. Returns `None` if no catalog is configured or it has no message for the kind.
+    ///
+    /// See [`JsonConfig::message_catalog`](super::JsonConfig::message_catalog).
+    pub fn localized_message(&self) -> Option<String> {
+        let catalog = self.message_catalog?;
+        catalog.message(self.kind, &i18n::accepted_languages(self.headers()))
+    }
+
+    /// The body of the request, if the extractor is configured to capture it.
+    ///
+    /// See [`JsonConfig::capture_body`](super::JsonConfig::capture_body) and
+    /// [`FormConfig::capture_body`](super::FormConfig::capture_body). Returns `None` if the
+    /// request isn't allowed to see the details of rejections, see
+    /// [`JsonConfig::verbose_errors_header`](super::JsonConfig::verbose_errors_header).
+    pub fn body(&self) -> Option<&'a RejectedBody> {
+        if self.details_hidden {
+            return None;
+        }
+        self.req.extensions().get::<RejectedBody>()
+    }
+
+    /// A [JSON Pointer] to the element of the body that failed to deserialize, such as
+    /// `/items/3/price`.
+    ///
+    /// Only set by [`Json`](super::Json) when the body is valid JSON but doesn't match the target
+    /// type. The pointer is empty if the body as a whole has the wrong type.
+    ///
+    /// [JSON Pointer]: https://www.rfc-editor.org/rfc/rfc6901
+    pub fn json_pointer(&self) -> Option<&'a str> {
+        self.req
+            .extensions()
+            .get::<JsonPointer>()
+            .map(|pointer| pointer.0.as_str())
+    }
+
+    /// The part of the JSON Schema of the target type that describes the element that failed to
+    /// deserialize.
+    ///
+    /// Only set by [`Json`](super::Json) for types whose schema was added with
+    /// [`JsonConfig::schema`](super::JsonConfig::schema), when the body is valid JSON but doesn't
+    /// match the schema. The whole schema is used if the body as a whole has the wrong type.
+    ///
+    /// Requires the `schemars` feature.
+    #[cfg(feature = "schemars")]
+    pub fn json_schema(&self) -> Option<&'a serde_json::Value> {
+        self.req
+            .extensions()
+            .get::<JsonSchema>()
+            .map(|schema| &*schema.0)
+    }
+
+    /// The errors of every element of the body that didn't match the target type.
+    ///
+    /// Only set by [`Json`](super::Json) when [`JsonConfig::all_errors`](super::JsonConfig::all_errors)
+    /// is enabled and the body is valid JSON but doesn't match the target type. Returns `None`
+    /// if the request isn't allowed to see the details of rejections, see
+    /// [`JsonConfig::verbose_errors_header`](super::JsonConfig::verbose_errors_header).
+    pub fn field_errors(&self) -> Option<&'a [FieldError]> {
+        if self.details_hidden {
+            return None;
+        }
+        self.req
+            .extensions()
+            .get::<FieldErrors>()
+            .map(|errors| &**errors.0)
+    }
+
+    /// Whether the rejection's details should be left out of responses.
+    ///
+    /// This is the case if the element that failed to deserialize is a redacted field, in which
+    /// case the details might contain the field's value, see
+    /// [`JsonConfig::redact`](super::JsonConfig::redact). It's also the case if the request
+    /// isn't allowed to see the details, see
+    /// [`JsonConfig::verbose_errors_header`](super::JsonConfig::verbose_errors_header).
+    pub fn details_redacted(&self) -> bool {
+        self.details_hidden || self.req.extensions().get::<DetailsRedacted>().is_some()
+    }
+}
+
+impl<'a, B> fmt::Debug for RejectionContext<'a, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RejectionContext")
+            .field("method", self.method())
+            .field("uri", self.uri())
+            .field("target_type_name", &self.target_type_name)
+            .field("kind", &self.kind)
+            .field("error_code", &self.error_code)
+            .finish()
+    }
+}
+
+impl<'a, B> Clone for RejectionContext<'a, B> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, B> Copy for RejectionContext<'a, B> {}
+
+/// Statistics about a successful extraction.
+///
+/// Given to the functions set with `on_success` on the configs, such as
+/// [`JsonConfig::on_success`](super::JsonConfig::on_success).
+#[derive(Debug, Clone, Copy)]
+pub struct ExtractionStats {
+    duration: Duration,
+    body_size: Option<usize>,
+}
+
+impl ExtractionStats {
+    pub(crate) fn new(duration: Duration, body_size: Option<usize>) -> Self {
+        Self {
+            duration,
+            body_size,
+        }
+    }
+
+    /// How long extraction took, including buffering the body.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// The size of the body in bytes, if the extractor read it.
+    ///
+    /// This is `None` for extractors that don't read the body, such as
+    /// [`Query`](super::Query).
+    pub fn body_size(&self) -> Option<usize> {
+        self.body_size
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct BodySize(pub(crate) usize);
+
+#[derive(Debug, Clone)]
+pub(crate) struct JsonPointer(pub(crate) String);
+
+#[cfg(feature = "schemars")]
+#[derive(Debug, Clone)]
+pub(crate) struct JsonSchema(pub(crate) std::sync::Arc<serde_json::Value>);
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct DetailsRedacted;
+
+#[derive(Debug, Clone)]
+pub(crate) struct FieldErrors(pub(crate) std::sync::Arc<Vec<FieldError>>);
+
+/// An element of a body that didn't match the target type.
+///
+/// Available from [`RejectionContext::field_errors`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldError {
+    pointer: String,
+    message: String,
+}
+
+impl FieldError {
+    pub(crate) fn new(pointer: String, message: String) -> Self {
+        Self { pointer, message }
+    }
+
+    /// A [JSON Pointer] to the element, such as `/items/3/price`.
+    ///
+    /// [JSON Pointer]: https://www.rfc-editor.org/rfc/rfc6901
+    pub fn pointer(&self) -> &str {
+        &self.pointer
+    }
+
+    /// Why the element didn't match, such as `invalid type: string "one", expected u32`.
+    ///
+    /// Elements of redacted fields have the message `Invalid value`, see
+    /// [`JsonConfig::redact`](super::JsonConfig::redact).
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+/// The body of a request that was rejected.
+///
+/// Available from [`RejectionContext::body`].
+#[derive(Debug, Clone)]
+pub struct RejectedBody {
+    bytes: Bytes,
+    truncated: bool,
+}
+
+impl RejectedBody {
+    pub(crate) fn new(bytes: &Bytes, max_size: usize) -> Self {
+        Self {
+            bytes: bytes.slice(..bytes.len().min(max_size)),
+            truncated: bytes.len() > max_size,
+        }
+    }
+
+    /// The body, up to the configured maximum size.
+    pub fn bytes(&self) -> &Bytes {
+        &self.bytes
+    }
+
+    /// Whether the body was longer than the configured maximum size and has been truncated.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+}
+
+/// Information about a rejection, added to the extensions of rejection responses.
+///
+/// This allows middleware, for example for logging or metrics, to see what failed without
+/// parsing the response body.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::RejectionInfo;
+/// use axum::{
+///     Router,
+///     middleware::{self, Next},
+///     http::Request,
+///     response::Response,
+/// };
+///
+/// async fn log_rejections<B>(req: Request<B>, next: Next<B>) -> Response {
+///     let res = next.run(req).await;
+///     if let Some(info) = res.extensions().get::<RejectionInfo>() {
+///         println!("failed to extract {}: {}", info.target_type_name(), info.message());
+///     }
+///     res
+/// }
+///
+/// let app = Router::new().layer(middleware::from_fn(log_rejections));
+/// # let _: Router = app;
+/// ```
+#[derive(Clone)]
+pub struct RejectionInfo {
+    extractor: &'static str,
+    kind: RejectionKind,
+    target_type_name: &'static str,
+    message: Arc<LazyMessage>,
+}
+
+// The message of a rejection, formatted when it's first read since most rejections are never
+// logged. Clones of the info share it.
+#[derive(Default)]
+struct LazyMessage {
+    message: OnceLock<String>,
+    source: Option<Arc<dyn RejectionSource>>,
+}
+
+// A rejection kept in the info to format its message from.
+trait RejectionSource: fmt::Display + Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<R> RejectionSource for R
+where
+    R: fmt::Display + Send + Sync + 'static,
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl RejectionInfo {
+    pub(crate) fn new<T>(extractor: &'static str, kind: RejectionKind) -> Self {
+        Self {
+            extractor,
+            kind,
+            target_type_name: std::any::type_name::<T>(),
+            message: Arc::default(),
+        }
+    }
+
+    // Format the message from `rejection`, unless it already was. Used before the rejection is
+    // given away, since the message cannot be formatted later.
+    pub(crate) fn format_message<R>(&self, rejection: &R)
+    where
+        R: fmt::Display + ?Sized,
+    {
+        self.message.message.get_or_init(|| rejection.to_string());
+    }
+
+    // Keep the rejection to format the message from if it's read.
+    pub(crate) fn set_source<R>(&mut self, rejection: R)
+    where
+        R: fmt::Display + Send + Sync + 'static,
+    {
+        self.set_shared_source(Arc::new(rejection));
+    }
+
+    // Like `set_source`, for a rejection that's also kept elsewhere, such as in the body of the
+    // default response.
+    pub(crate) fn set_shared_source<R>(&mut self, rejection: Arc<R>)
+    where
+        R: fmt::Display + Send + Sync + 'static,
+    {
+        let source = Some(rejection as Arc<dyn RejectionSource>);
+        match Arc::get_mut(&mut self.message) {
+            Some(message) => message.source = source,
+            None => {
+                self.message = Arc::new(LazyMessage {
+                    message: self.message.message.clone(),
+                    source,
+                })
+            }
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn is_formatted(&self) -> bool {
+        self.message.message.get().is_some()
+    }
+
+    /// The name of the extractor that rejected the request, such as `"Json"`.
+    pub fn extractor(&self) -> &'static str {
+        self.extractor
+    }
+
+    /// The kind of the rejection.
+    pub fn kind(&self) -> RejectionKind {
+        self.kind
+    }
+
+    /// The name of the type that failed to extract, as returned by [`std::any::type_name`].
+    pub fn target_type_name(&self) -> &'static str {
+        self.target_type_name
+    }
+
+    /// The original rejection, if it's an `R`.
+    ///
+    /// The rejections of [`Json`](super::Json), [`Query`](super::Query) and
+    /// [`Form`](super::Form) are kept if no rejection handler handled them, so middleware can
+    /// inspect them, for example as a
+    /// [`JsonRejection`](axum::extract::rejection::JsonRejection). Other rejections aren't kept
+    /// and return `None`.
+    pub fn rejection<R>(&self) -> Option<&R>
+    where
+        R: Any,
+    {
+        self.message.source.as_deref()?.as_any().downcast_ref()
+    }
+
+    /// The rejection's message, as returned by its `Display` implementation.
+    ///
+    /// The message is formatted the first time it's read.
+    pub fn message(&self) -> &str {
+        let LazyMessage { message, source } = &*self.message;
+        message.get_or_init(|| {
+            source
+                .as_ref()
+                .map_or_else(String::new, ToString::to_string)
+        })
+    }
+}
+
+impl fmt::Debug for RejectionInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RejectionInfo")
+            .field("extractor", &self.extractor)
+            .field("kind", &self.kind)
+            .field("target_type_name", &self.target_type_name)
+            .field("message", &self.message.message.get())
+            .finish()
+    }
+}
+
+// The message of a rejection that a rejection handler took, when it's the same for every
+// rejection of its type.
+pub(crate) struct HandledRejection(pub(crate) &'static str);
+
+impl fmt::Display for HandledRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+// The message of rejections from hooks, which respond themselves.
+pub(crate) struct HookRejection(pub(crate) &'static str);
+
+impl fmt::Display for HookRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Rejected by a `{}` hook", self.0)
+    }
+}
+
+// compare secrets without leaking how much of them matched through timing
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
@@ -0,0 +1,365 @@
+use super::{
+    rejection::{ConfiguredRejection, DetailLevel, RejectionKind},
+    RejectionContext, RejectionInfo,
+};
+use axum::{extract::RequestParts, http::StatusCode, response::Response};
+use std::{fmt, sync::Arc};
+
+/// Wrap an extractor so it can be configured with a layer, like the extractors in this module.
+///
+/// This generates an extractor that runs the wrapped extractor, and a config with a rejection
+/// handler, an override for the status code, and `allow_override`. The config is added as a
+/// layer the same way as [`JsonConfig`](crate::via_extensions::JsonConfig), and the extractor
+/// rejects with [`ConfiguredRejection`], so the rejection can be inspected in middleware.
+///
+/// The rejection of the wrapped extractor must implement `Display`. Rejection handlers receive a
+/// [`RejectionContext`] with the kind [`RejectionKind::Other`].
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::make_configurable_extractor;
+/// use axum::{
+///     async_trait,
+///     extract::{FromRequest, RequestParts},
+///     http::StatusCode,
+///     response::{IntoResponse, Response},
+///     routing::get,
+///     Router,
+/// };
+/// use std::fmt;
+///
+/// // an extractor from another part of the application
+/// #[derive(Debug)]
+/// pub struct TenantId(String);
+///
+/// #[derive(Debug)]
+/// pub struct MissingTenant;
+///
+/// impl fmt::Display for MissingTenant {
+///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         write!(f, "Missing `x-tenant-id` header")
+///     }
+/// }
+///
+/// impl IntoResponse for MissingTenant {
+///     fn into_response(self) -> Response {
+///         (StatusCode::BAD_REQUEST, self.to_string()).into_response()
+///     }
+/// }
+///
+/// #[async_trait]
+/// impl<B> FromRequest<B> for TenantId
+/// where
+///     B: Send,
+/// {
+///     type Rejection = MissingTenant;
+///
+///     async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+///         req.headers()
+///             .get("x-tenant-id")
+///             .and_then(|value| value.to_str().ok())
+///             .map(|value| Self(value.to_owned()))
+///             .ok_or(MissingTenant)
+///     }
+/// }
+///
+/// make_configurable_extractor! {
+///     /// `TenantId` that can be configured with `TenantConfig`.
+///     #[derive(Debug)]
+///     pub struct Tenant(TenantId): MissingTenant;
+///
+///     /// Config for `Tenant`.
+///     pub struct TenantConfig;
+/// }
+///
+/// async fn handler(Tenant(tenant): Tenant) {}
+///
+/// let app = Router::new()
+///     .route("/", get(handler))
+///     .layer(TenantConfig::new().rejection_handler(|rejection, _ctx| {
+///         (StatusCode::UNAUTHORIZED, rejection.to_string())
+///     }));
+/// # let _: Router = app;
+/// ```
+#[macro_export]
+macro_rules! make_configurable_extractor {
+    (
+        $(#[$m:meta])*
+        $vis:vis struct $ident:ident($inner:ty): $rejection:ty;
+
+        $(#[$config_m:meta])*
+        $config_vis:vis struct $config:ident;
+    ) => {
+        $(#[$m])*
+        $vis struct $ident(pub $inner);
+
+        $(#[$config_m])*
+        $config_vis struct $config<B> {
+            rejection_handler: ::std::option::Option<
+                $crate::__private::CustomRejectionHandler<$rejection, B>,
+            >,
+            override_status: ::std::option::Option<$crate::__private::StatusCode>,
+            allow_override: bool,
+        }
+
+        impl<B> $config<B> {
+            #[doc = concat!("Create a new `", stringify!($config), "`")]
+            pub fn new() -> Self {
+                Self {
+                    rejection_handler: None,
+                    override_status: None,
+                    allow_override: false,
+                }
+            }
+
+            /// Set the rejection handler function.
+            pub fn rejection_handler<F, R>(mut self, f: F) -> Self
+            where
+                F: Fn($rejection, $crate::via_extensions::RejectionContext<'_, B>) -> R
+                    + Send
+                    + Sync
+                    + 'static,
+                R: $crate::__private::IntoResponse,
+            {
+                self.rejection_handler = Some(::std::sync::Arc::new(move |rejection, ctx| {
+                    $crate::__private::IntoResponse::into_response(f(rejection, ctx))
+                }));
+                self
+            }
+
+            /// Set the status code of the default rejection responses.
+            pub fn override_status(mut self, status: $crate::__private::StatusCode) -> Self {
+                self.override_status = Some(status);
+                self
+            }
+
+            /// Allow layers further in to override this config.
+            pub fn allow_override(mut self) -> Self {
+                self.allow_override = true;
+                self
+            }
+        }
+
+        impl<B> ::std::clone::Clone for $config<B> {
+            fn clone(&self) -> Self {
+                Self {
+                    rejection_handler: self.rejection_handler.clone(),
+                    override_status: self.override_status,
+                    allow_override: self.allow_override,
+                }
+            }
+        }
+
+        impl<B> ::std::default::Default for $config<B> {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl<B> ::std::fmt::Debug for $config<B> {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.debug_struct(stringify!($config))
+                    .field("override_status", &self.override_status)
+                    .field("allow_override", &self.allow_override)
+                    .finish()
+            }
+        }
+
+        impl<S, B> $crate::__private::Layer<S> for $config<B> {
+            type Service = <$crate::via_extensions::Config<Self, B> as $crate::__private::Layer<
+                S,
+            >>::Service;
+
+            fn layer(&self, inner: S) -> Self::Service {
+                let mut config: $crate::via_extensions::Config<_, B> =
+                    $crate::via_extensions::Config::new(self.clone());
+                if self.allow_override {
+                    config = config.allow_override();
+                }
+                $crate::__private::Layer::layer(&config, inner)
+            }
+        }
+
+        impl<B> $crate::via_extensions::ConfigurableExtractor<B> for $ident
+        where
+            $inner: $crate::__private::FromRequest<B, Rejection = $rejection>,
+            B: Send + 'static,
+        {
+            type Config = $config<B>;
+        }
+
+        #[$crate::__private::async_trait]
+        impl<B> $crate::__private::FromRequest<B> for $ident
+        where
+            $inner: $crate::__private::FromRequest<B, Rejection = $rejection>,
+            B: Send + 'static,
+        {
+            type Rejection = $crate::via_extensions::rejection::ConfiguredRejection<$rejection>;
+
+            async fn from_request(
+                req: &mut $crate::__private::RequestParts<B>,
+            ) -> ::std::result::Result<Self, Self::Rejection> {
+                match <$inner as $crate::__private::FromRequest<B>>::from_request(req).await {
+                    Ok(value) => Ok(Self(value)),
+                    Err(rejection) => {
+                        let config = $crate::__private::get_config::<$config<B>, B>(req)
+                            .unwrap_or_default();
+                        Err($crate::__private::reject_custom::<$inner, _, _>(
+                            stringify!($ident),
+                            rejection,
+                            config.rejection_handler.as_ref(),
+                            config.override_status,
+                            req,
+                        ))
+                    }
+                }
+            }
+        }
+    };
+}
+
+#[doc(hidden)]
+pub type CustomRejectionHandler<R, B> =
+    Arc<dyn Fn(R, RejectionContext<'_, B>) -> Response + Send + Sync>;
+
+// The rejection of an extractor generated by `make_configurable_extractor!`.
+// Get the config of an extractor generated by the macro.
+#[doc(hidden)]
+pub fn get_config<C, B>(req: &RequestParts<B>) -> Option<Arc<C>>
+where
+    C: Send + Sync + 'static,
+    B: 'static,
+{
+    super::config::get_shared::<C, B>(req.extensions())
+}
+
+#[doc(hidden)]
+pub fn reject_custom<T, R, B>(
+    extractor: &'static str,
+    rejection: R,
+    rejection_handler: Option<&CustomRejectionHandler<R, B>>,
+    override_status: Option<StatusCode>,
+    req: &RequestParts<B>,
+) -> ConfiguredRejection<R>
+where
+    R: fmt::Display,
+{
+    let info = RejectionInfo::new::<T>(extractor, RejectionKind::Other);
+    #[cfg(feature = "metrics")]
+    super::metrics::record_rejection(extractor, RejectionKind::Other, req);
+    let handled = match rejection_handler {
+        Some(rejection_handler) => {
+            info.format_message(&rejection);
+            let ctx = RejectionContext::new::<T>(req, RejectionKind::Other, None, None, None);
+            Ok(rejection_handler(rejection, ctx))
+        }
+        None => Err(rejection),
+    };
+
+    ConfiguredRejection::new(
+        handled,
+        override_status,
+        Arc::default(),
+        info,
+        DetailLevel::default(),
+    )
+}
+
+#[cfg(test)]
+// the macro generates public items that the tests don't use
+#[allow(unreachable_pub, dead_code)]
+mod tests {
+    use axum::{
+        async_trait,
+        body::Body,
+        extract::{FromRequest, RequestParts},
+        http::{Request, StatusCode},
+        response::{IntoResponse, Response},
+        routing::get,
+        Router,
+    };
+    use std::fmt;
+    use tower::ServiceExt;
+
+    struct Tenant(String);
+
+    #[derive(Debug)]
+    struct MissingTenant;
+
+    impl fmt::Display for MissingTenant {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "missing tenant")
+        }
+    }
+
+    impl IntoResponse for MissingTenant {
+        fn into_response(self) -> Response {
+            (StatusCode::BAD_REQUEST, self.to_string()).into_response()
+        }
+    }
+
+    #[async_trait]
+    impl<B> FromRequest<B> for Tenant
+    where
+        B: Send,
+    {
+        type Rejection = MissingTenant;
+
+        async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+            req.headers()
+                .get("x-tenant-id")
+                .and_then(|value| value.to_str().ok())
+                .map(|value| Self(value.to_owned()))
+                .ok_or(MissingTenant)
+        }
+    }
+
+    crate::make_configurable_extractor! {
+        struct ConfigurableTenant(Tenant): MissingTenant;
+
+        struct TenantConfig;
+    }
+
+    #[tokio::test]
+    async fn configurable_extractor() {
+        async fn handler(_: ConfigurableTenant) {}
+
+        let app = Router::new().route("/", get(handler));
+
+        let res = app
+            .clone()
+            .oneshot(Request::builder().uri("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        let res = app
+            .clone()
+            .layer(TenantConfig::new().override_status(StatusCode::UNAUTHORIZED))
+            .oneshot(Request::builder().uri("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        let res = app
+            .clone()
+            .layer(TenantConfig::new().rejection_handler(|_, _| StatusCode::IM_A_TEAPOT))
+            .oneshot(Request::builder().uri("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::IM_A_TEAPOT);
+
+        let res = app
+            .oneshot(
+                Request::builder()
+                    .uri("/")
+                    .header("x-tenant-id", "acme")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+}
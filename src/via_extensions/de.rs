@@ -0,0 +1,909 @@
+//! A `Deserializer` adapter that applies the deserialization options from the configs.
+//!
+//! It wraps another deserializer and everything that deserializer hands out (visitors, map and
+//! sequence access, etc.) so the options also apply to nested values.
+
+use axum::BoxError;
+use serde::de::{
+    self,
+    value::{CowStrDeserializer, MapAccessDeserializer, SeqAccessDeserializer},
+    DeserializeSeed, Deserializer, EnumAccess, IntoDeserializer, MapAccess, SeqAccess, Unexpected,
+    VariantAccess, Visitor,
+};
+use std::{borrow::Cow, collections::HashMap, fmt, marker::PhantomData, sync::Arc};
+
+pub(super) type ScalarParser = Arc<dyn Fn(&str) -> Result<String, BoxError> + Send + Sync>;
+
+/// Deserialization options shared by the extractors that deserialize strings, such as `Query` and
+/// `Form`.
+#[derive(Clone, Default)]
+pub(super) struct Options {
+    pub(super) lenient_bools: bool,
+    pub(super) empty_string_as_none: bool,
+    pub(super) key_aliases: Arc<HashMap<String, String>>,
+    pub(super) case_insensitive_keys: bool,
+    pub(super) scalar_parsers: Arc<HashMap<String, ScalarParser>>,
+}
+
+impl Options {
+    fn is_default(&self) -> bool {
+        !self.lenient_bools && !self.empty_string_as_none && !self.inspects_keys()
+    }
+
+    fn inspects_keys(&self) -> bool {
+        !self.key_aliases.is_empty()
+            || self.case_insensitive_keys
+            || !self.scalar_parsers.is_empty()
+    }
+
+    /// Resolve a key to the field it should populate, according to the aliases and
+    /// case-insensitivity.
+    fn resolve_key<'a>(&'a self, key: &'a str, fields: &[&'static str]) -> &'a str {
+        let key = if let Some(canonical) = self.key_aliases.get(key) {
+            canonical
+        } else if self.case_insensitive_keys {
+            self.key_aliases
+                .iter()
+                .find(|(alias, _)| alias.eq_ignore_ascii_case(key))
+                .map_or(key, |(_, canonical)| canonical)
+        } else {
+            key
+        };
+
+        if self.case_insensitive_keys {
+            if let Some(field) = fields.iter().find(|field| field.eq_ignore_ascii_case(key)) {
+                return field;
+            }
+        }
+
+        key
+    }
+
+    /// Deserialize a `T` from `deserializer` with the options applied.
+    pub(super) fn deserialize<'de, T, D>(&self, deserializer: D) -> Result<T, D::Error>
+    where
+        T: de::Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        if self.is_default() {
+            T::deserialize(deserializer)
+        } else {
+            T::deserialize(Adapter::new(deserializer, self))
+        }
+    }
+}
+
+struct Adapter<'a, D> {
+    inner: D,
+    options: &'a Options,
+}
+
+impl<'a, D> Adapter<'a, D> {
+    fn new(inner: D, options: &'a Options) -> Self {
+        Self { inner, options }
+    }
+}
+
+macro_rules! forward_deserialize {
+    ($($method:ident($($arg:ident: $ty:ty),*)),* $(,)?) => {
+        $(
+            fn $method<V>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                self.inner.$method($($arg,)* Wrap::new(visitor, self.options))
+            }
+        )*
+    };
+}
+
+impl<'a, 'de, D> Deserializer<'de> for Adapter<'a, D>
+where
+    D: Deserializer<'de>,
+{
+    type Error = D::Error;
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if self.options.lenient_bools {
+            self.inner.deserialize_str(LenientBool(visitor))
+        } else {
+            self.inner
+                .deserialize_bool(Wrap::new(visitor, self.options))
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if self.options.empty_string_as_none {
+            self.inner
+                .deserialize_any(EmptyStringAsNone::new(visitor, self.options))
+        } else {
+            self.inner
+                .deserialize_option(Wrap::new(visitor, self.options))
+        }
+    }
+
+    forward_deserialize! {
+        deserialize_any(),
+        deserialize_i8(),
+        deserialize_i16(),
+        deserialize_i32(),
+        deserialize_i64(),
+        deserialize_i128(),
+        deserialize_u8(),
+        deserialize_u16(),
+        deserialize_u32(),
+        deserialize_u64(),
+        deserialize_u128(),
+        deserialize_f32(),
+        deserialize_f64(),
+        deserialize_char(),
+        deserialize_str(),
+        deserialize_string(),
+        deserialize_bytes(),
+        deserialize_byte_buf(),
+        deserialize_unit(),
+        deserialize_unit_struct(name: &'static str),
+        deserialize_newtype_struct(name: &'static str),
+        deserialize_seq(),
+        deserialize_tuple(len: usize),
+        deserialize_tuple_struct(name: &'static str, len: usize),
+        deserialize_map(),
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]),
+        deserialize_identifier(),
+        deserialize_ignored_any(),
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.inner.deserialize_struct(
+            name,
+            fields,
+            Wrap::with_fields(visitor, self.options, fields),
+        )
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+/// Wraps a visitor so values it's given are wrapped in [`Adapter`].
+struct Wrap<'a, V> {
+    inner: V,
+    options: &'a Options,
+    // the fields of the struct being deserialized, if any. Used to resolve keys.
+    fields: &'static [&'static str],
+}
+
+impl<'a, V> Wrap<'a, V> {
+    fn new(inner: V, options: &'a Options) -> Self {
+        Self::with_fields(inner, options, &[])
+    }
+
+    fn with_fields(inner: V, options: &'a Options, fields: &'static [&'static str]) -> Self {
+        Self {
+            inner,
+            options,
+            fields,
+        }
+    }
+}
+
+macro_rules! forward_visit {
+    ($($method:ident($ty:ty)),* $(,)?) => {
+        $(
+            fn $method<E>(self, v: $ty) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                self.inner.$method(v)
+            }
+        )*
+    };
+}
+
+impl<'a, 'de, V> Visitor<'de> for Wrap<'a, V>
+where
+    V: Visitor<'de>,
+{
+    type Value = V::Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.expecting(f)
+    }
+
+    forward_visit! {
+        visit_bool(bool),
+        visit_i8(i8),
+        visit_i16(i16),
+        visit_i32(i32),
+        visit_i64(i64),
+        visit_i128(i128),
+        visit_u8(u8),
+        visit_u16(u16),
+        visit_u32(u32),
+        visit_u64(u64),
+        visit_u128(u128),
+        visit_f32(f32),
+        visit_f64(f64),
+        visit_char(char),
+        visit_str(&str),
+        visit_borrowed_str(&'de str),
+        visit_string(String),
+        visit_bytes(&[u8]),
+        visit_borrowed_bytes(&'de [u8]),
+        visit_byte_buf(Vec<u8>),
+    }
+
+    fn visit_none<E>(self) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.inner.visit_none()
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.inner
+            .visit_some(Adapter::new(deserializer, self.options))
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.inner.visit_unit()
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.inner
+            .visit_newtype_struct(Adapter::new(deserializer, self.options))
+    }
+
+    fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        self.inner.visit_seq(Wrap::new(seq, self.options))
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        self.inner
+            .visit_map(Map::new(map, self.options, self.fields))
+    }
+
+    fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+    where
+        A: EnumAccess<'de>,
+    {
+        self.inner.visit_enum(Wrap::new(data, self.options))
+    }
+}
+
+impl<'a, 'de, A> SeqAccess<'de> for Wrap<'a, A>
+where
+    A: SeqAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.inner.next_element_seed(Wrap::new(seed, self.options))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+/// Wraps a `MapAccess` and keeps track of the current key, so keys can be resolved and values
+/// parsed with the [`ScalarParser`] for their key.
+struct Map<'a, A> {
+    inner: A,
+    options: &'a Options,
+    fields: &'static [&'static str],
+    key: Option<String>,
+}
+
+impl<'a, A> Map<'a, A> {
+    fn new(inner: A, options: &'a Options, fields: &'static [&'static str]) -> Self {
+        Self {
+            inner,
+            options,
+            fields,
+            key: None,
+        }
+    }
+}
+
+impl<'a, 'de, A> MapAccess<'de> for Map<'a, A>
+where
+    A: MapAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        self.key = None;
+
+        if self.options.inspects_keys() {
+            self.inner.next_key_seed(KeySeed {
+                inner: seed,
+                options: self.options,
+                fields: self.fields,
+                key: &mut self.key,
+            })
+        } else {
+            self.inner.next_key_seed(Wrap::new(seed, self.options))
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let parser = self
+            .key
+            .take()
+            .and_then(|key| self.options.scalar_parsers.get(&key));
+
+        if let Some(parser) = parser {
+            self.inner.next_value_seed(ParseScalar {
+                inner: seed,
+                options: self.options,
+                parser,
+            })
+        } else {
+            self.inner.next_value_seed(Wrap::new(seed, self.options))
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, 'de, A> EnumAccess<'de> for Wrap<'a, A>
+where
+    A: EnumAccess<'de>,
+{
+    type Error = A::Error;
+    type Variant = Wrap<'a, A::Variant>;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let options = self.options;
+        let (value, variant) = self.inner.variant_seed(Wrap::new(seed, options))?;
+        Ok((value, Wrap::new(variant, options)))
+    }
+}
+
+impl<'a, 'de, A> VariantAccess<'de> for Wrap<'a, A>
+where
+    A: VariantAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        self.inner.unit_variant()
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.inner
+            .newtype_variant_seed(Wrap::new(seed, self.options))
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.inner
+            .tuple_variant(len, Wrap::new(visitor, self.options))
+    }
+
+    fn struct_variant<V>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.inner
+            .struct_variant(fields, Wrap::new(visitor, self.options))
+    }
+}
+
+impl<'a, 'de, T> DeserializeSeed<'de> for Wrap<'a, T>
+where
+    T: DeserializeSeed<'de>,
+{
+    type Value = T::Value;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.inner
+            .deserialize(Adapter::new(deserializer, self.options))
+    }
+}
+
+/// Seed for map keys that resolves them with [`Options::resolve_key`] and records the result.
+struct KeySeed<'a, 'k, T> {
+    inner: T,
+    options: &'a Options,
+    fields: &'static [&'static str],
+    key: &'k mut Option<String>,
+}
+
+impl<'a, 'k, 'de, T> DeserializeSeed<'de> for KeySeed<'a, 'k, T>
+where
+    T: DeserializeSeed<'de>,
+{
+    type Value = T::Value;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.inner.deserialize(Key {
+            inner: deserializer,
+            options: self.options,
+            fields: self.fields,
+            key: self.key,
+        })
+    }
+}
+
+struct Key<'a, 'k, D> {
+    inner: D,
+    options: &'a Options,
+    fields: &'static [&'static str],
+    key: &'k mut Option<String>,
+}
+
+macro_rules! resolve_key {
+    ($($method:ident),* $(,)?) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                self.inner.$method(ResolveKey {
+                    inner: visitor,
+                    options: self.options,
+                    fields: self.fields,
+                    key: self.key,
+                })
+            }
+        )*
+    };
+}
+
+impl<'a, 'k, 'de, D> Deserializer<'de> for Key<'a, 'k, D>
+where
+    D: Deserializer<'de>,
+{
+    type Error = D::Error;
+
+    resolve_key! {
+        deserialize_any,
+        deserialize_str,
+        deserialize_string,
+        deserialize_identifier,
+    }
+
+    forward_deserialize! {
+        deserialize_bool(),
+        deserialize_i8(),
+        deserialize_i16(),
+        deserialize_i32(),
+        deserialize_i64(),
+        deserialize_i128(),
+        deserialize_u8(),
+        deserialize_u16(),
+        deserialize_u32(),
+        deserialize_u64(),
+        deserialize_u128(),
+        deserialize_f32(),
+        deserialize_f64(),
+        deserialize_char(),
+        deserialize_bytes(),
+        deserialize_byte_buf(),
+        deserialize_option(),
+        deserialize_unit(),
+        deserialize_unit_struct(name: &'static str),
+        deserialize_newtype_struct(name: &'static str),
+        deserialize_seq(),
+        deserialize_tuple(len: usize),
+        deserialize_tuple_struct(name: &'static str, len: usize),
+        deserialize_map(),
+        deserialize_struct(name: &'static str, fields: &'static [&'static str]),
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]),
+        deserialize_ignored_any(),
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+/// Visitor for map keys that resolves them with [`Options::resolve_key`].
+struct ResolveKey<'a, 'k, V> {
+    inner: V,
+    options: &'a Options,
+    fields: &'static [&'static str],
+    key: &'k mut Option<String>,
+}
+
+impl<'a, 'k, V> ResolveKey<'a, 'k, V> {
+    /// Resolve the key and record it, if it's needed for finding the value's scalar parser.
+    fn resolve<'v>(&mut self, key: &'v str) -> &'v str
+    where
+        'a: 'v,
+    {
+        let key = self.options.resolve_key(key, self.fields);
+        if !self.options.scalar_parsers.is_empty() {
+            *self.key = Some(key.to_owned());
+        }
+        key
+    }
+}
+
+impl<'a, 'k, 'de, V> Visitor<'de> for ResolveKey<'a, 'k, V>
+where
+    V: Visitor<'de>,
+{
+    type Value = V::Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.expecting(f)
+    }
+
+    fn visit_str<E>(mut self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        let key = self.resolve(v);
+        self.inner.visit_str(key)
+    }
+
+    fn visit_borrowed_str<E>(mut self, v: &'de str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        let key = self.resolve(v);
+        if key == v {
+            self.inner.visit_borrowed_str(v)
+        } else {
+            self.inner.visit_str(key)
+        }
+    }
+
+    fn visit_string<E>(mut self, v: String) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        let key = self.resolve(&v);
+        if key == v {
+            self.inner.visit_string(v)
+        } else {
+            self.inner.visit_str(key)
+        }
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.inner.visit_u64(v)
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.inner.visit_bytes(v)
+    }
+}
+
+/// Seed for map values that have a [`ScalarParser`].
+///
+/// The value is deserialized as a string, given to the parser, and the parser's output is then
+/// deserialized with [`StrValue`].
+struct ParseScalar<'a, T> {
+    inner: T,
+    options: &'a Options,
+    parser: &'a ScalarParser,
+}
+
+impl<'a, 'de, T> DeserializeSeed<'de> for ParseScalar<'a, T>
+where
+    T: DeserializeSeed<'de>,
+{
+    type Value = T::Value;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = <String as de::Deserialize>::deserialize(deserializer)?;
+        let value = (self.parser)(&value).map_err(de::Error::custom)?;
+        self.inner.deserialize(Adapter::new(
+            StrValue::<D::Error>::new(Cow::Owned(value)),
+            self.options,
+        ))
+    }
+}
+
+/// Visitor that accepts `1/0`, `on/off`, and `yes/no` in addition to `true/false`.
+struct LenientBool<V>(V);
+
+impl<'de, V> Visitor<'de> for LenientBool<V>
+where
+    V: Visitor<'de>,
+{
+    type Value = V::Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.expecting(f)
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.0.visit_bool(v)
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        let value = if ["true", "1", "on", "yes"]
+            .iter()
+            .any(|s| v.eq_ignore_ascii_case(s))
+        {
+            true
+        } else if ["false", "0", "off", "no"]
+            .iter()
+            .any(|s| v.eq_ignore_ascii_case(s))
+        {
+            false
+        } else {
+            return Err(E::invalid_value(Unexpected::Str(v), &"a boolean"));
+        };
+
+        self.0.visit_bool(value)
+    }
+}
+
+/// Visitor for `Option<T>` that produces `None` for empty strings.
+///
+/// Formats like `serde_urlencoded` call `visit_some` for every value that is present, so this
+/// goes through `deserialize_any` to see the string and then deserializes non-empty values with
+/// [`StrValue`].
+struct EmptyStringAsNone<'a, V> {
+    inner: V,
+    options: &'a Options,
+}
+
+impl<'a, V> EmptyStringAsNone<'a, V> {
+    fn new(inner: V, options: &'a Options) -> Self {
+        Self { inner, options }
+    }
+
+    fn visit_cow_str<'de, E>(self, v: Cow<'de, str>) -> Result<V::Value, E>
+    where
+        V: Visitor<'de>,
+        E: de::Error,
+    {
+        if v.is_empty() {
+            self.inner.visit_none()
+        } else {
+            self.inner
+                .visit_some(Adapter::new(StrValue::new(v), self.options))
+        }
+    }
+}
+
+impl<'a, 'de, V> Visitor<'de> for EmptyStringAsNone<'a, V>
+where
+    V: Visitor<'de>,
+{
+    type Value = V::Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.expecting(f)
+    }
+
+    fn visit_none<E>(self) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.inner.visit_none()
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.inner
+            .visit_some(Adapter::new(deserializer, self.options))
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.inner.visit_unit()
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visit_cow_str(Cow::Owned(v.to_owned()))
+    }
+
+    fn visit_borrowed_str<E>(self, v: &'de str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visit_cow_str(Cow::Borrowed(v))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visit_cow_str(Cow::Owned(v))
+    }
+
+    fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        self.inner
+            .visit_some(Adapter::new(SeqAccessDeserializer::new(seq), self.options))
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        self.inner
+            .visit_some(Adapter::new(MapAccessDeserializer::new(map), self.options))
+    }
+}
+
+/// Deserializer for a string value that parses scalars from the string, the same way
+/// `serde_urlencoded` does for its values.
+pub(super) struct StrValue<'de, E> {
+    value: Cow<'de, str>,
+    _marker: PhantomData<fn() -> E>,
+}
+
+impl<'de, E> StrValue<'de, E> {
+    pub(super) fn new(value: Cow<'de, str>) -> Self {
+        Self {
+            value,
+            _marker: PhantomData,
+        }
+    }
+}
+
+macro_rules! forward_parsed_value {
+    ($($ty:ident => $method:ident,)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                match self.value.parse::<$ty>() {
+                    Ok(value) => value.into_deserializer().$method(visitor),
+                    Err(err) => Err(de::Error::custom(err)),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de, E> Deserializer<'de> for StrValue<'de, E>
+where
+    E: de::Error,
+{
+    type Error = E;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Cow::Borrowed(value) => visitor.visit_borrowed_str(value),
+            Cow::Owned(value) => visitor.visit_string(value),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_enum(CowStrDeserializer::new(self.value))
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        char str string unit bytes byte_buf unit_struct tuple_struct struct identifier tuple
+        ignored_any seq map
+    }
+
+    forward_parsed_value! {
+        bool => deserialize_bool,
+        u8 => deserialize_u8,
+        u16 => deserialize_u16,
+        u32 => deserialize_u32,
+        u64 => deserialize_u64,
+        u128 => deserialize_u128,
+        i8 => deserialize_i8,
+        i16 => deserialize_i16,
+        i32 => deserialize_i32,
+        i64 => deserialize_i64,
+        i128 => deserialize_i128,
+        f32 => deserialize_f32,
+        f64 => deserialize_f64,
+    }
+}
@@ -0,0 +1,176 @@
+use super::rejection::BodyDecodeError;
+use axum::{
+    body::{Bytes, HttpBody},
+    extract::{rejection::BytesRejection, FromRequest, RequestParts},
+    http::{header, HeaderMap, Request},
+    BoxError,
+};
+use http_body::Limited;
+use std::{
+    io::{self, Read},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Decompression of bodies with a `Content-Encoding`, set with
+/// [`JsonConfig::decompress`](super::JsonConfig::decompress).
+///
+/// Each supported encoding requires a feature:
+///
+/// Encoding | Feature
+/// ---|---
+/// `gzip` | `gzip`
+/// `deflate` | `deflate`
+/// `br` | `br`
+/// `zstd` | `zstd`
+///
+/// Bodies with an encoding that isn't supported, or that fail to decompress, are rejected with
+/// `FailedToBufferBody::UnknownBodyError`, which has the status `400 Bad Request` and a
+/// [`BodyDecodeError`] as its source. Bodies that are larger than
+/// [`max_size`](Self::max_size) once decompressed are rejected with
+/// `FailedToBufferBody::LengthLimitError`, which has the status `413 Payload Too Large`.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{Decompression, JsonConfig};
+///
+/// let config = JsonConfig::new().decompress(Decompression::new().max_size(1024 * 1024));
+/// # let _: JsonConfig<axum::body::Body> = config;
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Decompression {
+    max_size: usize,
+}
+
+impl Decompression {
+    /// Create a new `Decompression` that decompresses every encoding whose feature is enabled.
+    pub fn new() -> Self {
+        Self {
+            max_size: 2 * 1024 * 1024,
+        }
+    }
+
+    /// Set the maximum size of decompressed bodies, in bytes.
+    ///
+    /// This protects against small bodies that decompress into huge ones, often called zip
+    /// bombs. [`JsonConfig::max_body_size`](super::JsonConfig::max_body_size) applies to the
+    /// compressed body.
+    ///
+    /// Defaults to 2 MiB.
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    // Decompress `bytes` according to the `Content-Encoding` of the request.
+    pub(crate) async fn decode(
+        &self,
+        headers: &HeaderMap,
+        bytes: Bytes,
+    ) -> Result<Bytes, BytesRejection> {
+        let encodings = headers
+            .get_all(header::CONTENT_ENCODING)
+            .iter()
+            .flat_map(|value| value.to_str().unwrap_or_default().split(','))
+            .map(str::trim)
+            .filter(|encoding| !encoding.is_empty() && !encoding.eq_ignore_ascii_case("identity"))
+            .collect::<Vec<_>>();
+        if encodings.is_empty() {
+            return Ok(bytes);
+        }
+
+        // encodings are listed in the order they were applied, so undo them in reverse
+        let mut reader: Result<Box<dyn Read + Send>, BodyDecodeError> =
+            Ok(Box::new(io::Cursor::new(bytes)));
+        for encoding in encodings.into_iter().rev() {
+            reader = reader.and_then(|reader| decoder(encoding, reader));
+        }
+
+        let body = Limited::new(DecodingBody { reader }, self.max_size);
+        Bytes::from_request(&mut RequestParts::new(Request::new(body))).await
+    }
+}
+
+impl Default for Decompression {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg_attr(
+    not(any(
+        feature = "gzip",
+        feature = "deflate",
+        feature = "br",
+        feature = "zstd"
+    )),
+    allow(unused_variables)
+)]
+fn decoder(
+    encoding: &str,
+    reader: Box<dyn Read + Send>,
+) -> Result<Box<dyn Read + Send>, BodyDecodeError> {
+    let encoding = encoding.to_ascii_lowercase();
+    match encoding.as_str() {
+        #[cfg(feature = "gzip")]
+        "gzip" | "x-gzip" => Ok(Box::new(flate2::read::MultiGzDecoder::new(reader))),
+        // `deflate` in HTTP is the zlib format, not raw deflate
+        #[cfg(feature = "deflate")]
+        "deflate" => Ok(Box::new(flate2::read::ZlibDecoder::new(reader))),
+        #[cfg(feature = "br")]
+        "br" => Ok(Box::new(brotli_decompressor::Decompressor::new(
+            reader, 4096,
+        ))),
+        #[cfg(feature = "zstd")]
+        "zstd" => zstd::stream::read::Decoder::new(reader)
+            .map(|decoder| Box::new(decoder) as _)
+            .map_err(BodyDecodeError::invalid),
+        _ => Err(BodyDecodeError::unsupported(encoding)),
+    }
+}
+
+// Body that decompresses the buffered body a chunk at a time, so `Limited` stops it once it
+// reaches the maximum size.
+struct DecodingBody {
+    reader: Result<Box<dyn Read + Send>, BodyDecodeError>,
+}
+
+impl HttpBody for DecodingBody {
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_data(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        let this = self.get_mut();
+        let reader = match &mut this.reader {
+            Ok(reader) => reader,
+            Err(_) => {
+                let err = std::mem::replace(&mut this.reader, Ok(Box::new(io::empty())));
+                return Poll::Ready(err.err().map(|err| Err(err.into())));
+            }
+        };
+
+        let mut chunk = vec![0; 8 * 1024];
+        match reader.read(&mut chunk) {
+            Ok(0) => Poll::Ready(None),
+            Ok(len) => {
+                chunk.truncate(len);
+                Poll::Ready(Some(Ok(Bytes::from(chunk))))
+            }
+            Err(err) => {
+                this.reader = Ok(Box::new(io::empty()));
+                Poll::Ready(Some(Err(BodyDecodeError::invalid(err).into())))
+            }
+        }
+    }
+
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        Poll::Ready(Ok(None))
+    }
+}
@@ -0,0 +1,251 @@
+use super::{
+    config,
+    custom::{reject_custom, CustomRejectionHandler},
+    rejection::ConfiguredRejection,
+    Config, RejectionContext,
+};
+use axum::{
+    async_trait,
+    extract::{FromRequest, RequestParts},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::de::DeserializeOwned;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    sync::Arc,
+};
+
+/// Config type for [`Filters`].
+///
+/// Only the fields listed with [`fields`](Self::fields) can be filtered by.
+pub struct FilterConfig<B> {
+    fields: Arc<HashSet<String>>,
+    param: Arc<str>,
+    rejection_handler: Option<CustomRejectionHandler<FilterError, B>>,
+    override_status: Option<StatusCode>,
+    allow_override: bool,
+}
+
+impl<B> FilterConfig<B> {
+    /// Create a new `FilterConfig`.
+    ///
+    /// No fields can be filtered by until they're set with [`fields`](Self::fields).
+    pub fn new() -> Self {
+        Self {
+            fields: Arc::default(),
+            param: "filter".into(),
+            rejection_handler: None,
+            override_status: None,
+            allow_override: false,
+        }
+    }
+
+    /// Set the fields that can be filtered by.
+    pub fn fields<I, S>(mut self, fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.fields = Arc::new(fields.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Set the name of the query parameter the fields are in, as in `filter[status]`.
+    ///
+    /// Defaults to `filter`.
+    pub fn param(mut self, param: &str) -> Self {
+        self.param = param.into();
+        self
+    }
+
+    /// Set the rejection handler function.
+    pub fn rejection_handler<F, R>(mut self, f: F) -> Self
+    where
+        F: Fn(FilterError, RejectionContext<'_, B>) -> R + Send + Sync + 'static,
+        R: IntoResponse,
+    {
+        self.rejection_handler = Some(Arc::new(move |error, ctx| f(error, ctx).into_response()));
+        self
+    }
+
+    /// Set the status code of the default rejection responses.
+    pub fn override_status(mut self, status: StatusCode) -> Self {
+        self.override_status = Some(status);
+        self
+    }
+
+    /// Allow layers further in to override this config.
+    ///
+    /// See [`Config::allow_override`].
+    pub fn allow_override(mut self) -> Self {
+        self.allow_override = true;
+        self
+    }
+
+    fn parse<T>(&self, query: &str) -> Result<T, FilterError>
+    where
+        T: DeserializeOwned,
+    {
+        let params = serde_urlencoded::from_str::<Vec<(String, String)>>(query)
+            .map_err(|err| FilterError::Invalid(err.to_string()))?;
+
+        let mut filters = Vec::new();
+        for (name, value) in params {
+            let field = match name
+                .strip_prefix(&*self.param)
+                .and_then(|name| name.strip_prefix('['))
+                .and_then(|name| name.strip_suffix(']'))
+            {
+                Some(field) => field,
+                None => continue,
+            };
+            if !self.fields.contains(field) {
+                return Err(FilterError::NotFilterable(field.to_owned()));
+            }
+            filters.push((field.to_owned(), value));
+        }
+
+        let filters = serde_urlencoded::to_string(&filters)
+            .map_err(|err| FilterError::Invalid(err.to_string()))?;
+        serde_urlencoded::from_str(&filters).map_err(|err| FilterError::Invalid(err.to_string()))
+    }
+}
+
+impl<B> Clone for FilterConfig<B> {
+    fn clone(&self) -> Self {
+        Self {
+            fields: self.fields.clone(),
+            param: self.param.clone(),
+            rejection_handler: self.rejection_handler.clone(),
+            override_status: self.override_status,
+            allow_override: self.allow_override,
+        }
+    }
+}
+
+impl<B> Default for FilterConfig<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B> fmt::Debug for FilterConfig<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FilterConfig")
+            .field("fields", &self.fields)
+            .field("param", &self.param)
+            .field("override_status", &self.override_status)
+            .field("allow_override", &self.allow_override)
+            .finish()
+    }
+}
+
+impl<S, B> tower_layer::Layer<S> for FilterConfig<B> {
+    type Service = <Config<Self, B> as tower_layer::Layer<S>>::Service;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        let mut config: Config<_, B> = Config::new(self.clone());
+        if self.allow_override {
+            config = config.allow_override();
+        }
+        config.layer(inner)
+    }
+}
+
+/// Extractor for filter parameters such as `?filter[status]=open&filter[author]=alice`.
+///
+/// The filters are deserialized into `T` as if they were a query string without the
+/// `filter[...]` around the names, so `T` can be a struct with optional fields, or the default
+/// map of names to values. Other query parameters are ignored.
+///
+/// Fields that aren't allowed by [`FilterConfig`] are rejected with `400 Bad Request`.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{FilterConfig, Filters};
+/// use axum::{routing::get, Router};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct IssueFilters {
+///     status: Option<String>,
+///     author: Option<String>,
+/// }
+///
+/// async fn handler(Filters(filters): Filters<IssueFilters>) {}
+///
+/// let app = Router::new()
+///     .route("/issues", get(handler))
+///     .layer(FilterConfig::new().fields(["status", "author"]));
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Filters<T = HashMap<String, String>>(pub T);
+
+#[async_trait]
+impl<T, B> FromRequest<B> for Filters<T>
+where
+    T: DeserializeOwned + Send,
+    B: Send + 'static,
+{
+    type Rejection = ConfiguredRejection<FilterError>;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let config = config::get_shared::<FilterConfig<B>, B>(req.extensions());
+        let result = match &config {
+            Some(config) => config.parse(req.uri().query().unwrap_or_default()),
+            None => Err(FilterError::MissingConfig),
+        };
+
+        result.map(Self).map_err(|error| {
+            let config = config.unwrap_or_default();
+            reject_custom::<Self, _, _>(
+                "Filters",
+                error,
+                config.rejection_handler.as_ref(),
+                config.override_status,
+                req,
+            )
+        })
+    }
+}
+
+/// Why [`Filters`] rejected the request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FilterError {
+    /// The field isn't one of the fields allowed by [`FilterConfig::fields`].
+    NotFilterable(String),
+    /// The filters couldn't be deserialized, with the underlying error.
+    Invalid(String),
+    /// [`FilterConfig`] wasn't added, so no fields can be filtered by.
+    MissingConfig,
+}
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFilterable(field) => write!(f, "Cannot filter by `{}`", field),
+            Self::Invalid(err) => write!(f, "Invalid filter parameters: {}", err),
+            Self::MissingConfig => write!(
+                f,
+                "Missing `FilterConfig`. `Filters` requires the fields that can be filtered by"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FilterError {}
+
+impl IntoResponse for FilterError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            Self::NotFilterable(_) | Self::Invalid(_) => StatusCode::BAD_REQUEST,
+            Self::MissingConfig => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, self.to_string()).into_response()
+    }
+}
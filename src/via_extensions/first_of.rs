@@ -0,0 +1,386 @@
+use super::{
+    buffer::extract_from_copy,
+    combined,
+    custom::{reject_custom, CustomRejectionHandler},
+    rejection::ConfiguredRejection,
+    Config, RejectionContext, RejectionInfo,
+};
+use axum::{
+    async_trait,
+    body::{Bytes, HttpBody},
+    extract::{FromRequest, RequestParts},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    BoxError,
+};
+use serde_json::{json, Value};
+use std::{fmt, sync::Arc};
+
+/// Extractor that tries several extractors in order and uses the first one that succeeds.
+///
+/// The extractors are listed with [`Either`], [`Either3`], or [`Either4`], and the variant of the
+/// extractor that succeeded is returned. This allows accepting the same payload in several
+/// formats, such as JSON and forms. The body is buffered so each extractor can read it, and put
+/// back afterwards so later extractors can read it as well.
+///
+/// If all the extractors reject the request the rejection is handled by [`FirstOfConfig`]. By
+/// default it responds with the rejection of the last extractor.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{Either, FirstOf, Form, Json};
+/// use axum::{routing::post, Router};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Signup {
+///     username: String,
+/// }
+///
+/// async fn handler(FirstOf(signup): FirstOf<Either<Json<Signup>, Form<Signup>>>) {
+///     let signup = match signup {
+///         Either::E1(Json(signup)) => signup,
+///         Either::E2(Form(signup)) => signup,
+///     };
+/// }
+///
+/// let app = Router::new().route("/", post(handler));
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FirstOf<T>(pub T);
+
+#[async_trait]
+impl<T, B> FromRequest<B> for FirstOf<T>
+where
+    T: Alternatives<B> + Send,
+    B: HttpBody + From<Bytes> + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    type Rejection = ConfiguredRejection<FirstOfRejection>;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let config = combined::get::<FirstOfConfig<B>, B>(req).unwrap_or_default();
+
+        // extractors that don't read the body can still be tried if it has already been taken
+        let bytes = if req.body().is_some() {
+            match Bytes::from_request(req).await {
+                Ok(bytes) => bytes,
+                Err(rejection) => {
+                    let rejection = FirstOfRejection {
+                        attempts: vec![Attempt::new(rejection.into_response())],
+                        all_errors: config.all_errors,
+                    };
+                    return Err(config.reject::<T>(rejection, req));
+                }
+            }
+        } else {
+            Bytes::new()
+        };
+
+        let mut attempts = Vec::new();
+        let result = T::first_of(req, &bytes, &mut attempts).await;
+        *req.body_mut() = Some(B::from(bytes));
+
+        match result {
+            Some(value) => Ok(Self(value)),
+            None => {
+                let rejection = FirstOfRejection {
+                    attempts,
+                    all_errors: config.all_errors,
+                };
+                Err(config.reject::<T>(rejection, req))
+            }
+        }
+    }
+}
+
+/// Two extractors for [`FirstOf`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Either<E1, E2> {
+    #[allow(missing_docs)]
+    E1(E1),
+    #[allow(missing_docs)]
+    E2(E2),
+}
+
+/// Three extractors for [`FirstOf`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Either3<E1, E2, E3> {
+    #[allow(missing_docs)]
+    E1(E1),
+    #[allow(missing_docs)]
+    E2(E2),
+    #[allow(missing_docs)]
+    E3(E3),
+}
+
+/// Four extractors for [`FirstOf`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Either4<E1, E2, E3, E4> {
+    #[allow(missing_docs)]
+    E1(E1),
+    #[allow(missing_docs)]
+    E2(E2),
+    #[allow(missing_docs)]
+    E3(E3),
+    #[allow(missing_docs)]
+    E4(E4),
+}
+
+// The extractors `FirstOf` tries, in order.
+#[allow(unreachable_pub)]
+#[async_trait]
+pub trait Alternatives<B>: Sized + sealed::Sealed {
+    async fn first_of(
+        req: &mut RequestParts<B>,
+        bytes: &Bytes,
+        attempts: &mut Vec<Attempt>,
+    ) -> Option<Self>;
+}
+
+macro_rules! impl_alternatives {
+    ($either:ident, $($ty:ident),*) => {
+        impl<$($ty),*> sealed::Sealed for $either<$($ty),*> {}
+
+        #[async_trait]
+        impl<B, $($ty),*> Alternatives<B> for $either<$($ty),*>
+        where
+            B: From<Bytes> + Send,
+            $($ty: FromRequest<B> + Send,)*
+        {
+            async fn first_of(
+                req: &mut RequestParts<B>,
+                bytes: &Bytes,
+                attempts: &mut Vec<Attempt>,
+            ) -> Option<Self> {
+                $(
+                    match extract_from_copy::<$ty, B>(req, bytes.clone()).await {
+                        Ok(value) => return Some(Self::$ty(value)),
+                        Err(rejection) => attempts.push(Attempt::new(rejection.into_response())),
+                    }
+                )*
+                None
+            }
+        }
+    };
+}
+
+impl_alternatives!(Either, E1, E2);
+impl_alternatives!(Either3, E1, E2, E3);
+impl_alternatives!(Either4, E1, E2, E3, E4);
+
+/// Rejection used for [`FirstOf`] when none of the extractors succeeded.
+///
+/// Responds with the rejection of the last extractor, or with every rejection if
+/// [`FirstOfConfig::all_errors`] is enabled.
+pub struct FirstOfRejection {
+    attempts: Vec<Attempt>,
+    all_errors: bool,
+}
+
+// The rejection of one of the extractors.
+#[allow(unreachable_pub)]
+#[derive(Debug)]
+pub struct Attempt {
+    response: Response,
+    info: Option<RejectionInfo>,
+}
+
+impl Attempt {
+    fn new(response: Response) -> Self {
+        let info = response.extensions().get::<RejectionInfo>().cloned();
+        Self { response, info }
+    }
+}
+
+impl FirstOfRejection {
+    /// The status codes of the rejections, in the order the extractors were tried.
+    pub fn statuses(&self) -> impl Iterator<Item = StatusCode> + '_ {
+        self.attempts
+            .iter()
+            .map(|attempt| attempt.response.status())
+    }
+
+    /// Information about the rejections, in the order the extractors were tried.
+    ///
+    /// This is `None` for extractors that aren't from this crate.
+    pub fn infos(&self) -> impl Iterator<Item = Option<&RejectionInfo>> + '_ {
+        self.attempts.iter().map(|attempt| attempt.info.as_ref())
+    }
+
+    /// Consume the rejection and get the rejection response of the last extractor.
+    pub fn into_last_response(self) -> Response {
+        match self.attempts.into_iter().last() {
+            Some(attempt) => attempt.response,
+            None => StatusCode::BAD_REQUEST.into_response(),
+        }
+    }
+}
+
+impl fmt::Debug for FirstOfRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FirstOfRejection")
+            .field("statuses", &self.statuses().collect::<Vec<_>>())
+            .field("infos", &self.infos().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl fmt::Display for FirstOfRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "None of the extractors accepted the request")?;
+        let mut separator = ": ";
+        for attempt in &self.attempts {
+            match &attempt.info {
+                Some(info) => write!(f, "{}{}", separator, info.message())?,
+                None => write!(f, "{}{}", separator, attempt.response.status())?,
+            }
+            separator = ", ";
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for FirstOfRejection {}
+
+impl IntoResponse for FirstOfRejection {
+    fn into_response(self) -> Response {
+        if !self.all_errors {
+            return self.into_last_response();
+        }
+
+        let status = self.statuses().last().unwrap_or(StatusCode::BAD_REQUEST);
+        let errors = self
+            .attempts
+            .iter()
+            .map(|attempt| {
+                let mut error = json!({ "status": attempt.response.status().as_u16() });
+                if let Some(info) = &attempt.info {
+                    error["extractor"] = Value::from(info.extractor());
+                    error["message"] = Value::from(info.message());
+                }
+                error
+            })
+            .collect::<Vec<_>>();
+        (status, axum::Json(json!({ "errors": errors }))).into_response()
+    }
+}
+
+/// Config type for [`FirstOf`].
+pub struct FirstOfConfig<B> {
+    rejection_handler: Option<CustomRejectionHandler<FirstOfRejection, B>>,
+    override_status: Option<StatusCode>,
+    all_errors: bool,
+    allow_override: bool,
+}
+
+impl<B> FirstOfConfig<B> {
+    /// Create a new `FirstOfConfig`.
+    pub fn new() -> Self {
+        Self {
+            rejection_handler: None,
+            override_status: None,
+            all_errors: false,
+            allow_override: false,
+        }
+    }
+
+    /// Set the rejection handler function.
+    pub fn rejection_handler<F, R>(mut self, f: F) -> Self
+    where
+        F: Fn(FirstOfRejection, RejectionContext<'_, B>) -> R + Send + Sync + 'static,
+        R: IntoResponse,
+    {
+        self.rejection_handler = Some(Arc::new(move |rejection, ctx| {
+            f(rejection, ctx).into_response()
+        }));
+        self
+    }
+
+    /// Set the status code of the default rejection responses.
+    pub fn override_status(mut self, status: StatusCode) -> Self {
+        self.override_status = Some(status);
+        self
+    }
+
+    /// Set whether the rejections of every extractor are included in the response.
+    ///
+    /// When enabled the response has the status of the last rejection and a body such as
+    /// `{"errors":[{"status":415,"extractor":"Json","message":"..."},...]}`, with the extractor
+    /// and message of the rejections from this crate.
+    ///
+    /// Defaults to `false`, in which case the response is the rejection of the last extractor.
+    pub fn all_errors(mut self, all_errors: bool) -> Self {
+        self.all_errors = all_errors;
+        self
+    }
+
+    /// Allow layers further in to override this config.
+    ///
+    /// See [`Config::allow_override`].
+    pub fn allow_override(mut self) -> Self {
+        self.allow_override = true;
+        self
+    }
+
+    fn reject<T>(
+        &self,
+        rejection: FirstOfRejection,
+        req: &RequestParts<B>,
+    ) -> ConfiguredRejection<FirstOfRejection> {
+        reject_custom::<T, _, _>(
+            "FirstOf",
+            rejection,
+            self.rejection_handler.as_ref(),
+            self.override_status,
+            req,
+        )
+    }
+}
+
+impl<B> Clone for FirstOfConfig<B> {
+    fn clone(&self) -> Self {
+        Self {
+            rejection_handler: self.rejection_handler.clone(),
+            override_status: self.override_status,
+            all_errors: self.all_errors,
+            allow_override: self.allow_override,
+        }
+    }
+}
+
+impl<B> Default for FirstOfConfig<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B> fmt::Debug for FirstOfConfig<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FirstOfConfig")
+            .field("override_status", &self.override_status)
+            .field("all_errors", &self.all_errors)
+            .field("allow_override", &self.allow_override)
+            .finish()
+    }
+}
+
+impl<S, B> tower_layer::Layer<S> for FirstOfConfig<B> {
+    type Service = <Config<Self, B> as tower_layer::Layer<S>>::Service;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        let mut config: Config<_, B> = Config::new(self.clone());
+        if self.allow_override {
+            config = config.allow_override();
+        }
+        config.layer(inner)
+    }
+}
+
+mod sealed {
+    #[allow(unreachable_pub)]
+    pub trait Sealed {}
+}
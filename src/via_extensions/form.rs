@@ -0,0 +1,343 @@
+use super::{
+    context::BodySize,
+    de,
+    policy::RejectionPolicy,
+    rejection::{BytesRejection, FormBodyTooLarge, FormRejection, InvalidFormContentType},
+    transform_body, ExtractError, FormConfig, QueryParser, RejectedBody, Utf8Policy,
+};
+use axum::{
+    body::{Bytes, HttpBody},
+    extract::{
+        rejection::{BodyAlreadyExtracted, FailedToBufferBody},
+        FromRequest, RequestParts,
+    },
+    http::{header, Method, Request},
+    response::IntoResponse,
+    BoxError,
+};
+use http_body::Limited;
+use serde::de::DeserializeOwned;
+use std::sync::Arc;
+
+impl<B> FormConfig<B> {
+    /// Set the parser used to deserialize forms.
+    ///
+    /// Forms use the same encoding as query strings, so this supports the same parsers as
+    /// [`QueryConfig::parser`](super::QueryConfig::parser). For example
+    /// `QueryParser::Qs { strict: false }` supports Rails and PHP style keys such as
+    /// `items[]=a&items[]=b` and `user[name]=x`.
+    ///
+    /// Defaults to [`QueryParser::UrlEncoded`].
+    pub fn parser(mut self, parser: QueryParser) -> Self {
+        self.parser = parser;
+        self
+    }
+
+    /// Set the maximum size of the request body, in bytes.
+    ///
+    /// Larger bodies are rejected with [`FormBodyTooLarge`]. Requests with a `Content-Length`
+    /// header above the limit are rejected before the body is read, otherwise the body is read
+    /// until it exceeds the limit.
+    ///
+    /// Defaults to no limit.
+    pub fn max_body_size(mut self, max_body_size: usize) -> Self {
+        self.max_body_size = Some(max_body_size);
+        self
+    }
+
+    // Apply the limits of a `RejectionPolicy` that are specific to `Form`.
+    pub(super) fn apply_policy_limits(mut self, policy: &RejectionPolicy) -> Self {
+        self.max_body_size = policy.max_body_size;
+        self
+    }
+
+    /// Set whether `GET` requests read the form from the query string.
+    ///
+    /// Like `axum::extract::Form`, the form is read from the query string for `GET` requests and
+    /// from the body for other methods. When disabled `GET` requests are treated like other
+    /// methods, so they're rejected with [`InvalidFormContentType`] unless they have a form body.
+    ///
+    /// Defaults to `true`.
+    pub fn allow_query_on_get(mut self, allow_query_on_get: bool) -> Self {
+        self.allow_query_on_get = allow_query_on_get;
+        self
+    }
+
+    /// Give up to `max_size` bytes of the body to the rejection handler.
+    ///
+    /// If the body is rejected the rejection handler can get it from
+    /// [`RejectionContext::body`](super::RejectionContext::body), to log the offending payload or
+    /// include an excerpt of it in the response. Be careful not to echo sensitive data back to
+    /// clients.
+    ///
+    /// Defaults to not giving the body to the rejection handler.
+    pub fn capture_body(mut self, max_size: usize) -> Self {
+        self.capture_body = Some(max_size);
+        self
+    }
+
+    /// Set a function that changes the body before it's deserialized.
+    ///
+    /// This allows accepting bodies that need some work before they're valid forms, such as
+    /// decoding a base64 encoded payload. Returning `Err` rejects the request with that response,
+    /// without running the rejection handlers. Forms read from the query string of `GET`
+    /// requests are not changed.
+    ///
+    /// See [`JsonConfig::transform_body`](super::JsonConfig::transform_body) for an example.
+    pub fn transform_body<F, R>(mut self, f: F) -> Self
+    where
+        F: Fn(Bytes) -> Result<Bytes, R> + Send + Sync + 'static,
+        R: IntoResponse,
+    {
+        self.transform_body = Some(Arc::new(move |bytes| {
+            f(bytes).map_err(|err| Box::new(err.into_response()))
+        }));
+        self
+    }
+
+    /// Set how bodies that aren't valid UTF-8 are handled.
+    ///
+    /// Also applies to forms read from the query string of `GET` requests.
+    ///
+    /// Defaults to replacing invalid UTF-8 with `U+FFFD`, like [`Utf8Policy::Lossy`].
+    pub fn utf8_policy(mut self, utf8_policy: Utf8Policy) -> Self {
+        self.utf8_policy = Some(utf8_policy);
+        self
+    }
+
+    /// Set whether booleans can also be written as `1/0`, `on/off`, or `yes/no`.
+    ///
+    /// HTML checkboxes are submitted as `on` by default, so this is useful for forms that
+    /// deserialize checkboxes into `bool` fields. The values are matched case-insensitively.
+    ///
+    /// Defaults to `false`.
+    pub fn lenient_bools(mut self, lenient_bools: bool) -> Self {
+        self.options.lenient_bools = lenient_bools;
+        self
+    }
+
+    /// Set whether empty values deserialize into `None` for `Option` fields.
+    ///
+    /// For example `?name=` would deserialize into `None` for a `name: Option<u32>` field,
+    /// rather than failing because the empty string isn't a number. HTML forms submit empty
+    /// inputs as empty strings, so this is useful for optional fields in forms.
+    ///
+    /// Defaults to `false`.
+    pub fn empty_string_as_none(mut self, empty_string_as_none: bool) -> Self {
+        self.options.empty_string_as_none = empty_string_as_none;
+        self
+    }
+
+    /// Set aliases for keys, as pairs of `(alias, key)`.
+    ///
+    /// Values given for an alias populate the field named by the key. This is useful when
+    /// migrating clients between naming conventions, for example with
+    /// `key_aliases([("PageSize", "page_size")])` both `?PageSize=10` and `?page_size=10`
+    /// populate the `page_size` field.
+    ///
+    /// Defaults to no aliases.
+    pub fn key_aliases<I, K, V>(mut self, aliases: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.options.key_aliases = Arc::new(
+            aliases
+                .into_iter()
+                .map(|(alias, key)| (alias.into(), key.into()))
+                .collect(),
+        );
+        self
+    }
+
+    /// Set whether keys are matched against field names case-insensitively.
+    ///
+    /// This also applies to aliases set with [`key_aliases`](Self::key_aliases). Only ASCII
+    /// characters are compared case-insensitively.
+    ///
+    /// Defaults to `false`.
+    pub fn case_insensitive_keys(mut self, case_insensitive_keys: bool) -> Self {
+        self.options.case_insensitive_keys = case_insensitive_keys;
+        self
+    }
+
+    /// Add a parser for the values of a key.
+    ///
+    /// The parser receives the raw value and returns the string that is then deserialized into
+    /// the field. This allows accepting several formats for a field without wrapping its type in
+    /// a newtype. For example a timestamp field can accept both RFC 3339 and `YYYYMMDD`, by
+    /// converting the latter into RFC 3339 before it's given to `chrono` or `time`.
+    ///
+    /// Errors returned by the parser reject the request like other deserialization errors.
+    ///
+    /// `key` is matched against the field name, after applying
+    /// [`key_aliases`](Self::key_aliases) and [`case_insensitive_keys`](Self::case_insensitive_keys).
+    /// Adding a parser for a key that already has one replaces it.
+    pub fn scalar_parser<F, E>(mut self, key: impl Into<String>, parser: F) -> Self
+    where
+        F: Fn(&str) -> Result<String, E> + Send + Sync + 'static,
+        E: Into<BoxError>,
+    {
+        let parser: de::ScalarParser = Arc::new(move |value| parser(value).map_err(Into::into));
+        Arc::make_mut(&mut self.options.scalar_parsers).insert(key.into(), parser);
+        self
+    }
+}
+
+pub(super) async fn extract<T, B>(
+    config: &FormConfig<B>,
+    req: &mut RequestParts<B>,
+) -> Result<T, ExtractError<FormRejection>>
+where
+    B: HttpBody<Data = Bytes> + Send + 'static,
+    B::Error: Into<BoxError>,
+    T: DeserializeOwned,
+{
+    if config.allow_query_on_get && req.method() == Method::GET {
+        let query = req.uri().query().unwrap_or_default();
+        Ok(deserialize(config, query.as_bytes())?)
+    } else {
+        if !has_form_content_type(req) {
+            return Err(FormRejection::from(InvalidFormContentType::default()).into());
+        }
+
+        let bytes = buffer(config, req).await?;
+        if config.success_observer.is_some() {
+            req.extensions_mut().insert(BodySize(bytes.len()));
+        }
+        let bytes = transform_body(config.transform_body.as_ref(), bytes)?;
+        #[cfg(feature = "charset")]
+        let bytes = charset::transcode(req, bytes).map_err(FormRejection::from)?;
+
+        let result = deserialize(config, &bytes);
+        if let (Err(_), Some(max_size)) = (&result, config.capture_body) {
+            req.extensions_mut()
+                .insert(RejectedBody::new(&bytes, max_size));
+        }
+        Ok(result?)
+    }
+}
+
+async fn buffer<B>(
+    config: &FormConfig<B>,
+    req: &mut RequestParts<B>,
+) -> Result<Bytes, FormRejection>
+where
+    B: HttpBody<Data = Bytes> + Send + 'static,
+    B::Error: Into<BoxError>,
+{
+    match config.max_body_size {
+        Some(max_body_size) => buffer_limited(req, max_body_size).await,
+        None => Ok(Bytes::from_request(req).await?),
+    }
+}
+
+async fn buffer_limited<B>(
+    req: &mut RequestParts<B>,
+    max_body_size: usize,
+) -> Result<Bytes, FormRejection>
+where
+    B: HttpBody<Data = Bytes> + Send + 'static,
+    B::Error: Into<BoxError>,
+{
+    let content_length = req
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok()?.parse::<usize>().ok());
+    if content_length.is_some_and(|length| length > max_body_size) {
+        return Err(FormBodyTooLarge::new(max_body_size).into());
+    }
+
+    let body = req
+        .take_body()
+        .ok_or_else(|| BytesRejection::from(BodyAlreadyExtracted::default()))?;
+    let mut limited = RequestParts::new(Request::new(Limited::new(body, max_body_size)));
+    match Bytes::from_request(&mut limited).await {
+        Ok(bytes) => Ok(bytes),
+        Err(BytesRejection::FailedToBufferBody(FailedToBufferBody::LengthLimitError(_))) => {
+            Err(FormBodyTooLarge::new(max_body_size).into())
+        }
+        Err(rejection) => Err(rejection.into()),
+    }
+}
+
+fn deserialize<T, B>(config: &FormConfig<B>, input: &[u8]) -> Result<T, FormRejection>
+where
+    T: DeserializeOwned,
+{
+    if let Some(utf8_policy) = config.utf8_policy {
+        utf8_policy.check_form(input)?;
+    }
+    let value = config.parser.deserialize(input, &config.options)?;
+    Ok(value)
+}
+
+fn has_form_content_type<B>(req: &RequestParts<B>) -> bool {
+    req.headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .is_some_and(|content_type| content_type.starts_with("application/x-www-form-urlencoded"))
+}
+
+#[cfg(feature = "charset")]
+mod charset {
+    use super::super::rejection::UnsupportedFormCharset;
+    use axum::{body::Bytes, extract::RequestParts, http::header};
+    use encoding_rs::{Encoding, UTF_8};
+    use std::borrow::Cow;
+
+    /// Transcode a form body to UTF-8, according to the `charset` in the `Content-Type` header.
+    ///
+    /// Percent-encoded bytes are in the body's charset, so every key and value is decoded and
+    /// then encoded again as UTF-8.
+    pub(super) fn transcode<B>(
+        req: &RequestParts<B>,
+        bytes: Bytes,
+    ) -> Result<Bytes, UnsupportedFormCharset> {
+        let charset = match charset(req) {
+            Some(charset) => charset,
+            None => return Ok(bytes),
+        };
+
+        let encoding = Encoding::for_label(charset.as_bytes())
+            .ok_or_else(|| UnsupportedFormCharset::new(charset.to_owned()))?;
+        if encoding == UTF_8 {
+            return Ok(bytes);
+        }
+
+        let mut serializer = form_urlencoded::Serializer::new(String::new());
+        for pair in bytes.split(|b| *b == b'&').filter(|pair| !pair.is_empty()) {
+            let mut parts = pair.splitn(2, |b| *b == b'=');
+            let key = decode(encoding, parts.next().unwrap_or_default());
+            let value = decode(encoding, parts.next().unwrap_or_default());
+            serializer.append_pair(&key, &value);
+        }
+        Ok(Bytes::from(serializer.finish()))
+    }
+
+    fn charset<B>(req: &RequestParts<B>) -> Option<&str> {
+        req.headers()
+            .get(header::CONTENT_TYPE)?
+            .to_str()
+            .ok()?
+            .split(';')
+            .skip(1)
+            .find_map(|param| {
+                let (name, value) = param.split_once('=')?;
+                name.trim()
+                    .eq_ignore_ascii_case("charset")
+                    .then(|| value.trim().trim_matches('"'))
+            })
+    }
+
+    fn decode(encoding: &'static Encoding, input: &[u8]) -> String {
+        let input = input
+            .iter()
+            .map(|b| if *b == b'+' { b' ' } else { *b })
+            .collect::<Vec<_>>();
+        let input = Cow::from(percent_encoding::percent_decode(&input));
+        let (output, _) = encoding.decode_without_bom_handling(&input);
+        output.into_owned()
+    }
+}
@@ -0,0 +1,196 @@
+use super::{
+    i18n::{MessageCatalog, SharedMessageCatalog},
+    rejection::{AnyRejection, ErrorCode, RejectionKind},
+    Config, DocsLinks, RejectionContext, RejectionToResponseFn,
+};
+use axum::{
+    http::{HeaderName, HeaderValue},
+    response::IntoResponse,
+};
+use std::{collections::HashMap, fmt, sync::Arc};
+
+/// Config that applies to all extractors in this module.
+///
+/// The rejection handler receives an [`AnyRejection`], so applications with a single error format
+/// can handle rejections from [`Json`](super::Json), [`Query`](super::Query), and
+/// [`Form`](super::Form) with one handler, rather than adding a config for each.
+///
+/// Rejection handlers set on [`JsonConfig`](super::JsonConfig),
+/// [`QueryConfig`](super::QueryConfig), or [`FormConfig`](super::FormConfig) take precedence.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{
+///     rejection::AnyRejection,
+///     GlobalRejectionConfig,
+///     Json,
+///     Query,
+///     RejectionContext,
+/// };
+/// use axum::{Router, routing::post, http::StatusCode};
+/// use serde::Deserialize;
+/// use serde_json::{json, Value};
+///
+/// #[derive(Deserialize)]
+/// struct Params {}
+///
+/// #[derive(Deserialize)]
+/// struct Payload {}
+///
+/// async fn handler(Query(params): Query<Params>, Json(payload): Json<Payload>) {}
+///
+/// fn rejection_handler<B>(rejection: AnyRejection, ctx: RejectionContext<'_, B>) -> (StatusCode, Json<Value>) {
+///     (
+///         StatusCode::BAD_REQUEST,
+///         Json(json!({ "error": rejection.to_string() })),
+///     )
+/// }
+///
+/// let app = Router::new()
+///     .route("/", post(handler))
+///     .layer(GlobalRejectionConfig::new().rejection_handler(rejection_handler));
+/// # let _: Router = app;
+/// ```
+pub struct GlobalRejectionConfig<B> {
+    pub(super) rejection_handler: RejectionToResponseFn<AnyRejection, B>,
+    pub(super) error_codes: Arc<HashMap<RejectionKind, ErrorCode>>,
+    pub(super) message_catalog: Option<SharedMessageCatalog>,
+    pub(super) docs: Arc<DocsLinks>,
+    pub(super) verbose_errors_header: Option<(HeaderName, HeaderValue)>,
+    pub(super) request_id_header: Option<HeaderName>,
+    allow_override: bool,
+}
+
+impl<B> GlobalRejectionConfig<B> {
+    /// Create a new `GlobalRejectionConfig`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the rejection handler function.
+    pub fn rejection_handler<F, R>(mut self, f: F) -> Self
+    where
+        F: Fn(AnyRejection, RejectionContext<'_, B>) -> R + Send + Sync + 'static,
+        R: IntoResponse,
+    {
+        self.rejection_handler = Some(Arc::new(move |rejection, ctx| {
+            f(rejection, ctx).into_response()
+        }));
+        self
+    }
+
+    /// Set the application error code for a kind of rejection.
+    ///
+    /// Applies to all extractors, unless their config sets a code for the same kind. See
+    /// [`JsonConfig::error_code`](super::JsonConfig::error_code).
+    pub fn error_code(mut self, kind: RejectionKind, code: impl Into<ErrorCode>) -> Self {
+        Arc::make_mut(&mut self.error_codes).insert(kind, code.into());
+        self
+    }
+
+    /// Set the base URL of the documentation for rejections.
+    ///
+    /// Applies to all extractors, unless their config sets a base. See
+    /// [`JsonConfig::docs_url`](super::JsonConfig::docs_url).
+    pub fn docs_url(mut self, base: impl Into<String>) -> Self {
+        Arc::make_mut(&mut self.docs).base = Some(base.into());
+        self
+    }
+
+    /// Set the slug of the documentation link for a kind of rejection.
+    ///
+    /// Applies to all extractors, unless their config sets a slug for the same kind. See
+    /// [`JsonConfig::docs_url`](super::JsonConfig::docs_url).
+    pub fn docs_slug(mut self, kind: RejectionKind, slug: impl Into<String>) -> Self {
+        Arc::make_mut(&mut self.docs)
+            .slugs
+            .insert(kind, slug.into());
+        self
+    }
+
+    /// Only include the details of rejections in responses when the request has a header with
+    /// the given value.
+    ///
+    /// Applies to all extractors, unless their config sets a header. See
+    /// [`JsonConfig::verbose_errors_header`](super::JsonConfig::verbose_errors_header).
+    pub fn verbose_errors_header(mut self, name: HeaderName, token: HeaderValue) -> Self {
+        self.verbose_errors_header = Some((name, token));
+        self
+    }
+
+    /// Set the header with the ID of the request.
+    ///
+    /// Applies to all extractors, unless their config sets a header. See
+    /// [`JsonConfig::request_id_header`](super::JsonConfig::request_id_header).
+    pub fn request_id_header(mut self, name: HeaderName) -> Self {
+        self.request_id_header = Some(name);
+        self
+    }
+
+    /// Allow layers further in to override this config.
+    ///
+    /// See [`Config::allow_override`].
+    pub fn allow_override(mut self) -> Self {
+        self.allow_override = true;
+        self
+    }
+
+    /// Set the catalog of localized rejection messages.
+    ///
+    /// Applies to all extractors, unless their config sets a catalog. See
+    /// [`JsonConfig::message_catalog`](super::JsonConfig::message_catalog).
+    pub fn message_catalog<C>(mut self, catalog: C) -> Self
+    where
+        C: MessageCatalog,
+    {
+        self.message_catalog = Some(Arc::new(catalog));
+        self
+    }
+}
+
+impl<B> Clone for GlobalRejectionConfig<B> {
+    fn clone(&self) -> Self {
+        Self {
+            rejection_handler: self.rejection_handler.clone(),
+            error_codes: self.error_codes.clone(),
+            message_catalog: self.message_catalog.clone(),
+            docs: self.docs.clone(),
+            verbose_errors_header: self.verbose_errors_header.clone(),
+            request_id_header: self.request_id_header.clone(),
+            allow_override: self.allow_override,
+        }
+    }
+}
+
+impl<B> Default for GlobalRejectionConfig<B> {
+    fn default() -> Self {
+        Self {
+            rejection_handler: None,
+            error_codes: Arc::default(),
+            message_catalog: None,
+            docs: Arc::default(),
+            verbose_errors_header: None,
+            request_id_header: None,
+            allow_override: false,
+        }
+    }
+}
+
+impl<B> fmt::Debug for GlobalRejectionConfig<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GlobalRejectionConfig").finish()
+    }
+}
+
+impl<S, B> tower_layer::Layer<S> for GlobalRejectionConfig<B> {
+    type Service = <Config<Self, B> as tower_layer::Layer<S>>::Service;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        let mut config: Config<_, B> = Config::new(self.clone());
+        if self.allow_override {
+            config = config.allow_override();
+        }
+        config.layer(inner)
+    }
+}
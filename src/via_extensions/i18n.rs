@@ -0,0 +1,130 @@
+use super::rejection::RejectionKind;
+use axum::http::{header, HeaderMap};
+use std::{borrow::Cow, collections::HashMap, sync::Arc};
+
+/// A catalog of localized rejection messages.
+///
+/// Set it with `message_catalog` on the configs, such as
+/// [`JsonConfig::message_catalog`](super::JsonConfig::message_catalog), and get the message for a
+/// rejection from [`RejectionContext::localized_message`](super::RejectionContext::localized_message).
+///
+/// [`StaticMessageCatalog`] covers the common case of a fixed set of messages. Implement this
+/// trait to use another source of translations, such as Fluent bundles.
+pub trait MessageCatalog: Send + Sync + 'static {
+    /// Get the message for a kind of rejection.
+    ///
+    /// `languages` are the languages from the request's `Accept-Language` header, most preferred
+    /// first. Return `None` if there is no message for the kind.
+    fn message(&self, kind: RejectionKind, languages: &[&str]) -> Option<String>;
+}
+
+/// A [`MessageCatalog`] with a fixed set of messages.
+///
+/// Languages are matched case-insensitively, and `de-AT` falls back to `de` if there are no
+/// messages for `de-AT`.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{
+///     rejection::RejectionKind,
+///     GlobalRejectionConfig,
+///     StaticMessageCatalog,
+/// };
+///
+/// let catalog = StaticMessageCatalog::new()
+///     .message("en", RejectionKind::DeserializeError, "The request is invalid")
+///     .message("de", RejectionKind::DeserializeError, "Die Anfrage ist ungültig")
+///     .default_language("en");
+///
+/// let config = GlobalRejectionConfig::new().message_catalog(catalog);
+/// # let _: GlobalRejectionConfig<axum::body::Body> = config;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct StaticMessageCatalog {
+    messages: HashMap<String, HashMap<RejectionKind, Cow<'static, str>>>,
+    default_language: Option<String>,
+}
+
+impl StaticMessageCatalog {
+    /// Create a new empty `StaticMessageCatalog`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the message for a kind of rejection in a language.
+    pub fn message(
+        mut self,
+        language: &str,
+        kind: RejectionKind,
+        message: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        self.messages
+            .entry(language.to_ascii_lowercase())
+            .or_default()
+            .insert(kind, message.into());
+        self
+    }
+
+    /// Set the language used if none of the request's languages have a message.
+    ///
+    /// Defaults to no language, in which case there is no message.
+    pub fn default_language(mut self, language: &str) -> Self {
+        self.default_language = Some(language.to_ascii_lowercase());
+        self
+    }
+
+    fn lookup(&self, language: &str, kind: RejectionKind) -> Option<&str> {
+        self.messages
+            .get(language)
+            .and_then(|messages| messages.get(&kind))
+            .map(|message| &**message)
+    }
+}
+
+impl MessageCatalog for StaticMessageCatalog {
+    fn message(&self, kind: RejectionKind, languages: &[&str]) -> Option<String> {
+        languages
+            .iter()
+            .find_map(|language| {
+                let language = language.to_ascii_lowercase();
+                self.lookup(&language, kind).or_else(|| {
+                    let (primary, _) = language.split_once('-')?;
+                    self.lookup(primary, kind)
+                })
+            })
+            .or_else(|| self.lookup(self.default_language.as_deref()?, kind))
+            .map(ToOwned::to_owned)
+    }
+}
+
+pub(super) type SharedMessageCatalog = Arc<dyn MessageCatalog>;
+
+/// The languages from the `Accept-Language` header, most preferred first.
+pub(super) fn accepted_languages(headers: &HeaderMap) -> Vec<&str> {
+    let mut languages = headers
+        .get_all(header::ACCEPT_LANGUAGE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|range| {
+            let mut parts = range.split(';');
+            let language = parts.next()?.trim();
+            let quality = parts
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .map_or(Some(1.0), |quality| quality.trim().parse::<f32>().ok())?;
+            if language.is_empty() || language == "*" || quality <= 0.0 {
+                None
+            } else {
+                Some((language, quality))
+            }
+        })
+        .collect::<Vec<_>>();
+
+    // stable so languages with the same quality keep their order
+    languages.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+    languages
+        .into_iter()
+        .map(|(language, _)| language)
+        .collect()
+}
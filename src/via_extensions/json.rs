@@ -0,0 +1,760 @@
+#[cfg(feature = "schemars")]
+use super::context::JsonSchema;
+use super::{
+    context::{BodySize, DetailsRedacted, FieldError, FieldErrors, JsonPointer},
+    policy::RejectionPolicy,
+    rejection::{BodyTimeout, RejectionKind},
+    transform_body, Audit, Decompression, ExtractError, JsonConfig, RejectedBody, RejectionContext,
+    RejectionInfo, Utf8Policy,
+};
+use axum::{
+    body::{Bytes, Full, HttpBody},
+    extract::{
+        rejection::{
+            BytesRejection, JsonDataError, JsonRejection, JsonSyntaxError, MissingJsonContentType,
+        },
+        FromRequest, RequestParts,
+    },
+    http::{header, HeaderMap, Request},
+    response::IntoResponse,
+    BoxError,
+};
+use http_body::Limited;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use serde_path_to_error::{Path, Segment};
+use std::{
+    collections::HashMap,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+impl<B> JsonConfig<B> {
+    /// Give up to `max_size` bytes of the body to the rejection handler.
+    ///
+    /// If the body is rejected the rejection handler can get it from
+    /// [`RejectionContext::body`](super::RejectionContext::body), to log the offending payload or
+    /// include an excerpt of it in the response. Be careful not to echo sensitive data back to
+    /// clients.
+    ///
+    /// Defaults to not giving the body to the rejection handler.
+    pub fn capture_body(mut self, max_size: usize) -> Self {
+        self.capture_body = Some(max_size);
+        self
+    }
+
+    /// Set the maximum size of the body, in bytes.
+    ///
+    /// Larger bodies are rejected with `FailedToBufferBody::LengthLimitError`, which has the
+    /// status `413 Payload Too Large`. Requests with a `Content-Length` header above the limit are
+    /// rejected before the body is read, otherwise the body is read until it exceeds the limit.
+    ///
+    /// Defaults to no limit.
+    pub fn max_body_size(mut self, max_body_size: usize) -> Self {
+        self.max_body_size = Some(max_body_size);
+        self
+    }
+
+    /// Set the maximum time reading the body may take.
+    ///
+    /// Bodies that take longer, for example because the client sends them very slowly, are
+    /// rejected with `FailedToBufferBody::UnknownBodyError`, which has a
+    /// [`BodyTimeout`](super::rejection::BodyTimeout) as its source. The rejection goes through
+    /// the rejection handlers as usual and its default response has the status
+    /// `408 Request Timeout`.
+    ///
+    /// Defaults to no timeout.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use axum_extractor_config::via_extensions::JsonConfig;
+    /// use std::time::Duration;
+    ///
+    /// let config = JsonConfig::new().body_timeout(Duration::from_secs(10));
+    /// # let _: JsonConfig<axum::body::Body> = config;
+    /// ```
+    pub fn body_timeout(mut self, timeout: Duration) -> Self {
+        self.body_timeout = Some(timeout);
+        self
+    }
+
+    /// Set how bodies that aren't valid UTF-8 are handled.
+    ///
+    /// The policy applies after [`transform_body`](Self::transform_body). Rejected bodies have
+    /// the kind [`RejectionKind::InvalidUtf8`](super::rejection::RejectionKind::InvalidUtf8).
+    ///
+    /// Defaults to rejecting invalid UTF-8 as a syntax error, like `axum::Json`.
+    pub fn utf8_policy(mut self, utf8_policy: Utf8Policy) -> Self {
+        self.utf8_policy = Some(utf8_policy);
+        self
+    }
+
+    /// Decompress bodies according to their `Content-Encoding` before deserializing them.
+    ///
+    /// See [`Decompression`] for the supported encodings and how failures are rejected.
+    ///
+    /// Defaults to not decompressing bodies, so compressed bodies fail to parse.
+    pub fn decompress(mut self, decompression: Decompression) -> Self {
+        self.decompression = Some(decompression);
+        self
+    }
+
+    // Apply the limits of a `RejectionPolicy` that are specific to `Json`.
+    pub(super) fn apply_policy_limits(mut self, policy: &RejectionPolicy) -> Self {
+        self.max_body_size = policy.max_body_size;
+        self
+    }
+
+    /// Set a function that changes the body before it's deserialized.
+    ///
+    /// This allows accepting bodies that need some work before they're valid JSON, such as
+    /// stripping a byte order mark, unwrapping an envelope, or decoding a base64 encoded payload.
+    /// Returning `Err` rejects the request with that response, without running the rejection
+    /// handlers.
+    ///
+    /// The function runs after the body has been buffered, so
+    /// [`max_body_size`](Self::max_body_size) applies to the original body.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use axum_extractor_config::via_extensions::JsonConfig;
+    /// use axum::{body::Body, http::StatusCode};
+    ///
+    /// let config = JsonConfig::<Body>::new().transform_body(|bytes| {
+    ///     Ok::<_, StatusCode>(match bytes.strip_prefix(b"\xEF\xBB\xBF") {
+    ///         Some(rest) => bytes.slice(3..3 + rest.len()),
+    ///         None => bytes,
+    ///     })
+    /// });
+    /// ```
+    pub fn transform_body<F, R>(mut self, f: F) -> Self
+    where
+        F: Fn(Bytes) -> Result<Bytes, R> + Send + Sync + 'static,
+        R: IntoResponse,
+    {
+        self.transform_body = Some(Arc::new(move |bytes| {
+            f(bytes).map_err(|err| Box::new(err.into_response()))
+        }));
+        self
+    }
+
+    /// Include the JSON Schema of `T` in rejections of bodies that don't match it.
+    ///
+    /// When a body is valid JSON but fails to deserialize into `T`, rejection handlers can get
+    /// the part of the schema for the element that failed from
+    /// [`RejectionContext::json_schema`](super::RejectionContext::json_schema), and the
+    /// [presets](super::presets) include it as `schema`. This helps API clients find out what
+    /// they should have sent. Types without a schema added are rejected as usual.
+    ///
+    /// Requires the `schemars` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use axum_extractor_config::via_extensions::{presets, JsonConfig};
+    /// use schemars::JsonSchema;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize, JsonSchema)]
+    /// struct CreateUser {
+    ///     name: String,
+    ///     age: u32,
+    /// }
+    ///
+    /// let config = JsonConfig::new()
+    ///     .schema::<CreateUser>()
+    ///     .rejection_handler(presets::simple_json());
+    /// # let _: JsonConfig<axum::body::Body> = config;
+    /// ```
+    #[cfg(feature = "schemars")]
+    pub fn schema<T>(mut self) -> Self
+    where
+        T: schemars::JsonSchema + 'static,
+    {
+        let schema = serde_json::to_value(schemars::schema_for!(T))
+            .expect("schemas can be converted to JSON");
+        Arc::make_mut(&mut self.schemas).insert(std::any::TypeId::of::<T>(), Arc::new(schema));
+        self
+    }
+
+    /// Set whether to find every element of the body that doesn't match the target type.
+    ///
+    /// Deserialization stops at the first error, so by default rejections only describe one
+    /// invalid field. When enabled, bodies that are valid JSON but fail to deserialize are checked
+    /// again to find the other invalid fields, which rejection handlers can get from
+    /// [`RejectionContext::field_errors`](super::RejectionContext::field_errors). The
+    /// [presets](super::presets) include them as `errors`. This is useful for forms that show
+    /// every invalid field at once.
+    ///
+    /// Finding the other errors means deserializing the body again for each of them, replacing
+    /// the invalid values as it goes, so it only happens for rejected bodies and is limited to
+    /// the first 32 errors. Errors that are caused by earlier errors, such as an enum that cannot
+    /// be matched once its tag is invalid, might not be found.
+    ///
+    /// Defaults to `false`.
+    pub fn all_errors(mut self, all_errors: bool) -> Self {
+        self.all_errors = all_errors;
+        self
+    }
+
+    /// Set fields whose values are hidden from rejections.
+    ///
+    /// Fields are either names, such as `password`, which match fields with that name anywhere in
+    /// the body, or [JSON Pointers], such as `/card/number`, which match that field and everything
+    /// inside it.
+    ///
+    /// Their values are replaced with `"[REDACTED]"` in bodies captured with
+    /// [`capture_body`](Self::capture_body). Bodies that aren't valid JSON cannot be redacted so
+    /// they're not captured. If a redacted field fails to deserialize,
+    /// [`RejectionContext::details_redacted`](super::RejectionContext::details_redacted) returns
+    /// `true` and the default rejection response leaves out the deserialization error, since it
+    /// might contain the value.
+    ///
+    /// Defaults to no fields.
+    ///
+    /// [JSON Pointers]: https://www.rfc-editor.org/rfc/rfc6901
+    pub fn redact<I, S>(mut self, fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.redact = Arc::new(fields.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Set the audit hook, which is called for every rejection with a redacted excerpt of the
+    /// body.
+    ///
+    /// See [`Audit`] for more details.
+    pub fn audit(mut self, audit: Audit) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// Set the handler for requests without a JSON `Content-Type`.
+    ///
+    /// Handlers for specific rejections are tried before the one set with
+    /// [`rejection_handler`](Self::rejection_handler), which handles all other rejections.
+    pub fn on_missing_content_type<F, R>(self, f: F) -> Self
+    where
+        F: Fn(MissingJsonContentType, RejectionContext<'_, B>) -> R + Send + Sync + 'static,
+        R: IntoResponse,
+    {
+        self.push_rejection_handler(move |rejection, ctx| match rejection {
+            JsonRejection::MissingJsonContentType(inner) => Ok(f(inner, ctx).into_response()),
+            other => Err(other),
+        })
+    }
+
+    /// Set the handler for bodies that are valid JSON but don't match the target type.
+    ///
+    /// Handlers for specific rejections are tried before the one set with
+    /// [`rejection_handler`](Self::rejection_handler), which handles all other rejections.
+    pub fn on_deserialize_error<F, R>(self, f: F) -> Self
+    where
+        F: Fn(JsonDataError, RejectionContext<'_, B>) -> R + Send + Sync + 'static,
+        R: IntoResponse,
+    {
+        self.push_rejection_handler(move |rejection, ctx| match rejection {
+            JsonRejection::JsonDataError(inner) => Ok(f(inner, ctx).into_response()),
+            other => Err(other),
+        })
+    }
+
+    /// Set the handler for bodies that aren't valid JSON.
+    ///
+    /// Handlers for specific rejections are tried before the one set with
+    /// [`rejection_handler`](Self::rejection_handler), which handles all other rejections.
+    pub fn on_syntax_error<F, R>(self, f: F) -> Self
+    where
+        F: Fn(JsonSyntaxError, RejectionContext<'_, B>) -> R + Send + Sync + 'static,
+        R: IntoResponse,
+    {
+        self.push_rejection_handler(move |rejection, ctx| match rejection {
+            JsonRejection::JsonSyntaxError(inner) => Ok(f(inner, ctx).into_response()),
+            other => Err(other),
+        })
+    }
+
+    /// Set the handler for errors buffering the body.
+    ///
+    /// Handlers for specific rejections are tried before the one set with
+    /// [`rejection_handler`](Self::rejection_handler), which handles all other rejections.
+    pub fn on_bytes_error<F, R>(self, f: F) -> Self
+    where
+        F: Fn(BytesRejection, RejectionContext<'_, B>) -> R + Send + Sync + 'static,
+        R: IntoResponse,
+    {
+        self.push_rejection_handler(move |rejection, ctx| match rejection {
+            JsonRejection::BytesRejection(inner) => Ok(f(inner, ctx).into_response()),
+            other => Err(other),
+        })
+    }
+}
+
+pub(super) async fn extract<T, B>(
+    config: &JsonConfig<B>,
+    req: &mut RequestParts<B>,
+) -> Result<T, ExtractError<JsonRejection>>
+where
+    B: HttpBody<Data = Bytes> + Send + 'static,
+    B::Error: Into<BoxError>,
+    T: DeserializeOwned + 'static,
+{
+    // check before reading the body, which is left in place if the request is rejected
+    if !has_json_content_type(req) {
+        let rejection = JsonRejection::from(MissingJsonContentType::default());
+        audit::<T, B>(config, req, &rejection, None);
+        return Err(rejection.into());
+    }
+    let bytes = match buffer(config, req).await {
+        Ok(bytes) => bytes,
+        Err(rejection) => {
+            audit::<T, B>(config, req, &rejection, None);
+            return Err(rejection.into());
+        }
+    };
+    if config.success_observer.is_some() {
+        req.extensions_mut().insert(BodySize(bytes.len()));
+    }
+    let bytes = transform_body(config.transform_body.as_ref(), bytes)?;
+    let bytes = match apply_utf8_policy(config, bytes.clone()).await {
+        Ok(bytes) => bytes,
+        Err(rejection) => {
+            audit::<T, B>(config, req, &rejection, Some(&bytes));
+            return Err(rejection.into());
+        }
+    };
+    match deserialize(config, req, bytes.clone()).await {
+        Ok(value) => Ok(value),
+        Err(rejection) => {
+            audit::<T, B>(config, req, &rejection, Some(&bytes));
+            Err(rejection.into())
+        }
+    }
+}
+
+fn audit<T, B>(
+    config: &JsonConfig<B>,
+    req: &RequestParts<B>,
+    rejection: &JsonRejection,
+    bytes: Option<&Bytes>,
+) {
+    if let Some(audit) = &config.audit {
+        let info = RejectionInfo::new::<T>("Json", RejectionKind::from(rejection));
+        audit.record(&info, rejection, req, bytes);
+    }
+}
+
+// The same content types `axum::Json` accepts: `application/json` and `application/*+json`.
+pub(super) fn has_json_content_type<B>(req: &RequestParts<B>) -> bool {
+    let content_type = match req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+    {
+        Some(content_type) => content_type,
+        None => return false,
+    };
+    let essence = content_type.split(';').next().unwrap_or_default().trim();
+    match essence.split_once('/') {
+        Some((type_, subtype)) => {
+            type_.eq_ignore_ascii_case("application")
+                && (subtype.eq_ignore_ascii_case("json")
+                    || subtype.to_ascii_lowercase().ends_with("+json"))
+        }
+        None => false,
+    }
+}
+
+// Buffer the body ourselves so we still have it if `axum::Json` rejects it.
+pub(super) async fn buffer<B>(
+    config: &JsonConfig<B>,
+    req: &mut RequestParts<B>,
+) -> Result<Bytes, JsonRejection>
+where
+    B: HttpBody<Data = Bytes> + Send + 'static,
+    B::Error: Into<BoxError>,
+{
+    let bytes = match config.body_timeout {
+        Some(timeout) => match tokio::time::timeout(timeout, read(config, req)).await {
+            Ok(bytes) => bytes?,
+            Err(_) => return Err(buffer_error(BodyTimeout::new(timeout).into()).await.into()),
+        },
+        None => read(config, req).await?,
+    };
+    match &config.decompression {
+        Some(decompression) => Ok(decompression.decode(req.headers(), bytes).await?),
+        None => Ok(bytes),
+    }
+}
+
+pub(super) async fn apply_utf8_policy<B>(
+    config: &JsonConfig<B>,
+    bytes: Bytes,
+) -> Result<Bytes, JsonRejection> {
+    match config.utf8_policy {
+        Some(utf8_policy) => match utf8_policy.apply_json(bytes) {
+            Ok(bytes) => Ok(bytes),
+            Err(err) => Err(buffer_error(err.into()).await.into()),
+        },
+        None => Ok(bytes),
+    }
+}
+
+async fn read<B>(config: &JsonConfig<B>, req: &mut RequestParts<B>) -> Result<Bytes, BytesRejection>
+where
+    B: HttpBody<Data = Bytes> + Send + 'static,
+    B::Error: Into<BoxError>,
+{
+    match config.max_body_size {
+        Some(max_body_size) => {
+            let content_length = req
+                .headers()
+                .get(header::CONTENT_LENGTH)
+                .and_then(|value| value.to_str().ok()?.parse::<usize>().ok());
+            if content_length.is_some_and(|length| length > max_body_size) {
+                // `LengthLimitError` can't be created directly, so buffer a body that's over a
+                // limit of zero
+                let body = Limited::new(Full::new(Bytes::from_static(b" ")), 0);
+                return Err(
+                    Bytes::from_request(&mut RequestParts::new(Request::new(body)))
+                        .await
+                        .expect_err("the body is over the limit"),
+                );
+            }
+
+            let body = req.take_body().ok_or_else(|| {
+                BytesRejection::from(axum::extract::rejection::BodyAlreadyExtracted::default())
+            })?;
+            let mut limited = RequestParts::new(Request::new(Limited::new(body, max_body_size)));
+            Bytes::from_request(&mut limited).await
+        }
+        None => Bytes::from_request(req).await,
+    }
+}
+
+// The rejection for a body that fails with `err`. axum's rejections can only be created by
+// buffering a body, so this buffers one that fails.
+async fn buffer_error(err: BoxError) -> BytesRejection {
+    struct ErrorBody(Option<BoxError>);
+
+    impl HttpBody for ErrorBody {
+        type Data = Bytes;
+        type Error = BoxError;
+
+        fn poll_data(
+            mut self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+        ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+            Poll::Ready(self.0.take().map(Err))
+        }
+
+        fn poll_trailers(
+            self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+        ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+            Poll::Ready(Ok(None))
+        }
+    }
+
+    let mut req = RequestParts::new(Request::new(ErrorBody(Some(err))));
+    Bytes::from_request(&mut req)
+        .await
+        .expect_err("the body always fails")
+}
+
+async fn deserialize<T, B>(
+    config: &JsonConfig<B>,
+    req: &mut RequestParts<B>,
+    bytes: Bytes,
+) -> Result<T, JsonRejection>
+where
+    T: DeserializeOwned + 'static,
+{
+    let mut buffered = Request::new(Full::new(bytes.clone()));
+    *buffered.headers_mut() = req.headers().clone();
+    let mut buffered = RequestParts::new(buffered);
+
+    match buffered.extract::<axum::Json<T>>().await {
+        Ok(axum::Json(value)) => Ok(value),
+        Err(rejection) => {
+            if let JsonRejection::JsonDataError(_) = &rejection {
+                if let Some(pointer) = json_pointer::<T>(&bytes) {
+                    if is_redacted(&config.redact, &pointer.0) {
+                        req.extensions_mut().insert(DetailsRedacted);
+                    }
+                    #[cfg(feature = "schemars")]
+                    if let Some(schema) = config.schemas.get(&std::any::TypeId::of::<T>()) {
+                        req.extensions_mut()
+                            .insert(JsonSchema(schema::fragment(schema, &pointer.0)));
+                    }
+                    req.extensions_mut().insert(pointer);
+                }
+                if config.all_errors {
+                    let errors = field_errors::<T>(&bytes, &config.redact);
+                    req.extensions_mut().insert(FieldErrors(Arc::new(errors)));
+                }
+            }
+            if let Some(max_size) = config.capture_body {
+                if config.redact.is_empty() {
+                    req.extensions_mut()
+                        .insert(RejectedBody::new(&bytes, max_size));
+                } else if let Ok(mut value) = serde_json::from_slice::<Value>(&bytes) {
+                    redact(&config.redact, &mut value, &mut String::new());
+                    let bytes = Bytes::from(value.to_string());
+                    req.extensions_mut()
+                        .insert(RejectedBody::new(&bytes, max_size));
+                }
+            }
+            Err(rejection)
+        }
+    }
+}
+
+/// Deserialize the body again, this time tracking the path, to find where it failed.
+///
+/// This only happens for bodies that have already failed to deserialize, so successful requests
+/// don't pay for tracking the path.
+fn json_pointer<T>(bytes: &[u8]) -> Option<JsonPointer>
+where
+    T: DeserializeOwned,
+{
+    let mut deserializer = serde_json::Deserializer::from_slice(bytes);
+    let err = serde_path_to_error::deserialize::<_, T>(&mut deserializer).err()?;
+    Some(JsonPointer(pointer(err.path())))
+}
+
+pub(super) fn pointer(path: &Path) -> String {
+    let mut pointer = String::new();
+    for segment in path.iter() {
+        let segment = match segment {
+            Segment::Seq { index } => index.to_string(),
+            Segment::Map { key } => key.clone(),
+            Segment::Enum { variant } => variant.clone(),
+            // point at the closest element we know of
+            Segment::Unknown => break,
+        };
+        pointer.push('/');
+        pointer.push_str(&segment.replace('~', "~0").replace('/', "~1"));
+    }
+    pointer
+}
+
+const MAX_FIELD_ERRORS: usize = 32;
+
+// values tried in place of invalid ones, until one of them is accepted
+const PLACEHOLDERS: [fn() -> Value; 6] = [
+    || Value::Null,
+    || Value::from(0),
+    || Value::Bool(false),
+    || Value::String(String::new()),
+    || Value::Array(Vec::new()),
+    || Value::Object(Default::default()),
+];
+
+/// Find the errors of every element of the body that doesn't match the target type.
+///
+/// Deserialization stops at the first error, so each error is fixed by replacing the invalid
+/// value with a placeholder, or adding missing and removing unknown fields, and the body is
+/// deserialized again to find the next one. Errors inside placeholders are not reported, since
+/// the client didn't send them.
+fn field_errors<T>(bytes: &[u8], redact: &[String]) -> Vec<FieldError>
+where
+    T: DeserializeOwned,
+{
+    let mut value = match serde_json::from_slice::<Value>(bytes) {
+        Ok(value) => value,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut errors = Vec::new();
+    let mut placeholders = HashMap::<String, usize>::new();
+    for _ in 0..MAX_FIELD_ERRORS * 4 {
+        let err = match serde_path_to_error::deserialize::<_, T>(&value) {
+            Ok(_) => break,
+            Err(err) => err,
+        };
+        let pointer = pointer(err.path());
+        let message = err.into_inner().to_string();
+
+        let from_placeholder = placeholders.keys().any(|placeholder| {
+            pointer == *placeholder
+                || matches!(pointer.strip_prefix(placeholder.as_str()), Some(rest) if rest.starts_with('/'))
+        });
+        if !from_placeholder {
+            let message = if is_redacted(redact, &pointer) {
+                "Invalid value".to_owned()
+            } else {
+                message.clone()
+            };
+            errors.push(FieldError::new(pointer.clone(), message));
+            if errors.len() == MAX_FIELD_ERRORS {
+                break;
+            }
+        }
+
+        if let Some(field) = quoted_field(&message, "unknown field `") {
+            let removed = value
+                .pointer_mut(&pointer)
+                .and_then(Value::as_object_mut)
+                .and_then(|object| object.remove(field));
+            if removed.is_none() {
+                break;
+            }
+            continue;
+        }
+
+        // the element to replace with a placeholder
+        let target = match quoted_field(&message, "missing field `") {
+            Some(field) => format!(
+                "{}/{}",
+                pointer,
+                field.replace('~', "~0").replace('/', "~1")
+            ),
+            None if pointer.is_empty() => break,
+            None => pointer,
+        };
+        let attempt = placeholders.entry(target.clone()).or_insert(0);
+        let placeholder = match PLACEHOLDERS.get(*attempt) {
+            Some(placeholder) => placeholder(),
+            None => break,
+        };
+        *attempt += 1;
+        if !set(&mut value, &target, placeholder) {
+            break;
+        }
+    }
+    errors
+}
+
+// The field named in messages such as "missing field `name`".
+fn quoted_field<'a>(message: &'a str, prefix: &str) -> Option<&'a str> {
+    message.strip_prefix(prefix)?.split('`').next()
+}
+
+// Set the element at `pointer`, adding it to its object if it's missing.
+fn set(value: &mut Value, pointer: &str, element: Value) -> bool {
+    let (parent, key) = match pointer.rsplit_once('/') {
+        Some(split) => split,
+        None => return false,
+    };
+    let key = key.replace("~1", "/").replace("~0", "~");
+    match value.pointer_mut(parent) {
+        Some(Value::Object(object)) => {
+            object.insert(key, element);
+            true
+        }
+        Some(Value::Array(array)) => match key.parse::<usize>().ok().and_then(|i| array.get_mut(i))
+        {
+            Some(slot) => {
+                *slot = element;
+                true
+            }
+            None => false,
+        },
+        _ => false,
+    }
+}
+
+#[cfg(feature = "schemars")]
+mod schema {
+    use serde_json::Value;
+    use std::sync::Arc;
+
+    /// Find the part of `schema` that describes the element at `pointer`.
+    ///
+    /// Follows `properties`, `items`, and `additionalProperties`, resolving references to
+    /// `definitions` along the way. Stops at the closest schema it can find, such as when the
+    /// element is matched by one of several schemas with `anyOf`.
+    pub(super) fn fragment(schema: &Arc<Value>, pointer: &str) -> Arc<Value> {
+        let mut current = resolve(schema, schema);
+        for segment in pointer.split('/').skip(1) {
+            let segment = segment.replace("~1", "/").replace("~0", "~");
+            let next = current
+                .get("properties")
+                .and_then(|properties| properties.get(&segment))
+                .or_else(|| match current.get("items") {
+                    Some(Value::Array(items)) => items.get(segment.parse::<usize>().ok()?),
+                    items => items,
+                })
+                .or_else(|| current.get("additionalProperties"))
+                .filter(|next| next.is_object());
+            match next {
+                Some(next) => current = resolve(schema, next),
+                None => break,
+            }
+        }
+
+        if std::ptr::eq(current, &**schema) {
+            schema.clone()
+        } else {
+            Arc::new(current.clone())
+        }
+    }
+
+    // Follow `$ref`s to the root schema's `definitions`.
+    fn resolve<'a>(root: &'a Value, mut schema: &'a Value) -> &'a Value {
+        // a limit in case definitions refer to each other in a loop
+        for _ in 0..32 {
+            let definition = schema
+                .get("$ref")
+                .and_then(Value::as_str)
+                .and_then(|reference| reference.strip_prefix("#/definitions/"))
+                .and_then(|name| root.get("definitions")?.get(name));
+            match definition {
+                Some(definition) => schema = definition,
+                None => break,
+            }
+        }
+        schema
+    }
+}
+
+/// Whether the element at `pointer` is, or is inside, one of the redacted fields.
+fn is_redacted(fields: &[String], pointer: &str) -> bool {
+    fields.iter().any(|field| {
+        if field.starts_with('/') {
+            pointer == field
+                || matches!(pointer.strip_prefix(field.as_str()), Some(rest) if rest.starts_with('/'))
+        } else {
+            let field = field.replace('~', "~0").replace('/', "~1");
+            pointer.split('/').skip(1).any(|segment| segment == field)
+        }
+    })
+}
+
+pub(super) fn redact(fields: &[String], value: &mut Value, pointer: &mut String) {
+    let len = pointer.len();
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                pointer.push('/');
+                pointer.push_str(&key.replace('~', "~0").replace('/', "~1"));
+                if is_redacted(fields, pointer) {
+                    *value = Value::String("[REDACTED]".to_owned());
+                } else {
+                    redact(fields, value, pointer);
+                }
+                pointer.truncate(len);
+            }
+        }
+        Value::Array(array) => {
+            for (index, value) in array.iter_mut().enumerate() {
+                pointer.push('/');
+                pointer.push_str(&index.to_string());
+                if is_redacted(fields, pointer) {
+                    *value = Value::String("[REDACTED]".to_owned());
+                } else {
+                    redact(fields, value, pointer);
+                }
+                pointer.truncate(len);
+            }
+        }
+        _ => {}
+    }
+}
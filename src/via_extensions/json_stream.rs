@@ -0,0 +1,124 @@
+use axum::{
+    body::{Bytes, StreamBody},
+    http::{header, HeaderValue},
+    response::{IntoResponse, Response},
+};
+use futures_util::{
+    future,
+    stream::{self, Stream, StreamExt},
+};
+use serde::Serialize;
+
+/// Response that serializes the items of a stream as they're produced.
+///
+/// By default the items are written as a JSON array, with the content type `application/json`.
+/// [`ndjson`](Self::ndjson) writes them as [newline delimited JSON] instead. Either way the
+/// response is sent in chunks, so large results don't have to be collected into memory first.
+///
+/// The status and headers are sent before the first item is serialized, so an item that fails to
+/// serialize can't change the status. Instead the response body is aborted, which clients see as
+/// a connection error rather than a truncated, but valid, array.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::JsonStream;
+/// use axum::{routing::get, Router};
+/// use futures_util::stream;
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct User {
+///     id: u32,
+/// }
+///
+/// async fn handler() -> JsonStream<impl futures_util::Stream<Item = User>> {
+///     JsonStream::new(stream::iter((0..1000).map(|id| User { id })))
+/// }
+///
+/// let app = Router::new().route("/users", get(handler));
+/// # let _: Router = app;
+/// ```
+///
+/// [newline delimited JSON]: https://github.com/ndjson/ndjson-spec
+#[derive(Debug, Clone)]
+pub struct JsonStream<S> {
+    stream: S,
+    format: JsonStreamFormat,
+}
+
+/// How [`JsonStream`] writes the items of the stream.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum JsonStreamFormat {
+    /// A JSON array, with the content type `application/json`.
+    #[default]
+    Array,
+    /// One JSON value per line, with the content type `application/x-ndjson`.
+    Ndjson,
+}
+
+impl<S> JsonStream<S> {
+    /// Create a new `JsonStream` that writes the items as a JSON array.
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            format: JsonStreamFormat::Array,
+        }
+    }
+
+    /// Set how the items are written.
+    ///
+    /// Defaults to [`JsonStreamFormat::Array`].
+    pub fn format(mut self, format: JsonStreamFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Write the items as newline delimited JSON.
+    ///
+    /// This is the same as `format(JsonStreamFormat::Ndjson)`.
+    pub fn ndjson(self) -> Self {
+        self.format(JsonStreamFormat::Ndjson)
+    }
+}
+
+impl<S, T> IntoResponse for JsonStream<S>
+where
+    S: Stream<Item = T> + Send + 'static,
+    T: Serialize,
+{
+    fn into_response(self) -> Response {
+        let format = self.format;
+        let items = self.stream.enumerate().map(move |(index, item)| {
+            let mut buf = match format {
+                JsonStreamFormat::Array if index > 0 => b",".to_vec(),
+                _ => Vec::new(),
+            };
+            serde_json::to_writer(&mut buf, &item)?;
+            if format == JsonStreamFormat::Ndjson {
+                buf.push(b'\n');
+            }
+            Ok::<_, serde_json::Error>(Bytes::from(buf))
+        });
+
+        let (content_type, body) = match format {
+            JsonStreamFormat::Array => (
+                "application/json",
+                StreamBody::new(
+                    stream::once(future::ready(Ok(Bytes::from_static(b"["))))
+                        .chain(items)
+                        .chain(stream::once(future::ready(Ok(Bytes::from_static(b"]")))))
+                        .boxed(),
+                ),
+            ),
+            JsonStreamFormat::Ndjson => ("application/x-ndjson", StreamBody::new(items.boxed())),
+        };
+
+        (
+            [(header::CONTENT_TYPE, HeaderValue::from_static(content_type))],
+            body,
+        )
+            .into_response()
+    }
+}
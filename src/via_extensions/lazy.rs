@@ -0,0 +1,101 @@
+use axum::{
+    async_trait,
+    body::Body,
+    extract::{rejection::BodyAlreadyExtracted, FromRequest, RequestParts},
+    http::Request,
+};
+use std::{fmt, marker::PhantomData};
+
+/// Extractor that defers another extractor until [`Lazy::get`] is called.
+///
+/// At extraction time this only captures the request, so handlers that sometimes return early,
+/// for example on authentication failures or cache hits, don't pay for reading and deserializing
+/// the body. When [`Lazy::get`] is called the wrapped extractor runs on the captured request and
+/// is rejected with its config as usual.
+///
+/// The body and the request extensions are moved into the `Lazy`, so it should be the last
+/// extractor of the handler. It's only rejected if the body has already been taken.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{Json, Lazy};
+/// use axum::{
+///     http::{HeaderMap, StatusCode},
+///     response::{IntoResponse, Response},
+///     routing::post,
+///     Router,
+/// };
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Payload {
+///     id: u32,
+/// }
+///
+/// async fn handler(headers: HeaderMap, payload: Lazy<Json<Payload>>) -> Response {
+///     if !headers.contains_key("authorization") {
+///         // the body is never deserialized
+///         return StatusCode::UNAUTHORIZED.into_response();
+///     }
+///
+///     let Json(payload) = match payload.get().await {
+///         Ok(payload) => payload,
+///         Err(rejection) => return rejection.into_response(),
+///     };
+///     // ...
+///     # StatusCode::OK.into_response()
+/// }
+///
+/// let app = Router::new().route("/", post(handler));
+/// # let _: Router = app;
+/// ```
+pub struct Lazy<E, B = Body> {
+    req: RequestParts<B>,
+    _marker: PhantomData<fn() -> E>,
+}
+
+impl<E, B> Lazy<E, B>
+where
+    E: FromRequest<B>,
+    B: Send,
+{
+    /// Run the wrapped extractor.
+    pub async fn get(mut self) -> Result<E, E::Rejection> {
+        E::from_request(&mut self.req).await
+    }
+}
+
+#[async_trait]
+impl<E, B> FromRequest<B> for Lazy<E, B>
+where
+    E: FromRequest<B>,
+    B: Send,
+{
+    type Rejection = BodyAlreadyExtracted;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let body = req.take_body().ok_or_else(BodyAlreadyExtracted::default)?;
+
+        let mut copy = Request::new(body);
+        *copy.method_mut() = req.method().clone();
+        *copy.uri_mut() = req.uri().clone();
+        *copy.version_mut() = req.version();
+        *copy.headers_mut() = req.headers().clone();
+        *copy.extensions_mut() = std::mem::take(req.extensions_mut());
+
+        Ok(Self {
+            req: RequestParts::new(copy),
+            _marker: PhantomData,
+        })
+    }
+}
+
+impl<E, B> fmt::Debug for Lazy<E, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Lazy")
+            .field("method", self.req.method())
+            .field("uri", self.req.uri())
+            .finish()
+    }
+}
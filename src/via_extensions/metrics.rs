@@ -0,0 +1,37 @@
+use super::rejection::RejectionKind;
+use axum::extract::{MatchedPath, RequestParts};
+
+const EXTRACTIONS: &str = "axum_extractor_config_extractions_total";
+const REJECTIONS: &str = "axum_extractor_config_rejections_total";
+
+pub(crate) fn record_extraction<B>(extractor: &'static str, req: &RequestParts<B>) {
+    metrics::counter!(
+        EXTRACTIONS,
+        "extractor" => extractor,
+        "route" => route(req),
+    )
+    .increment(1);
+}
+
+pub(crate) fn record_rejection<B>(
+    extractor: &'static str,
+    kind: RejectionKind,
+    req: &RequestParts<B>,
+) {
+    metrics::counter!(
+        REJECTIONS,
+        "extractor" => extractor,
+        "kind" => kind.as_str(),
+        "route" => route(req),
+    )
+    .increment(1);
+}
+
+// Requests without a matched path, such as ones handled by a fallback, get an empty route so
+// every series has the same labels.
+fn route<B>(req: &RequestParts<B>) -> String {
+    req.extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_owned())
+        .unwrap_or_default()
+}
@@ -4,33 +4,292 @@ use axum::{
     async_trait,
     body::{Bytes, HttpBody},
     extract::{FromRequest, RequestParts},
+    http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode},
     response::{IntoResponse, Response},
     BoxError,
 };
 use serde::{de::DeserializeOwned, Serialize};
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
 use std::fmt;
-use std::sync::Arc;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+use std::time::{Duration, Instant};
+
+pub mod presets;
+pub mod rejection;
+#[cfg(feature = "testing")]
+pub mod testing;
 
+mod audit;
+mod borrowed;
+mod buffer;
+mod cached;
+mod combined;
+#[cfg(feature = "axum-06")]
+mod compat_06;
+#[cfg(feature = "axum-07")]
+mod compat_07;
 mod config;
-pub use config::Config;
+mod context;
+pub(crate) mod custom;
+mod de;
+mod decompress;
+mod filters;
+mod first_of;
+mod form;
+mod global;
+mod i18n;
+mod json;
+mod json_stream;
+mod lazy;
+#[cfg(feature = "metrics")]
+mod metrics;
+#[cfg(feature = "multipart")]
+mod multipart;
+mod negotiate;
+mod optional;
+#[cfg(feature = "otel")]
+mod otel;
+mod policy;
+mod query;
+mod query_and_json;
+mod request_ext;
+mod router_ext;
+mod sort;
+#[cfg(feature = "tracing")]
+mod trace;
+mod utf8;
+mod valid;
+#[cfg(feature = "hmac")]
+mod verify;
+
+pub use audit::{Audit, AuditRecord};
+pub use borrowed::BorrowedJson;
+pub use buffer::{BufferBody, Peek};
+pub use cached::Cached;
+pub use combined::{ExtractorConfigBuilder, ExtractorConfigLayer};
+#[cfg(feature = "axum-07")]
+pub use compat_07::CompatBody;
+pub use config::{
+    configured, Config, ConfigError, ConfigErrorBody, ExtractorConfig, OnConfigError,
+};
+pub use context::{ExtractionStats, FieldError, RejectedBody, RejectionContext, RejectionInfo};
+pub use decompress::Decompression;
+pub use filters::{FilterConfig, FilterError, Filters};
+pub use first_of::{Either, Either3, Either4, FirstOf, FirstOfConfig, FirstOfRejection};
+pub use global::GlobalRejectionConfig;
+pub use i18n::{MessageCatalog, StaticMessageCatalog};
+pub use json_stream::{JsonStream, JsonStreamFormat};
+pub use lazy::Lazy;
+#[cfg(feature = "multipart")]
+pub use multipart::{
+    FieldConfig, Multipart, MultipartConfig, MultipartError, MultipartField, TempFile,
+    TypedMultipart, UploadedFile,
+};
+pub use negotiate::{Negotiate, NegotiateConfig, ResponseFormat};
+pub use optional::{JsonOrDefault, Optional};
+pub use policy::{Preset, RejectionPolicy};
+pub use query::QueryParser;
+pub use query_and_json::{QueryAndJson, QueryAndJsonRejection};
+pub use request_ext::{ConfigurableExtractor, RequestExt};
+pub use router_ext::RouterConfigExt;
+pub use sort::{Sort, SortBy, SortConfig, SortDirection, SortError};
+pub use utf8::Utf8Policy;
+#[cfg(feature = "garde")]
+pub use valid::Garde;
+pub use valid::{
+    Valid, ValidRejection, ValidWithContext, Validate, ValidateWithContext, ValidationConfig,
+    ValidationErrors,
+};
+#[cfg(feature = "hmac")]
+pub use verify::{HmacAlgorithm, SignatureError, Verified, VerifiedRejection, VerifyConfig};
 
 type RejectionToResponseFn<T, B> =
-    Option<Arc<dyn Fn(T, &axum::extract::RequestParts<B>) -> Response + Send + Sync>>;
+    Option<Arc<dyn Fn(T, RejectionContext<'_, B>) -> Response + Send + Sync>>;
+
+// handlers that only handle some rejections and give the others back
+type TryRejectionToResponseFn<T, B> =
+    Arc<dyn Fn(T, RejectionContext<'_, B>) -> Result<Response, T> + Send + Sync>;
+
+// documentation links added to rejections by the presets
+#[derive(Clone, Default)]
+struct DocsLinks {
+    base: Option<String>,
+    slugs: HashMap<rejection::RejectionKind, String>,
+}
+
+// observers that see every rejection without producing a response
+type RejectionObserverFn<T, B> = Arc<dyn Fn(&T, RejectionContext<'_, B>) + Send + Sync>;
+
+// which rejections the observers are called for
+enum ObserverSampling<B> {
+    Rate { rate: f64, count: Arc<AtomicU64> },
+    Predicate(Arc<dyn Fn(RejectionContext<'_, B>) -> bool + Send + Sync>),
+}
+
+impl<B> ObserverSampling<B> {
+    fn sample(&self, ctx: RejectionContext<'_, B>) -> bool {
+        match self {
+            // sample the rejections where the expected number of samples reaches the next whole
+            // number, which spreads them evenly without needing random numbers
+            Self::Rate { rate, count } => {
+                let count = count.fetch_add(1, Ordering::Relaxed) as f64;
+                ((count + 1.0) * rate).floor() > (count * rate).floor()
+            }
+            Self::Predicate(predicate) => predicate(ctx),
+        }
+    }
+}
+
+impl<B> Clone for ObserverSampling<B> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Rate { rate, count } => Self::Rate {
+                rate: *rate,
+                count: count.clone(),
+            },
+            Self::Predicate(predicate) => Self::Predicate(predicate.clone()),
+        }
+    }
+}
+
+// hooks that run after deserializing, keyed by the `TypeId` of the type they take. They return the
+// response to reject the request with, if any.
+type PostDeserializeFn<B> =
+    Arc<dyn Fn(&mut dyn Any, &RequestParts<B>) -> Option<Response> + Send + Sync>;
+
+// hooks that change the body before it's deserialized, or reject it with a response
+type TransformBodyFn = Arc<dyn Fn(Bytes) -> Result<Bytes, Box<Response>> + Send + Sync>;
+
+// why extracting failed: the rejection of the extractor, or a response from one of the hooks
+enum ExtractError<R> {
+    Rejection(R),
+    Hook(Box<Response>),
+}
+
+fn transform_body<R>(
+    transform: Option<&TransformBodyFn>,
+    bytes: Bytes,
+) -> Result<Bytes, ExtractError<R>> {
+    match transform {
+        Some(transform) => transform(bytes).map_err(ExtractError::Hook),
+        None => Ok(bytes),
+    }
+}
+
+type SuccessObserverFn<B> = Option<Arc<dyn Fn(&RequestParts<B>, ExtractionStats) + Send + Sync>>;
+
+// Warn, once per extractor, that it rejected a request without a config, which usually means the
+// layer was forgotten.
+#[cfg(feature = "tracing")]
+fn warn_missing_config(warned: &std::sync::atomic::AtomicBool, extractor: &str, config: &str) {
+    if !warned.swap(true, std::sync::atomic::Ordering::Relaxed) {
+        tracing::warn!(
+            "`{extractor}` rejected a request but neither `{config}` nor `GlobalRejectionConfig` \
+            was added, so the default rejection was used. Add one of them with `Router::layer` to \
+            configure the rejection",
+            extractor = extractor,
+            config = config,
+        );
+    }
+}
 
 macro_rules! make_deserialize_wrapper {
     (
         $(#[$m:meta])*
         $ident:ident,
-        $rejection:ident,
-        $config:ident $(,)?
+        $rejection:ty,
+        $config:ident {
+            $($(#[$field_m:meta])* $field:ident: $ty:ty = $default:expr),* $(,)?
+        },
+        $extract:path $(,)?
     ) => {
         $(#[$m])*
-        #[derive(Clone, Copy, Debug)]
+        #[derive(
+            Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, serde::Deserialize,
+        )]
+        #[serde(transparent)]
         pub struct $ident<T>(pub T);
 
+        impl<T> $ident<T> {
+            /// Consume the extractor and return the inner value.
+            pub fn into_inner(self) -> T {
+                self.0
+            }
+
+            /// Convert the inner value with `f`.
+            pub fn map<U, F>(self, f: F) -> $ident<U>
+            where
+                F: FnOnce(T) -> U,
+            {
+                $ident(f(self.0))
+            }
+
+            /// Convert the inner value with `f`, which can fail.
+            pub fn try_map<U, E, F>(self, f: F) -> Result<$ident<U>, E>
+            where
+                F: FnOnce(T) -> Result<U, E>,
+            {
+                f(self.0).map($ident)
+            }
+        }
+
+        impl<T> std::ops::Deref for $ident<T> {
+            type Target = T;
+
+            fn deref(&self) -> &T {
+                &self.0
+            }
+        }
+
+        impl<T> std::ops::DerefMut for $ident<T> {
+            fn deref_mut(&mut self) -> &mut T {
+                &mut self.0
+            }
+        }
+
+        impl<T> From<T> for $ident<T> {
+            fn from(value: T) -> Self {
+                Self(value)
+            }
+        }
+
+        impl<T> From<axum::extract::$ident<T>> for $ident<T> {
+            fn from(axum::extract::$ident(value): axum::extract::$ident<T>) -> Self {
+                Self(value)
+            }
+        }
+
+        impl<T> From<$ident<T>> for axum::extract::$ident<T> {
+            fn from($ident(value): $ident<T>) -> Self {
+                Self(value)
+            }
+        }
+
         #[doc = concat!("Config type for `", stringify!($ident), "`")]
         pub struct $config<B> {
-            rejection_handler: RejectionToResponseFn<axum::extract::rejection::$rejection, B>,
+            rejection_handler: RejectionToResponseFn<$rejection, B>,
+            rejection_handlers: Vec<TryRejectionToResponseFn<$rejection, B>>,
+            rejection_observers: Vec<RejectionObserverFn<$rejection, B>>,
+            observer_sampling: Option<ObserverSampling<B>>,
+            success_observer: SuccessObserverFn<B>,
+            post_deserialize: Arc<HashMap<TypeId, Vec<PostDeserializeFn<B>>>>,
+            override_status: Option<StatusCode>,
+            detail_level: rejection::DetailLevel,
+            rejection_headers: Arc<HeaderMap>,
+            error_codes: Arc<HashMap<rejection::RejectionKind, rejection::ErrorCode>>,
+            message_catalog: Option<i18n::SharedMessageCatalog>,
+            docs: Arc<DocsLinks>,
+            verbose_errors_header: Option<(HeaderName, HeaderValue)>,
+            request_id_header: Option<HeaderName>,
+            allow_override: bool,
+            method_overrides: Arc<HashMap<Method, Arc<Self>>>,
+            #[cfg(feature = "tracing")]
+            trace_rejections: Option<tracing::Level>,
+            $($(#[$field_m])* $field: $ty,)*
         }
 
         impl<B> $config<B> {
@@ -40,22 +299,410 @@ macro_rules! make_deserialize_wrapper {
             }
 
             /// Set the rejection handler function.
+            ///
+            /// The handler can return any type that implements `IntoResponse`. That includes
+            /// `Result<R, E>` where both `R` and `E` implement `IntoResponse`, so handlers that
+            /// can fail, for example while rendering a template, can use `?`.
             pub fn rejection_handler<F, R>(mut self, f: F) -> Self
             where
-                F: Fn(axum::extract::rejection::$rejection, &axum::extract::RequestParts<B>) -> R + Send + Sync + 'static,
+                F: Fn($rejection, RejectionContext<'_, B>) -> R + Send + Sync + 'static,
                 R: axum::response::IntoResponse,
             {
-                self.rejection_handler = Some(Arc::new(move |rejection, req| {
-                    f(rejection, req).into_response()
+                self.rejection_handler = Some(Arc::new(move |rejection, ctx| {
+                    f(rejection, ctx).into_response()
                 }));
                 self
             }
+
+            /// Add a rejection handler that can choose not to handle a rejection.
+            ///
+            /// Handlers added with this method are tried in the order they were added. The
+            /// first one to return `Some` produces the response, while `None` falls through to
+            /// the next handler. If no handler returns `Some` the rejection is handled by the
+            /// handler set with `rejection_handler`, or the default response if none is set.
+            ///
+            /// This allows a library to provide a base set of handlers that applications can
+            /// override specific cases of.
+            pub fn try_rejection_handler<F, R>(self, f: F) -> Self
+            where
+                F: Fn(&$rejection, RejectionContext<'_, B>) -> Option<R> + Send + Sync + 'static,
+                R: axum::response::IntoResponse,
+            {
+                self.push_rejection_handler(move |rejection, ctx| {
+                    match f(&rejection, ctx) {
+                        Some(response) => Ok(response.into_response()),
+                        None => Err(rejection),
+                    }
+                })
+            }
+
+            /// Add a function that is called for every rejection.
+            ///
+            /// Unlike rejection handlers, observers don't produce a response, so they can be
+            /// used for metrics, logging, or alerting without changing what clients see.
+            /// Several observers can be added and they're called in the order they were added,
+            /// before the rejection handlers run.
+            pub fn on_rejection<F>(mut self, f: F) -> Self
+            where
+                F: Fn(&$rejection, RejectionContext<'_, B>) + Send + Sync + 'static,
+            {
+                self.rejection_observers.push(Arc::new(f));
+                self
+            }
+
+            /// Only call the observers added with [`on_rejection`](Self::on_rejection) for a
+            /// fraction of the rejections.
+            ///
+            /// `rate` is between `0.0` and `1.0`, for example `0.01` calls the observers for one
+            /// in every hundred rejections. This allows high-traffic services to record only some
+            /// rejections with expensive observers, while the rejection handlers still run for
+            /// every rejection. The rejections are sampled evenly, rather than at random.
+            ///
+            /// Defaults to calling the observers for every rejection.
+            ///
+            /// # Example
+            ///
+            /// ```
+            #[doc = concat!("use axum_extractor_config::via_extensions::", stringify!($config), ";")]
+            ///
+            #[doc = concat!("let config = ", stringify!($config), "::new()")]
+            ///     .on_rejection(|rejection, _ctx| eprintln!("{}", rejection))
+            ///     .sample_observers(0.1);
+            #[doc = concat!("# let _: ", stringify!($config), "<axum::body::Body> = config;")]
+            /// ```
+            pub fn sample_observers(mut self, rate: f64) -> Self {
+                self.observer_sampling = Some(ObserverSampling::Rate {
+                    rate: rate.clamp(0.0, 1.0),
+                    count: Arc::default(),
+                });
+                self
+            }
+
+            /// Only call the observers added with [`on_rejection`](Self::on_rejection) for the
+            /// rejections `f` returns `true` for.
+            ///
+            /// This allows sampling by other means than a rate, such as always observing some
+            /// routes. See [`sample_observers`](Self::sample_observers).
+            pub fn sample_observers_with<F>(mut self, f: F) -> Self
+            where
+                F: Fn(RejectionContext<'_, B>) -> bool + Send + Sync + 'static,
+            {
+                self.observer_sampling = Some(ObserverSampling::Predicate(Arc::new(f)));
+                self
+            }
+
+            /// Set a function that is called when extraction succeeds.
+            ///
+            /// It receives how long extraction took, including buffering the body, and the size
+            /// of the body. This allows monitoring the cost of parsing payloads per route.
+            pub fn on_success<F>(mut self, f: F) -> Self
+            where
+                F: Fn(&RequestParts<B>, ExtractionStats) + Send + Sync + 'static,
+            {
+                self.success_observer = Some(Arc::new(f));
+                self
+            }
+
+            /// Add a function that runs after a value of type `T` has been deserialized.
+            ///
+            /// This allows normalizing values, such as trimming strings or lowercasing emails,
+            /// for every extraction of `T` without wrapping it in another type. Returning `Err`
+            /// rejects the request with that response, without running the rejection handlers.
+            /// Several functions can be added for the same type and they run in the order they
+            /// were added.
+            ///
+            /// # Example
+            ///
+            /// ```
+            #[doc = concat!("use axum_extractor_config::via_extensions::", stringify!($config), ";")]
+            /// use axum::{body::Body, http::StatusCode};
+            ///
+            /// struct Signup {
+            ///     email: String,
+            /// }
+            ///
+            #[doc = concat!("let config = ", stringify!($config), "::<Body>::new().post_deserialize(")]
+            ///     |signup: &mut Signup, _req| {
+            ///         signup.email = signup.email.trim().to_lowercase();
+            ///         if signup.email.is_empty() {
+            ///             return Err((StatusCode::UNPROCESSABLE_ENTITY, "Missing email"));
+            ///         }
+            ///         Ok(())
+            ///     },
+            /// );
+            /// ```
+            pub fn post_deserialize<T, F, R>(mut self, f: F) -> Self
+            where
+                T: 'static,
+                F: Fn(&mut T, &RequestParts<B>) -> Result<(), R> + Send + Sync + 'static,
+                R: IntoResponse,
+            {
+                let hook: PostDeserializeFn<B> = Arc::new(move |value, req| {
+                    let value = value
+                        .downcast_mut::<T>()
+                        .expect("hooks are only called with the type they're keyed by");
+                    f(value, req).err().map(IntoResponse::into_response)
+                });
+                Arc::make_mut(&mut self.post_deserialize)
+                    .entry(TypeId::of::<T>())
+                    .or_default()
+                    .push(hook);
+                self
+            }
+
+            /// Set the status code of rejection responses.
+            ///
+            /// This applies to the default rejection responses, for example to use
+            /// `400 Bad Request` rather than `422 Unprocessable Entity` without writing a
+            /// rejection handler. Responses from rejection handlers are not changed.
+            pub fn override_status(mut self, status: StatusCode) -> Self {
+                self.override_status = Some(status);
+                self
+            }
+
+            /// Set how much detail the default rejection responses include.
+            ///
+            /// For example production deployments can hide the underlying deserialization
+            /// errors while development keeps them, without writing two rejection handlers.
+            /// Responses from rejection handlers are not changed.
+            ///
+            /// Defaults to [`DetailLevel::Full`](rejection::DetailLevel::Full).
+            pub fn detail_level(mut self, detail_level: rejection::DetailLevel) -> Self {
+                self.detail_level = detail_level;
+                self
+            }
+
+            /// Set headers that are added to rejection responses.
+            ///
+            /// This is useful for headers every rejection should have, such as
+            /// `Cache-Control: no-store` or CORS headers. It applies to both the default
+            /// responses and responses from rejection handlers, though headers a response
+            /// already has are not overridden.
+            pub fn rejection_headers(mut self, headers: HeaderMap) -> Self {
+                self.rejection_headers = Arc::new(headers);
+                self
+            }
+
+            /// Set the application error code for a kind of rejection.
+            ///
+            /// Rejection handlers can get it from
+            /// [`RejectionContext::error_code`], so error codes can be defined in one place
+            /// rather than in each handler. Codes set here take precedence over ones set with
+            /// [`GlobalRejectionConfig::error_code`].
+            pub fn error_code(
+                mut self,
+                kind: rejection::RejectionKind,
+                code: impl Into<rejection::ErrorCode>,
+            ) -> Self {
+                Arc::make_mut(&mut self.error_codes).insert(kind, code.into());
+                self
+            }
+
+            /// Set the catalog of localized rejection messages.
+            ///
+            /// Rejection handlers can get the message for the rejection, in the language the
+            /// client prefers according to the `Accept-Language` header, from
+            /// [`RejectionContext::localized_message`]. The catalog set here takes precedence
+            /// over one set with [`GlobalRejectionConfig::message_catalog`].
+            pub fn message_catalog<C>(mut self, catalog: C) -> Self
+            where
+                C: MessageCatalog,
+            {
+                self.message_catalog = Some(Arc::new(catalog));
+                self
+            }
+
+            /// Set the base URL of the documentation for rejections.
+            ///
+            /// The [presets](presets) include a link to the documentation of the kind of the
+            /// rejection, which is the base followed by
+            /// [`RejectionKind::as_str`](rejection::RejectionKind::as_str), or the slug set with
+            /// [`docs_slug`](Self::docs_slug). For example with the base
+            /// `https://example.com/errors/` the link for syntax errors is
+            /// `https://example.com/errors/syntax-error`. Rejection handlers can get the link from
+            /// [`RejectionContext::docs_url`].
+            ///
+            /// The base set here takes precedence over one set with
+            /// [`GlobalRejectionConfig::docs_url`].
+            pub fn docs_url(mut self, base: impl Into<String>) -> Self {
+                Arc::make_mut(&mut self.docs).base = Some(base.into());
+                self
+            }
+
+            /// Set the slug of the documentation link for a kind of rejection.
+            ///
+            /// See [`docs_url`](Self::docs_url).
+            pub fn docs_slug(
+                mut self,
+                kind: rejection::RejectionKind,
+                slug: impl Into<String>,
+            ) -> Self {
+                Arc::make_mut(&mut self.docs).slugs.insert(kind, slug.into());
+                self
+            }
+
+            /// Only include the details of rejections in responses when the request has a header
+            /// with the given value, such as `X-Debug-Errors: <secret>`.
+            ///
+            /// Requests without the header get default responses with
+            /// [`DetailLevel::Message`](rejection::DetailLevel::Message), the
+            /// [presets](presets) leave out the underlying error, and
+            /// [`RejectionContext::body`] returns `None`. This allows support staff to debug
+            /// issues in production without showing internals to everyone else.
+            ///
+            /// The header set here takes precedence over one set with
+            /// [`GlobalRejectionConfig::verbose_errors_header`].
+            pub fn verbose_errors_header(mut self, name: HeaderName, token: HeaderValue) -> Self {
+                self.verbose_errors_header = Some((name, token));
+                self
+            }
+
+            /// Emit a `tracing` event for every rejection.
+            ///
+            /// The events have the given level and the target
+            /// `axum_extractor_config::rejection`, so they can be filtered separately from other
+            /// events. Their message is the rejection's message and they have the fields
+            /// `extractor`, `kind` (the [`RejectionKind`](rejection::RejectionKind) in kebab
+            /// case), `route`, `status`, and `target_type`. The event is emitted when the
+            /// rejection is turned into a response, so `status` is the status clients see.
+            ///
+            /// Requires the `tracing` feature.
+            ///
+            /// # Example
+            ///
+            /// ```
+            #[doc = concat!("use axum_extractor_config::via_extensions::", stringify!($config), ";")]
+            /// use tracing::Level;
+            ///
+            #[doc = concat!("let config = ", stringify!($config), "::new().trace_rejections(Level::WARN);")]
+            #[doc = concat!("# let _: ", stringify!($config), "<axum::body::Body> = config;")]
+            /// ```
+            #[cfg(feature = "tracing")]
+            pub fn trace_rejections(mut self, level: tracing::Level) -> Self {
+                self.trace_rejections = Some(level);
+                self
+            }
+
+            /// Set the header with the ID of the request, such as `X-Request-Id`.
+            ///
+            /// Rejection handlers can get the ID from [`RejectionContext::request_id`], the
+            /// [presets](presets) include it as `request_id`, and rejection responses get the
+            /// header with the same value, so clients can quote it when reporting errors. This
+            /// works with tower-http's `SetRequestIdLayer`, which adds the header to requests.
+            ///
+            /// The header set here takes precedence over one set with
+            /// [`GlobalRejectionConfig::request_id_header`].
+            ///
+            /// # Example
+            ///
+            /// ```
+            #[doc = concat!("use axum_extractor_config::via_extensions::{presets, ", stringify!($config), "};")]
+            /// use axum::http::HeaderName;
+            ///
+            #[doc = concat!("let config = ", stringify!($config), "::new()")]
+            ///     .request_id_header(HeaderName::from_static("x-request-id"))
+            ///     .rejection_handler(presets::simple_json());
+            #[doc = concat!("# let _: ", stringify!($config), "<axum::body::Body> = config;")]
+            /// ```
+            pub fn request_id_header(mut self, name: HeaderName) -> Self {
+                self.request_id_header = Some(name);
+                self
+            }
+
+            /// Use a different config for requests with the given method.
+            ///
+            /// The function receives this config, as it is when `for_method` is called, and
+            /// returns the config used for the method. This allows, for example, stricter body
+            /// limits on `PUT` than on `PATCH`.
+            ///
+            /// # Example
+            ///
+            /// ```
+            /// use axum_extractor_config::via_extensions::JsonConfig;
+            /// use axum::http::Method;
+            ///
+            /// let config = JsonConfig::new()
+            ///     .max_body_size(1024 * 1024)
+            ///     .for_method(Method::PUT, |config| config.max_body_size(64 * 1024));
+            /// # let _: JsonConfig<axum::body::Body> = config;
+            /// ```
+            pub fn for_method<F>(mut self, method: Method, f: F) -> Self
+            where
+                F: FnOnce(Self) -> Self,
+            {
+                let mut config = f(self.clone());
+                config.method_overrides = Arc::default();
+                Arc::make_mut(&mut self.method_overrides).insert(method, Arc::new(config));
+                self
+            }
+
+            // The config for a request with the given method.
+            fn for_request_method(self: Arc<Self>, method: &Method) -> Arc<Self> {
+                match self.method_overrides.get(method) {
+                    Some(config) => config.clone(),
+                    None => self,
+                }
+            }
+
+            #[doc = concat!("Create a `", stringify!($config), "` from a [`RejectionPolicy`].")]
+            ///
+            /// The policy sets the status override, detail level, rejection handler, and size
+            /// limits. Other settings can be changed afterwards.
+            pub fn from_policy(policy: &RejectionPolicy) -> Self
+            where
+                B: 'static,
+            {
+                let mut config = Self::new().apply_policy_limits(policy);
+                config.override_status = policy.override_status;
+                config.detail_level = policy.detail_level;
+                config.rejection_handler = policy.preset.map(Preset::into_handler);
+                config
+            }
+
+            /// Allow layers further in to override this config.
+            ///
+            /// See [`Config::allow_override`].
+            pub fn allow_override(mut self) -> Self {
+                self.allow_override = true;
+                self
+            }
+
+            // Add a handler that's tried before the one set with `rejection_handler`. It returns
+            // `Err` with the rejection for rejections it doesn't handle.
+            fn push_rejection_handler<F>(mut self, f: F) -> Self
+            where
+                F: Fn($rejection, RejectionContext<'_, B>) -> Result<Response, $rejection>
+                    + Send
+                    + Sync
+                    + 'static,
+            {
+                self.rejection_handlers.push(Arc::new(f));
+                self
+            }
         }
 
         impl<B> Clone for $config<B> {
             fn clone(&self) -> Self {
                 Self {
                     rejection_handler: self.rejection_handler.clone(),
+                    rejection_handlers: self.rejection_handlers.clone(),
+                    rejection_observers: self.rejection_observers.clone(),
+                    observer_sampling: self.observer_sampling.clone(),
+                    success_observer: self.success_observer.clone(),
+                    post_deserialize: self.post_deserialize.clone(),
+                    override_status: self.override_status,
+                    detail_level: self.detail_level,
+                    error_codes: self.error_codes.clone(),
+                    message_catalog: self.message_catalog.clone(),
+                    docs: self.docs.clone(),
+                    verbose_errors_header: self.verbose_errors_header.clone(),
+                    request_id_header: self.request_id_header.clone(),
+                    allow_override: self.allow_override,
+                    method_overrides: self.method_overrides.clone(),
+                    rejection_headers: self.rejection_headers.clone(),
+                    #[cfg(feature = "tracing")]
+                    trace_rejections: self.trace_rejections,
+                    $($(#[$field_m])* $field: self.$field.clone(),)*
                 }
             }
         }
@@ -64,6 +711,24 @@ macro_rules! make_deserialize_wrapper {
             fn default() -> Self {
                 Self {
                     rejection_handler: None,
+                    rejection_handlers: Vec::new(),
+                    rejection_observers: Vec::new(),
+                    observer_sampling: None,
+                    success_observer: None,
+                    post_deserialize: Arc::default(),
+                    override_status: None,
+                    detail_level: rejection::DetailLevel::Full,
+                    error_codes: Arc::default(),
+                    message_catalog: None,
+                    docs: Arc::default(),
+                    verbose_errors_header: None,
+                    request_id_header: None,
+                    allow_override: false,
+                    method_overrides: Arc::default(),
+                    rejection_headers: Arc::default(),
+                    #[cfg(feature = "tracing")]
+                    trace_rejections: None,
+                    $($(#[$field_m])* $field: $default,)*
                 }
             }
         }
@@ -73,26 +738,280 @@ macro_rules! make_deserialize_wrapper {
         where
             B: HttpBody<Data = Bytes> + Send + 'static,
             B::Error: Into<BoxError>,
-            T: DeserializeOwned + Send,
+            T: DeserializeOwned + Send + 'static,
         {
-            type Rejection = Response;
+            type Rejection = rejection::ConfiguredRejection<$rejection>;
 
             async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
-                match req.extract::<axum::extract::$ident<T>>().await {
-                    Ok(axum::extract::$ident(value)) => Ok(Self(value)),
-                    Err(rejection) => {
-                        let config =
-                            req.extract::<Config<$config<B>, B>>()
-                                .await
-                                .unwrap_or_default()
-                                .into_inner();
-
-                        if let Some(rejection_handler) = &config.rejection_handler {
-                            Err(rejection_handler(rejection, req))
-                        } else {
-                            Err(rejection.into_response())
+                let config = combined::get::<$config<B>, B>(req);
+                #[cfg(feature = "tracing")]
+                let config_missing = config.is_none();
+                let config = config.unwrap_or_default().for_request_method(req.method());
+
+                let start = Instant::now();
+                match $extract(&config, req).await.map_err(ExtractError::<$rejection>::from) {
+                    Ok(mut value) => {
+                        if let Some(rejection) = config.run_post_deserialize(&mut value, req) {
+                            return Err(rejection);
+                        }
+                        #[cfg(feature = "metrics")]
+                        metrics::record_extraction(stringify!($ident), req);
+                        if let Some(success_observer) = &config.success_observer {
+                            let body_size =
+                                req.extensions().get::<context::BodySize>().map(|size| size.0);
+                            success_observer(req, ExtractionStats::new(start.elapsed(), body_size));
                         }
+                        Ok(Self(value))
+                    }
+                    Err(ExtractError::Hook(response)) => {
+                        Err(config.hook_rejection::<T>(*response, "transform_body", req))
+                    }
+                    Err(ExtractError::Rejection(rejection)) => {
+                        let global = combined::get::<GlobalRejectionConfig<B>, B>(req);
+                        #[cfg(feature = "tracing")]
+                        if config_missing && global.is_none() {
+                            static WARNED: std::sync::atomic::AtomicBool =
+                                std::sync::atomic::AtomicBool::new(false);
+                            warn_missing_config(
+                                &WARNED,
+                                stringify!($ident),
+                                stringify!($config),
+                            );
+                        }
+                        Err(config.reject::<T>(rejection, req, global.as_deref()))
+                    }
+                }
+            }
+        }
+
+        impl From<$rejection> for ExtractError<$rejection> {
+            fn from(rejection: $rejection) -> Self {
+                Self::Rejection(rejection)
+            }
+        }
+
+        impl<T, B> ConfigurableExtractor<B> for $ident<T>
+        where
+            B: HttpBody<Data = Bytes> + Send + 'static,
+            B::Error: Into<BoxError>,
+            T: DeserializeOwned + Send + 'static,
+        {
+            type Config = $config<B>;
+        }
+
+        impl<B> $config<B> {
+            // Reject with the config added to the request, like the extractor does. Used by
+            // `via_types::FromConfig`.
+            #[allow(dead_code)]
+            pub(crate) fn reject_with_extensions<T>(
+                rejection: $rejection,
+                req: &RequestParts<B>,
+            ) -> rejection::ConfiguredRejection<$rejection>
+            where
+                B: 'static,
+            {
+                let config = combined::get::<$config<B>, B>(req)
+                    .unwrap_or_default()
+                    .for_request_method(req.method());
+                let global = combined::get::<GlobalRejectionConfig<B>, B>(req);
+                config.reject::<T>(rejection, req, global.as_deref())
+            }
+
+            // Run the hooks added with `post_deserialize` for `T`, returning the rejection of the
+            // first one that fails.
+            fn run_post_deserialize<T>(
+                &self,
+                value: &mut T,
+                req: &RequestParts<B>,
+            ) -> Option<rejection::ConfiguredRejection<$rejection>>
+            where
+                T: 'static,
+            {
+                let hooks = self.post_deserialize.get(&TypeId::of::<T>())?;
+                hooks
+                    .iter()
+                    .find_map(|hook| hook(value, req))
+                    .map(|response| self.hook_rejection::<T>(response, "post_deserialize", req))
+            }
+
+            // The rejection of requests a hook rejected, which is the hook's response as is.
+            fn hook_rejection<T>(
+                &self,
+                response: Response,
+                hook: &'static str,
+                #[cfg_attr(not(feature = "metrics"), allow(unused_variables))] req: &RequestParts<B>,
+            ) -> rejection::ConfiguredRejection<$rejection> {
+                #[cfg(feature = "metrics")]
+                metrics::record_rejection(stringify!($ident), rejection::RejectionKind::Other, req);
+                let mut info =
+                    RejectionInfo::new::<T>(stringify!($ident), rejection::RejectionKind::Other);
+                info.set_source(context::HookRejection(hook));
+                rejection::ConfiguredRejection::new(
+                    Ok(response),
+                    None,
+                    self.rejection_headers.clone(),
+                    info,
+                    rejection::DetailLevel::Full,
+                )
+            }
+
+            fn reject<T>(
+                &self,
+                rejection: $rejection,
+                req: &RequestParts<B>,
+                global: Option<&GlobalRejectionConfig<B>>,
+            ) -> rejection::ConfiguredRejection<$rejection> {
+                let kind = rejection::RejectionKind::from(&rejection);
+                let mut info = RejectionInfo::new::<T>(stringify!($ident), kind);
+                let ctx = self.rejection_context::<T>(req, kind, global);
+                let detail_level = if ctx.details_redacted() {
+                    // the details might contain redacted values or the request isn't allowed to
+                    // see them
+                    self.detail_level.min(rejection::DetailLevel::Message)
+                } else {
+                    self.detail_level
+                };
+                let sampled = match &self.observer_sampling {
+                    Some(sampling) => sampling.sample(ctx),
+                    None => true,
+                };
+                if sampled {
+                    for observer in &self.rejection_observers {
+                        observer(&rejection, ctx);
+                    }
+                }
+                #[cfg(feature = "metrics")]
+                metrics::record_rejection(stringify!($ident), kind, req);
+                #[cfg(feature = "tracing")]
+                let trace = self
+                    .trace_rejections
+                    .map(|level| trace::RejectionTrace::new(level, ctx.matched_path()));
+                if self.has_rejection_handler(global) {
+                    // the handlers take the rejection, so keep what its message is formatted from
+                    rejection::keep_message(&rejection, &mut info);
+                }
+                let handled = self.handle_rejection(rejection, ctx, global);
+
+                let headers = match self.request_id(req, global) {
+                    Some((name, value)) => {
+                        let mut headers = (*self.rejection_headers).clone();
+                        headers.insert(name, value.clone());
+                        Arc::new(headers)
                     }
+                    None => self.rejection_headers.clone(),
+                };
+
+                let rejection = rejection::ConfiguredRejection::new(
+                    handled,
+                    self.override_status,
+                    headers,
+                    info,
+                    detail_level,
+                )
+                .render(rejection::render_into_info);
+                #[cfg(feature = "tracing")]
+                let rejection = rejection.trace(trace);
+                rejection
+            }
+
+            // Resolve the settings given to rejection handlers, falling back to the global config.
+            fn rejection_context<'a, T>(
+                &'a self,
+                req: &'a RequestParts<B>,
+                kind: rejection::RejectionKind,
+                global: Option<&'a GlobalRejectionConfig<B>>,
+            ) -> RejectionContext<'a, B> {
+                let error_code = self
+                    .error_codes
+                    .get(&kind)
+                    .or_else(|| global.and_then(|global| global.error_codes.get(&kind)));
+
+                let message_catalog = self
+                    .message_catalog
+                    .as_ref()
+                    .or_else(|| global.and_then(|global| global.message_catalog.as_ref()))
+                    .map(|catalog| &**catalog);
+
+                let global_docs = global.map(|global| &*global.docs);
+                let docs = self
+                    .docs
+                    .base
+                    .as_deref()
+                    .or_else(|| global_docs.and_then(|docs| docs.base.as_deref()))
+                    .map(|base| {
+                        let slug = self
+                            .docs
+                            .slugs
+                            .get(&kind)
+                            .or_else(|| global_docs.and_then(|docs| docs.slugs.get(&kind)));
+                        (base, slug.map(String::as_str))
+                    });
+
+                let details_hidden = match self
+                    .verbose_errors_header
+                    .as_ref()
+                    .or_else(|| global.and_then(|global| global.verbose_errors_header.as_ref()))
+                {
+                    Some((name, token)) => !req
+                        .headers()
+                        .get_all(name)
+                        .iter()
+                        .any(|value| context::constant_time_eq(value.as_bytes(), token.as_bytes())),
+                    None => false,
+                };
+
+                let request_id = self
+                    .request_id(req, global)
+                    .and_then(|(_, value)| value.to_str().ok());
+
+                RejectionContext::new::<T>(req, kind, error_code, message_catalog, docs)
+                    .details_hidden(details_hidden)
+                    .with_request_id(request_id)
+            }
+
+            // The request ID header and its value, falling back to the header of the global
+            // config.
+            fn request_id<'a>(
+                &'a self,
+                req: &'a RequestParts<B>,
+                global: Option<&'a GlobalRejectionConfig<B>>,
+            ) -> Option<(&'a HeaderName, &'a HeaderValue)> {
+                let name = self
+                    .request_id_header
+                    .as_ref()
+                    .or_else(|| global.and_then(|global| global.request_id_header.as_ref()))?;
+                Some((name, req.headers().get(name)?))
+            }
+
+            fn has_rejection_handler(&self, global: Option<&GlobalRejectionConfig<B>>) -> bool {
+                !self.rejection_handlers.is_empty()
+                    || self.rejection_handler.is_some()
+                    || global.is_some_and(|global| global.rejection_handler.is_some())
+            }
+
+            // Run the rejection handlers. Returns the rejection if none of them handled it.
+            fn handle_rejection(
+                &self,
+                mut rejection: $rejection,
+                ctx: RejectionContext<'_, B>,
+                global: Option<&GlobalRejectionConfig<B>>,
+            ) -> Result<Response, $rejection> {
+                for rejection_handler in &self.rejection_handlers {
+                    match rejection_handler(rejection, ctx) {
+                        Ok(response) => return Ok(response),
+                        Err(unhandled) => rejection = unhandled,
+                    }
+                }
+
+                let global_rejection_handler =
+                    global.and_then(|global| global.rejection_handler.as_ref());
+
+                if let Some(rejection_handler) = &self.rejection_handler {
+                    Ok(rejection_handler(rejection, ctx))
+                } else if let Some(rejection_handler) = global_rejection_handler {
+                    Ok(rejection_handler(rejection.into(), ctx))
+                } else {
+                    Err(rejection)
                 }
             }
         }
@@ -103,11 +1022,43 @@ macro_rules! make_deserialize_wrapper {
             }
         }
 
+        impl<B: 'static> ExtractorConfig<B> for $config<B> {
+            type Rejection = $rejection;
+
+            fn rejection_handler<F, R>(self, f: F) -> Self
+            where
+                F: Fn($rejection, RejectionContext<'_, B>) -> R + Send + Sync + 'static,
+                R: IntoResponse,
+            {
+                Self::rejection_handler(self, f)
+            }
+
+            fn override_status(self, status: StatusCode) -> Self {
+                Self::override_status(self, status)
+            }
+
+            fn detail_level(self, detail_level: rejection::DetailLevel) -> Self {
+                Self::detail_level(self, detail_level)
+            }
+
+            fn into_config(self) -> Config<Self, B> {
+                let allow_override = self.allow_override;
+                let mut config = Config::new(self);
+                if allow_override {
+                    config = config.allow_override();
+                }
+                config
+            }
+        }
+
         impl<S, B> tower_layer::Layer<S> for $config<B> {
             type Service = <Config<Self, B> as tower_layer::Layer<S>>::Service;
 
             fn layer(&self, inner: S) -> Self::Service {
-                let config: Config::<_, B> = Config::new(self.clone());
+                let mut config: Config::<_, B> = Config::new(self.clone());
+                if self.allow_override {
+                    config = config.allow_override();
+                }
                 config.layer(inner)
             }
         }
@@ -126,11 +1077,12 @@ make_deserialize_wrapper! {
     ///     // make sure to use this `Json`, and not the one in axum
     ///     Json,
     ///     JsonConfig,
+    ///     RejectionContext,
     /// };
     /// use axum::{
     ///     Router,
     ///     routing::post,
-    ///     extract::{RequestParts, rejection::JsonRejection},
+    ///     extract::rejection::JsonRejection,
     ///     response::{IntoResponse, Response},
     ///     http::StatusCode,
     /// };
@@ -143,7 +1095,7 @@ make_deserialize_wrapper! {
     /// #[axum::debug_handler]
     /// async fn handler(Json(payload): Json<Payload>) {}
     ///
-    /// fn rejection_handler<B>(rejection: JsonRejection, req: &RequestParts<B>) -> (StatusCode, Json<Value>) {
+    /// fn rejection_handler<B>(rejection: JsonRejection, ctx: RejectionContext<'_, B>) -> (StatusCode, Json<Value>) {
     ///     (
     ///         StatusCode::BAD_REQUEST,
     ///         Json(json!({ "error": rejection.to_string() })),
@@ -156,8 +1108,21 @@ make_deserialize_wrapper! {
     /// # let _: Router = app;
     /// ```
     Json,
-    JsonRejection,
-    JsonConfig,
+    axum::extract::rejection::JsonRejection,
+    JsonConfig {
+        capture_body: Option<usize> = None,
+        max_body_size: Option<usize> = None,
+        decompression: Option<Decompression> = None,
+        body_timeout: Option<Duration> = None,
+        utf8_policy: Option<Utf8Policy> = None,
+        redact: Arc<Vec<String>> = Arc::default(),
+        transform_body: Option<TransformBodyFn> = None,
+        all_errors: bool = false,
+        audit: Option<Audit> = None,
+        #[cfg(feature = "schemars")]
+        schemas: Arc<HashMap<TypeId, Arc<serde_json::Value>>> = Arc::default(),
+    },
+    json::extract,
 }
 
 impl<T> IntoResponse for Json<T>
@@ -181,12 +1146,13 @@ make_deserialize_wrapper! {
     ///     // make sure to use this `Query`, and not the one in axum
     ///     Query,
     ///     QueryConfig,
+    ///     RejectionContext,
+    ///     rejection::QueryRejection,
     /// };
     /// use axum::{
     ///     Router,
     ///     Json,
     ///     routing::get,
-    ///     extract::{RequestParts, rejection::QueryRejection},
     ///     response::{IntoResponse, Response},
     ///     http::StatusCode,
     /// };
@@ -202,7 +1168,7 @@ make_deserialize_wrapper! {
     /// #[axum::debug_handler]
     /// async fn handler(Query(pagination): Query<Pagination>) {}
     ///
-    /// fn rejection_handler<B>(rejection: QueryRejection, req: &RequestParts<B>) -> (StatusCode, Json<Value>) {
+    /// fn rejection_handler<B>(rejection: QueryRejection, ctx: RejectionContext<'_, B>) -> (StatusCode, Json<Value>) {
     ///     (
     ///         StatusCode::BAD_REQUEST,
     ///         Json(json!({ "error": rejection.to_string() })),
@@ -215,8 +1181,15 @@ make_deserialize_wrapper! {
     /// # let _: Router = app;
     /// ```
     Query,
-    QueryRejection,
-    QueryConfig,
+    rejection::QueryRejection,
+    QueryConfig {
+        parser: QueryParser = QueryParser::default(),
+        plus_as_space: bool = true,
+        max_length: Option<usize> = None,
+        max_params: Option<usize> = None,
+        options: de::Options = de::Options::default(),
+    },
+    query::extract,
 }
 
 make_deserialize_wrapper! {
@@ -231,12 +1204,13 @@ make_deserialize_wrapper! {
     ///     // make sure to use this `Form`, and not the one in axum
     ///     Form,
     ///     FormConfig,
+    ///     RejectionContext,
+    ///     rejection::FormRejection,
     /// };
     /// use axum::{
     ///     Router,
     ///     Json,
     ///     routing::post,
-    ///     extract::{RequestParts, rejection::FormRejection},
     ///     response::{IntoResponse, Response},
     ///     http::StatusCode,
     /// };
@@ -249,7 +1223,7 @@ make_deserialize_wrapper! {
     /// #[axum::debug_handler]
     /// async fn handler(Form(payload): Form<Payload>) {}
     ///
-    /// fn rejection_handler<B>(rejection: FormRejection, req: &RequestParts<B>) -> (StatusCode, Json<Value>) {
+    /// fn rejection_handler<B>(rejection: FormRejection, ctx: RejectionContext<'_, B>) -> (StatusCode, Json<Value>) {
     ///     (
     ///         StatusCode::BAD_REQUEST,
     ///         Json(json!({ "error": rejection.to_string() })),
@@ -262,8 +1236,17 @@ make_deserialize_wrapper! {
     /// # let _: Router = app;
     /// ```
     Form,
-    FormRejection,
-    FormConfig,
+    rejection::FormRejection,
+    FormConfig {
+        parser: QueryParser = QueryParser::default(),
+        max_body_size: Option<usize> = None,
+        allow_query_on_get: bool = true,
+        capture_body: Option<usize> = None,
+        transform_body: Option<TransformBodyFn> = None,
+        utf8_policy: Option<Utf8Policy> = None,
+        options: de::Options = de::Options::default(),
+    },
+    form::extract,
 }
 
 impl<T> IntoResponse for Form<T>
@@ -280,7 +1263,7 @@ mod tests {
     use super::*;
     use axum::{
         body::Body,
-        extract::{rejection::JsonRejection, RequestParts},
+        extract::rejection::JsonRejection,
         http::{Method, Request, StatusCode},
         response::IntoResponse,
         routing::post,
@@ -288,8 +1271,9 @@ mod tests {
     };
     use serde::{Deserialize, Serialize};
     use serde_json::{json, Value};
+    use std::collections::HashMap;
     use std::error::Error;
-    use tower::Service;
+    use tower::{Service, ServiceExt};
 
     #[derive(Deserialize)]
     struct Payload {
@@ -356,7 +1340,10 @@ mod tests {
 
     #[tokio::test]
     async fn json_custom_rejection() {
-        fn rejection_handler<B>(rejection: JsonRejection, _req: &RequestParts<B>) -> Response {
+        fn rejection_handler<B>(
+            rejection: JsonRejection,
+            _ctx: RejectionContext<'_, B>,
+        ) -> Response {
             #[derive(Serialize)]
             struct Error {
                 message: String,
@@ -402,29 +1389,3668 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn error_on_duplicate_config() {
-        let mut app = app().layer(JsonConfig::new()).layer(JsonConfig::new());
+    async fn rejection_parts() {
+        use axum::routing::get;
+        use rejection::RejectionParts;
+
+        fn parts_response(parts: RejectionParts) -> Response {
+            Json(json!({
+                "status": parts.status().as_u16(),
+                "title": parts.title(),
+                "detail": parts.detail(),
+                "source_chain": parts.source_chain(),
+            }))
+            .into_response()
+        }
+
+        async fn query_handler(Query(_): Query<Payload>) {}
+        async fn form_handler(Form(_): Form<Payload>) {}
+
+        let app =
+            app()
+                .route("/query", get(query_handler))
+                .route("/form", post(form_handler))
+                .layer(JsonConfig::new().rejection_handler(
+                    |rejection, _: RejectionContext<'_, _>| parts_response(rejection.into()),
+                ))
+                .layer(QueryConfig::new().rejection_handler(
+                    |rejection, _: RejectionContext<'_, _>| parts_response(rejection.into()),
+                ))
+                .layer(FormConfig::new().rejection_handler(
+                    |rejection, _: RejectionContext<'_, _>| parts_response(rejection.into()),
+                ));
+
+        let requests = [
+            Request::builder()
+                .method(Method::POST)
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{"id":"foo"}"#))
+                .unwrap(),
+            Request::builder()
+                .uri("/query?id=foo")
+                .body(Body::empty())
+                .unwrap(),
+            Request::builder()
+                .method(Method::POST)
+                .uri("/form")
+                .header("content-type", "application/x-www-form-urlencoded")
+                .body(Body::from("id=foo"))
+                .unwrap(),
+        ];
+        let query_string_error = json!({
+            "status": 422,
+            "title": "Failed to deserialize query string",
+            "detail": "invalid digit found in string",
+            "source_chain": ["invalid digit found in string"],
+        });
+        let expected = [
+            json!({
+                "status": 422,
+                "title": "Failed to deserialize the JSON body into the target type",
+                "detail": "invalid type: string \"foo\", expected u32 at line 1 column 11",
+                "source_chain": ["invalid type: string \"foo\", expected u32 at line 1 column 11"],
+            }),
+            query_string_error.clone(),
+            // forms are deserialized like query strings
+            query_string_error,
+        ];
+
+        for (req, expected) in requests.into_iter().zip(expected) {
+            let res = app.clone().oneshot(req).await.unwrap();
+            let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+            let body = serde_json::from_slice::<Value>(&body[..]).unwrap();
+            assert_eq!(body, expected);
+        }
+    }
+
+    #[cfg(feature = "html-form")]
+    #[tokio::test]
+    async fn query_html_form_parser() {
+        use axum::routing::get;
+
+        #[derive(Deserialize)]
+        struct Params {
+            tag: Vec<String>,
+        }
+
+        async fn handler(Query(params): Query<Params>) -> String {
+            params.tag.join(",")
+        }
+
+        let mut app = Router::new()
+            .route("/", get(handler))
+            .layer(QueryConfig::new().parser(QueryParser::HtmlForm));
 
         let res = app
             .call(
                 Request::builder()
-                    .method(Method::POST)
-                    .uri("/")
+                    .uri("/?tag=a&tag=b")
                     .body(Body::empty())
                     .unwrap(),
             )
             .await
             .unwrap();
 
-        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
-        assert_eq!(res.headers()["content-type"], "text/plain; charset=utf-8");
+        assert_eq!(res.status(), StatusCode::OK);
         let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
         let body = String::from_utf8(body.to_vec()).unwrap();
-        assert_eq!(
-            body,
-            "Config of type \"axum_extractor_config::via_extensions::\
-            JsonConfig<hyper::body::body::Body>\" was already added. \
-            Configs can you be added once"
+        assert_eq!(body, "a,b");
+    }
+
+    #[tokio::test]
+    async fn json_capture_body() {
+        fn rejection_handler<B>(_rejection: JsonRejection, ctx: RejectionContext<'_, B>) -> String {
+            let body = ctx.body().unwrap();
+            format!(
+                "{} {}",
+                String::from_utf8_lossy(body.bytes()),
+                body.is_truncated()
+            )
+        }
+
+        let mut app = app().layer(
+            JsonConfig::new()
+                .capture_body(8)
+                .rejection_handler(rejection_handler),
         );
+
+        for (body, expected) in [
+            (r#"{"id":1"#, r#"{"id":1 false"#),
+            (r#"{"id":"foo"}"#, r#"{"id":"f true"#),
+        ] {
+            let res = app
+                .call(
+                    Request::builder()
+                        .method(Method::POST)
+                        .uri("/")
+                        .header("content-type", "application/json")
+                        .body(Body::from(body))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+
+            let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+            let body = String::from_utf8(body.to_vec()).unwrap();
+            assert_eq!(body, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn json_pointer() {
+        #[derive(Deserialize)]
+        struct Order {
+            #[allow(dead_code)]
+            items: Vec<Item>,
+        }
+
+        #[derive(Deserialize)]
+        struct Item {
+            #[allow(dead_code)]
+            price: u32,
+        }
+
+        async fn handler(Json(_): Json<Order>) {}
+
+        fn rejection_handler<B>(_rejection: JsonRejection, ctx: RejectionContext<'_, B>) -> String {
+            format!("{:?}", ctx.json_pointer())
+        }
+
+        let mut app = Router::new()
+            .route("/", post(handler))
+            .layer(JsonConfig::new().rejection_handler(rejection_handler));
+
+        for (body, expected) in [
+            (
+                json!({ "items": [{ "price": 1 }, { "price": "foo" }] }).to_string(),
+                r#"Some("/items/1/price")"#,
+            ),
+            (json!([]).to_string(), r#"Some("")"#),
+            (r#"{"items":"#.to_owned(), "None"),
+        ] {
+            let res = app
+                .call(
+                    Request::builder()
+                        .method(Method::POST)
+                        .uri("/")
+                        .header("content-type", "application/json")
+                        .body(Body::from(body))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+
+            let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+            let body = String::from_utf8(body.to_vec()).unwrap();
+            assert_eq!(body, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn json_redact() {
+        #[derive(Deserialize)]
+        struct Login {
+            #[allow(dead_code)]
+            username: String,
+            #[allow(dead_code)]
+            password: u32,
+        }
+
+        async fn handler(Json(_): Json<Login>) {}
+
+        fn rejection_handler<B>(_rejection: JsonRejection, ctx: RejectionContext<'_, B>) -> String {
+            format!(
+                "{} {}",
+                ctx.details_redacted(),
+                String::from_utf8_lossy(ctx.body().unwrap().bytes()),
+            )
+        }
+
+        let mut app = Router::new()
+            .route("/", post(handler))
+            .layer(JsonConfig::new().redact(["password"]));
+
+        let res = app
+            .call(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/")
+                    .header("content-type", "application/json")
+                    .body(Body::from(r#"{"username":"bob","password":"hunter2"}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        // the default response doesn't include the deserialization error, since it might contain
+        // the password
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert_eq!(
+            body,
+            "Failed to deserialize the JSON body into the target type"
+        );
+
+        let mut app = Router::new().route("/", post(handler)).layer(
+            JsonConfig::new()
+                .capture_body(1024)
+                .redact(["password"])
+                .rejection_handler(rejection_handler),
+        );
+
+        for (body, expected) in [
+            (
+                r#"{"username":"bob","password":"hunter2"}"#,
+                r#"true {"password":"[REDACTED]","username":"bob"}"#,
+            ),
+            (
+                r#"{"username":1,"password":"hunter2"}"#,
+                r#"false {"password":"[REDACTED]","username":1}"#,
+            ),
+        ] {
+            let res = app
+                .call(
+                    Request::builder()
+                        .method(Method::POST)
+                        .uri("/")
+                        .header("content-type", "application/json")
+                        .body(Body::from(body))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+
+            let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+            let body = String::from_utf8(body.to_vec()).unwrap();
+            assert_eq!(body, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn rejection_context() {
+        fn rejection_handler<B>(_rejection: JsonRejection, ctx: RejectionContext<'_, B>) -> String {
+            format!(
+                "{} {} {:?} {}",
+                ctx.method(),
+                ctx.uri(),
+                ctx.matched_path(),
+                ctx.target_type_name()
+            )
+        }
+
+        let mut app = app().layer(JsonConfig::new().rejection_handler(rejection_handler));
+
+        let res = app
+            .call(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/?a=1")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert_eq!(
+            body,
+            "POST /?a=1 Some(\"/\") axum_extractor_config::via_extensions::tests::Payload"
+        );
+    }
+
+    #[tokio::test]
+    async fn json_per_variant_rejection_handlers() {
+        let mut app = app().layer(
+            JsonConfig::new()
+                .on_missing_content_type(|_, _: RejectionContext<'_, _>| "missing content type")
+                .on_deserialize_error(|_, _: RejectionContext<'_, _>| "deserialize error")
+                .rejection_handler(|_, _: RejectionContext<'_, _>| "fallback"),
+        );
+
+        for (content_type, body, expected) in [
+            (None, "", "missing content type"),
+            (
+                Some("application/json"),
+                r#"{"id":"foo"}"#,
+                "deserialize error",
+            ),
+            (Some("application/json"), "{", "fallback"),
+        ] {
+            let mut req = Request::builder().method(Method::POST).uri("/");
+            if let Some(content_type) = content_type {
+                req = req.header("content-type", content_type);
+            }
+
+            let res = app.call(req.body(Body::from(body)).unwrap()).await.unwrap();
+
+            let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+            let body = String::from_utf8(body.to_vec()).unwrap();
+            assert_eq!(body, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn fallible_rejection_handler() {
+        fn rejection_handler<B>(
+            rejection: JsonRejection,
+            _ctx: RejectionContext<'_, B>,
+        ) -> Result<String, (StatusCode, &'static str)> {
+            match rejection {
+                JsonRejection::JsonDataError(_) => {
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, "failed to render error"))
+                }
+                other => Ok(other.to_string()),
+            }
+        }
+
+        let mut app = app().layer(JsonConfig::new().rejection_handler(rejection_handler));
+
+        let res = app
+            .call(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/")
+                    .header("content-type", "application/json")
+                    .body(Body::from(r#"{"id":"foo"}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(&body[..], b"failed to render error");
+    }
+
+    #[tokio::test]
+    async fn try_rejection_handler() {
+        fn base<B>(
+            rejection: &JsonRejection,
+            _ctx: RejectionContext<'_, B>,
+        ) -> Option<&'static str> {
+            match rejection {
+                JsonRejection::MissingJsonContentType(_) => Some("base content type"),
+                JsonRejection::JsonDataError(_) => Some("base data"),
+                _ => None,
+            }
+        }
+
+        fn overrides<B>(
+            rejection: &JsonRejection,
+            _ctx: RejectionContext<'_, B>,
+        ) -> Option<&'static str> {
+            match rejection {
+                JsonRejection::JsonDataError(_) => Some("override data"),
+                _ => None,
+            }
+        }
+
+        let mut app = app().layer(
+            JsonConfig::new()
+                .try_rejection_handler(overrides)
+                .try_rejection_handler(base),
+        );
+
+        for (content_type, body, status, expected) in [
+            (None, "", StatusCode::OK, "base content type"),
+            (Some("application/json"), r#"{"id":"foo"}"#, StatusCode::OK, "override data"),
+            (
+                Some("application/json"),
+                "{",
+                StatusCode::BAD_REQUEST,
+                "Failed to parse the request body as JSON: EOF while parsing an object at line 1 column 1",
+            ),
+        ] {
+            let mut req = Request::builder().method(Method::POST).uri("/");
+            if let Some(content_type) = content_type {
+                req = req.header("content-type", content_type);
+            }
+
+            let res = app.call(req.body(Body::from(body)).unwrap()).await.unwrap();
+
+            assert_eq!(res.status(), status);
+            let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+            let body = String::from_utf8(body.to_vec()).unwrap();
+            assert_eq!(body, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn query_plus_as_space_disabled() {
+        use axum::routing::get;
+
+        #[derive(Deserialize)]
+        struct Params {
+            name: String,
+        }
+
+        async fn handler(Query(params): Query<Params>) -> String {
+            params.name
+        }
+
+        let mut app = Router::new()
+            .route("/", get(handler))
+            .layer(QueryConfig::new().plus_as_space(false));
+
+        let res = app
+            .call(
+                Request::builder()
+                    .uri("/?name=a+b%20c")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert_eq!(body, "a+b c");
+    }
+
+    #[tokio::test]
+    async fn query_limits() {
+        use axum::routing::get;
+        use std::collections::HashMap;
+
+        async fn handler(Query(_): Query<HashMap<String, String>>) {}
+
+        fn rejection_handler<B>(
+            rejection: rejection::QueryRejection,
+            _ctx: RejectionContext<'_, B>,
+        ) -> String {
+            match rejection {
+                rejection::QueryRejection::QueryStringTooLong(inner) => {
+                    format!("too long {} {}", inner.length(), inner.max_length())
+                }
+                rejection::QueryRejection::TooManyQueryParams(inner) => {
+                    format!("too many {} {}", inner.count(), inner.max_params())
+                }
+                other => other.to_string(),
+            }
+        }
+
+        let mut app = Router::new().route("/", get(handler)).layer(
+            QueryConfig::new()
+                .max_length(16)
+                .max_params(2)
+                .rejection_handler(rejection_handler),
+        );
+
+        for (uri, expected) in [
+            ("/?a=1&b=2", ""),
+            ("/?a=1&b=2&c=3", "too many 3 2"),
+            ("/?a=123456789&b=123456789", "too long 23 16"),
+        ] {
+            let res = app
+                .call(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+
+            let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+            let body = String::from_utf8(body.to_vec()).unwrap();
+            assert_eq!(body, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn json_content_length_above_max_body_size() {
+        let app = app().layer(JsonConfig::new().max_body_size(16));
+
+        let request = |content_length: &str| {
+            Request::builder()
+                .method(Method::POST)
+                .uri("/")
+                .header("content-type", "application/json")
+                .header("content-length", content_length)
+                .body(Body::from(r#"{"id":1}"#))
+                .unwrap()
+        };
+
+        let res = app.clone().oneshot(request("8")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        // the body itself is below the limit, so only the `Content-Length` can be rejected
+        let res = app.oneshot(request("1024")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn json_body_timeout() {
+        let app = app().layer(JsonConfig::new().body_timeout(Duration::from_millis(10)));
+
+        let body = futures_util::stream::pending::<Result<Bytes, std::io::Error>>();
+        let res = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/")
+                    .header("content-type", "application/json")
+                    .body(Body::wrap_stream(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::REQUEST_TIMEOUT);
+        let info = res.extensions().get::<RejectionInfo>().unwrap();
+        assert_eq!(info.kind(), rejection::RejectionKind::BodyTimeout);
+    }
+
+    #[tokio::test]
+    async fn form_max_body_size() {
+        use axum::routing::post;
+        use std::collections::HashMap;
+
+        async fn handler(Form(_): Form<HashMap<String, String>>) {}
+
+        fn rejection_handler<B>(
+            rejection: rejection::FormRejection,
+            _ctx: RejectionContext<'_, B>,
+        ) -> String {
+            match rejection {
+                rejection::FormRejection::FormBodyTooLarge(inner) => {
+                    format!("too large {}", inner.max_size())
+                }
+                other => other.to_string(),
+            }
+        }
+
+        let mut app = Router::new().route("/", post(handler)).layer(
+            FormConfig::new()
+                .max_body_size(8)
+                .rejection_handler(rejection_handler),
+        );
+
+        for (body, content_length, expected) in [
+            ("a=1&b=2", None, ""),
+            ("a=123&b=456", None, "too large 8"),
+            ("a=123&b=456", Some("11"), "too large 8"),
+        ] {
+            let mut req = Request::builder()
+                .method(Method::POST)
+                .uri("/")
+                .header("content-type", "application/x-www-form-urlencoded");
+            if let Some(content_length) = content_length {
+                req = req.header("content-length", content_length);
+            }
+
+            let res = app.call(req.body(Body::from(body)).unwrap()).await.unwrap();
+
+            let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+            let body = String::from_utf8(body.to_vec()).unwrap();
+            assert_eq!(body, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn form_disallow_query_on_get() {
+        use axum::routing::get;
+        use std::collections::HashMap;
+
+        async fn handler(Form(form): Form<HashMap<String, String>>) -> String {
+            form["a"].clone()
+        }
+
+        let mut app = Router::new()
+            .route("/", get(handler))
+            .layer(FormConfig::new().allow_query_on_get(false));
+
+        let res = app
+            .call(Request::builder().uri("/?a=1").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let res = app
+            .call(
+                Request::builder()
+                    .uri("/?a=1")
+                    .header("content-type", "application/x-www-form-urlencoded")
+                    .body(Body::from("a=2"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(&body[..], b"2");
+    }
+
+    #[cfg(feature = "html")]
+    #[tokio::test]
+    async fn html_preset() {
+        use axum::routing::post;
+        use presets::{HtmlError, HtmlPage};
+
+        #[derive(Deserialize)]
+        struct Signup {
+            #[allow(dead_code)]
+            age: u32,
+        }
+
+        async fn handler(Form(_): Form<Signup>) {}
+
+        fn template(error: &HtmlError<'_>) -> String {
+            format!("{} {}", error.status().as_u16(), error.title_escaped())
+        }
+
+        let req = || {
+            Request::builder()
+                .method(Method::POST)
+                .uri("/signup")
+                .header("content-type", "application/x-www-form-urlencoded")
+                .body(Body::from("age=<b>"))
+                .unwrap()
+        };
+
+        let mut app = Router::new()
+            .route("/signup", post(handler))
+            .layer(FormConfig::new().rejection_handler(presets::html()));
+
+        let res = app.call(req()).await.unwrap();
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(res.headers()["content-type"], "text/html; charset=utf-8");
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert_eq!(
+            body,
+            "<!DOCTYPE html>\n\
+            <html>\n\
+            <head>\n\
+            <meta charset=\"utf-8\">\n\
+            <title>422 Unprocessable Entity</title>\n\
+            </head>\n\
+            <body>\n\
+            <h1>Failed to deserialize query string</h1>\n\
+            <p>invalid digit found in string</p>\n\
+            </body>\n\
+            </html>\n",
+        );
+
+        let mut app = Router::new().route("/signup", post(handler)).layer(
+            FormConfig::new().rejection_handler(HtmlPage::new().template(template).into_handler()),
+        );
+
+        let res = app.call(req()).await.unwrap();
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(&body[..], b"422 Failed to deserialize query string");
+    }
+
+    #[cfg(feature = "charset")]
+    #[tokio::test]
+    async fn form_charset() {
+        use axum::routing::post;
+        use std::collections::HashMap;
+
+        async fn handler(Form(form): Form<HashMap<String, String>>) -> String {
+            form["name"].clone()
+        }
+
+        let mut app = Router::new().route("/", post(handler));
+
+        for (content_type, status, expected) in [
+            (
+                "application/x-www-form-urlencoded; charset=ISO-8859-1",
+                StatusCode::OK,
+                "Jos\u{e9} M",
+            ),
+            (
+                "application/x-www-form-urlencoded; charset=\"utf-8\"",
+                StatusCode::OK,
+                "Jos\u{fffd} M",
+            ),
+            (
+                "application/x-www-form-urlencoded; charset=nope",
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                "Form body has an unsupported charset: `nope`",
+            ),
+        ] {
+            let res = app
+                .call(
+                    Request::builder()
+                        .method(Method::POST)
+                        .uri("/")
+                        .header("content-type", content_type)
+                        .body(Body::from("name=Jos%E9+M"))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+
+            assert_eq!(res.status(), status);
+            let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+            let body = String::from_utf8(body.to_vec()).unwrap();
+            assert_eq!(body, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn on_rejection() {
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Mutex,
+        };
+
+        let count = Arc::new(AtomicUsize::new(0));
+        let paths = Arc::new(Mutex::new(Vec::new()));
+
+        let mut app = app().layer(
+            JsonConfig::new()
+                .on_rejection({
+                    let count = count.clone();
+                    move |_, _| {
+                        count.fetch_add(1, Ordering::SeqCst);
+                    }
+                })
+                .on_rejection({
+                    let paths = paths.clone();
+                    move |_, ctx| {
+                        paths.lock().unwrap().push(ctx.uri().to_string());
+                    }
+                }),
+        );
+
+        for uri in ["/", "/?a=1"] {
+            let res = app
+                .call(
+                    Request::builder()
+                        .method(Method::POST)
+                        .uri(uri)
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+
+            // the response isn't changed
+            assert_eq!(res.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        }
+
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+        assert_eq!(*paths.lock().unwrap(), ["/", "/?a=1"]);
+    }
+
+    #[tokio::test]
+    async fn on_success() {
+        use std::sync::Mutex;
+
+        let body_sizes = Arc::new(Mutex::new(Vec::new()));
+
+        let mut app = app().layer(JsonConfig::new().on_success({
+            let body_sizes = body_sizes.clone();
+            move |_: &RequestParts<_>, stats: ExtractionStats| {
+                body_sizes.lock().unwrap().push(stats.body_size());
+            }
+        }));
+
+        for body in [json!({ "id": 1 }), json!({ "id": "foo" })] {
+            app.call(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        }
+
+        // only called for the request that succeeded
+        assert_eq!(*body_sizes.lock().unwrap(), [Some(8)]);
+    }
+
+    #[tokio::test]
+    async fn override_status() {
+        use axum::routing::get;
+
+        #[derive(Deserialize)]
+        struct Params {
+            #[allow(dead_code)]
+            page: u32,
+        }
+
+        async fn handler(Query(_): Query<Params>) {}
+
+        let mut app = Router::new()
+            .route("/", get(handler))
+            .layer(QueryConfig::new().override_status(StatusCode::BAD_REQUEST));
+
+        let res = app
+            .call(
+                Request::builder()
+                    .uri("/?page=foo")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert_eq!(
+            body,
+            "Failed to deserialize query string: invalid digit found in string"
+        );
+    }
+
+    #[tokio::test]
+    async fn detail_level() {
+        use rejection::DetailLevel;
+
+        for (detail_level, expected) in [
+            (
+                DetailLevel::Full,
+                "Failed to deserialize the JSON body into the target type: \
+                invalid type: string \"foo\", expected u32 at line 1 column 11",
+            ),
+            (
+                DetailLevel::Message,
+                "Failed to deserialize the JSON body into the target type",
+            ),
+            (DetailLevel::Minimal, ""),
+        ] {
+            let mut app = app().layer(JsonConfig::new().detail_level(detail_level));
+
+            let res = app
+                .call(
+                    Request::builder()
+                        .method(Method::POST)
+                        .uri("/")
+                        .header("content-type", "application/json")
+                        .body(Body::from(json!({ "id": "foo" }).to_string()))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+
+            assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+            let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+            let body = String::from_utf8(body.to_vec()).unwrap();
+            assert_eq!(body, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn error_codes() {
+        use rejection::{AnyRejection, RejectionKind};
+
+        fn rejection_handler<B>(_rejection: AnyRejection, ctx: RejectionContext<'_, B>) -> String {
+            format!("{:?} {:?}", ctx.kind(), ctx.error_code())
+        }
+
+        let mut app = app()
+            .layer(JsonConfig::new().error_code(RejectionKind::DeserializeError, "INVALID_BODY"))
+            .layer(
+                GlobalRejectionConfig::new()
+                    .error_code(RejectionKind::DeserializeError, 1)
+                    .error_code(RejectionKind::SyntaxError, 2)
+                    .rejection_handler(rejection_handler),
+            );
+
+        for (body, expected) in [
+            (
+                r#"{"id":"foo"}"#,
+                r#"DeserializeError Some(String("INVALID_BODY"))"#,
+            ),
+            ("{", "SyntaxError Some(Int(2))"),
+        ] {
+            let res = app
+                .call(
+                    Request::builder()
+                        .method(Method::POST)
+                        .uri("/")
+                        .header("content-type", "application/json")
+                        .body(Body::from(body))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+
+            let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+            let body = String::from_utf8(body.to_vec()).unwrap();
+            assert_eq!(body, expected);
+        }
+
+        let res = app
+            .call(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(&body[..], b"InvalidContentType None");
+    }
+
+    #[tokio::test]
+    async fn localized_message() {
+        use rejection::RejectionKind;
+
+        fn rejection_handler<B>(_rejection: JsonRejection, ctx: RejectionContext<'_, B>) -> String {
+            format!("{:?}", ctx.localized_message())
+        }
+
+        let catalog = StaticMessageCatalog::new()
+            .message("en", RejectionKind::SyntaxError, "Invalid JSON")
+            .message("de", RejectionKind::SyntaxError, "Ungültiges JSON")
+            .message("de-CH", RejectionKind::SyntaxError, "Ungültiges JSON!")
+            .default_language("en");
+
+        let mut app = app().layer(
+            JsonConfig::new()
+                .message_catalog(catalog)
+                .rejection_handler(rejection_handler),
+        );
+
+        for (accept_language, expected) in [
+            (None, r#"Some("Invalid JSON")"#),
+            (Some("fr"), r#"Some("Invalid JSON")"#),
+            (Some("de-AT"), r#"Some("Ungültiges JSON")"#),
+            (Some("de-CH, de;q=0.9"), r#"Some("Ungültiges JSON!")"#),
+            (Some("fr;q=0.9, en;q=0.5, de"), r#"Some("Ungültiges JSON")"#),
+            (Some("de;q=0, en"), r#"Some("Invalid JSON")"#),
+        ] {
+            let mut req = Request::builder()
+                .method(Method::POST)
+                .uri("/")
+                .header("content-type", "application/json");
+            if let Some(accept_language) = accept_language {
+                req = req.header("accept-language", accept_language);
+            }
+            let res = app.call(req.body(Body::from("{")).unwrap()).await.unwrap();
+
+            let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+            let body = String::from_utf8(body.to_vec()).unwrap();
+            assert_eq!(body, expected, "{:?}", accept_language);
+        }
+    }
+
+    #[tokio::test]
+    async fn problem_details() {
+        use axum::routing::get;
+
+        #[derive(Deserialize)]
+        struct Params {
+            #[allow(dead_code)]
+            page: u32,
+        }
+
+        async fn query_handler(Query(_): Query<Params>) {}
+
+        let mut app = app()
+            .route("/query", get(query_handler))
+            .layer(
+                JsonConfig::new()
+                    .error_code(rejection::RejectionKind::DeserializeError, "INVALID_BODY"),
+            )
+            .layer(QueryConfig::new().rejection_handler(presets::problem_details()))
+            .layer(
+                GlobalRejectionConfig::new().rejection_handler(
+                    presets::ProblemDetails::new()
+                        .type_base("https://example.com/problems/")
+                        .into_handler(),
+                ),
+            );
+
+        let res = app
+            .call(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/")
+                    .header("content-type", "application/json")
+                    .body(Body::from(json!({ "id": "foo" }).to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(res.headers()["content-type"], "application/problem+json");
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        let body = serde_json::from_slice::<Value>(&body[..]).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "type": "https://example.com/problems/deserialize-error",
+                "title": "Failed to deserialize the JSON body into the target type",
+                "status": 422,
+                "detail": "invalid type: string \"foo\", expected u32 at line 1 column 11",
+                "instance": "/",
+                "code": "INVALID_BODY",
+                "pointer": "/id",
+            })
+        );
+
+        let res = app
+            .call(
+                Request::builder()
+                    .uri("/query?page=foo")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        let body = serde_json::from_slice::<Value>(&body[..]).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "type": "about:blank",
+                "title": "Failed to deserialize query string",
+                "status": 422,
+                "detail": "invalid digit found in string",
+                "instance": "/query",
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn simple_json() {
+        let mut app = app().layer(JsonConfig::new().rejection_handler(presets::simple_json()));
+
+        for (req, status, expected) in [
+            (
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/")
+                    .header("content-type", "application/json")
+                    .body(Body::from(json!({ "id": "foo" }).to_string()))
+                    .unwrap(),
+                StatusCode::UNPROCESSABLE_ENTITY,
+                json!({
+                    "error": "Failed to deserialize the JSON body into the target type",
+                    "details": "invalid type: string \"foo\", expected u32 at line 1 column 11",
+                    "pointer": "/id",
+                }),
+            ),
+            (
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/")
+                    .body(Body::empty())
+                    .unwrap(),
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                json!({
+                    "error": "Expected request with `Content-Type: application/json`",
+                }),
+            ),
+        ] {
+            let res = app.call(req).await.unwrap();
+
+            assert_eq!(res.status(), status);
+            assert_eq!(res.headers()["content-type"], "application/json");
+            let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+            let body = serde_json::from_slice::<Value>(&body[..]).unwrap();
+            assert_eq!(body, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn negotiate() {
+        use presets::{Format, Negotiate};
+
+        let req = |accept: Option<&str>| {
+            let mut req = Request::builder().method(Method::POST).uri("/");
+            if let Some(accept) = accept {
+                req = req.header("accept", accept);
+            }
+            req.body(Body::empty()).unwrap()
+        };
+
+        for (default_format, accept, content_type) in [
+            (Format::Json, None, "application/json"),
+            (Format::Json, Some("*/*"), "application/json"),
+            (
+                Format::Json,
+                Some("text/plain"),
+                "text/plain; charset=utf-8",
+            ),
+            (
+                Format::Json,
+                Some("text/plain;q=0.5, application/problem+json"),
+                "application/json",
+            ),
+            (Format::PlainText, None, "text/plain; charset=utf-8"),
+            (
+                Format::PlainText,
+                Some("image/png, application/json;q=0.1"),
+                "application/json",
+            ),
+        ] {
+            let mut app = app().layer(
+                JsonConfig::new().rejection_handler(
+                    Negotiate::new()
+                        .default_format(default_format)
+                        .into_handler(),
+                ),
+            );
+
+            let res = app.call(req(accept)).await.unwrap();
+            assert_eq!(res.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+            assert_eq!(
+                res.headers()["content-type"],
+                content_type,
+                "{:?} {:?}",
+                default_format,
+                accept
+            );
+        }
+
+        let mut app = app().layer(JsonConfig::new().rejection_handler(presets::negotiate()));
+        let res = app.call(req(Some("text/plain"))).await.unwrap();
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(
+            &body[..],
+            b"Expected request with `Content-Type: application/json`"
+        );
+
+        #[cfg(feature = "html")]
+        {
+            let res = app
+                .call(req(Some("text/html,application/xml;q=0.9,*/*;q=0.8")))
+                .await
+                .unwrap();
+            assert_eq!(res.headers()["content-type"], "text/html; charset=utf-8");
+        }
+    }
+
+    #[cfg(feature = "dev-errors")]
+    #[tokio::test]
+    async fn dev_errors() {
+        use presets::DevErrors;
+
+        let mut enabled = app().layer(
+            JsonConfig::new().capture_body(1024).rejection_handler(
+                DevErrors::new()
+                    .enabled(true)
+                    .context_lines(1)
+                    .into_handler(),
+            ),
+        );
+
+        let res = enabled
+            .call(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/")
+                    .header("content-type", "application/json")
+                    .body(Body::from("{\n  \"id\": \"foo\"\n}\n"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert_eq!(
+            body,
+            "\x1b[1m\x1b[31mFailed to deserialize the JSON body into the target type: \
+            invalid type: string \"foo\", expected u32 at line 2 column 13\x1b[0m\n\
+            \n\
+            \x1b[2m     1 |\x1b[0m {\n\
+            \x1b[2m>    2 |\x1b[0m   \"id\": \"foo\"\n\
+            \x1b[2m       |\x1b[0m             \x1b[31m^\x1b[0m\n\
+            \x1b[2m     3 |\x1b[0m }\n",
+        );
+
+        let mut disabled = app().layer(
+            JsonConfig::new().rejection_handler(DevErrors::new().enabled(false).into_handler()),
+        );
+
+        let res = disabled
+            .call(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(
+            &body[..],
+            b"Expected request with `Content-Type: application/json`"
+        );
+    }
+
+    #[tokio::test]
+    async fn graphql() {
+        let mut app = app().layer(
+            JsonConfig::new()
+                .error_code(rejection::RejectionKind::DeserializeError, "BAD_USER_INPUT")
+                .rejection_handler(presets::graphql()),
+        );
+
+        let res = app
+            .call(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/")
+                    .header("content-type", "application/json")
+                    .body(Body::from(json!({ "id": "foo" }).to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        let body = serde_json::from_slice::<Value>(&body[..]).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "errors": [
+                    {
+                        "message": "Failed to deserialize the JSON body into the target type",
+                        "extensions": {
+                            "status": 422,
+                            "detail": "invalid type: string \"foo\", expected u32 at line 1 column 11",
+                            "code": "BAD_USER_INPUT",
+                            "pointer": "/id",
+                        },
+                    },
+                ],
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn docs_links() {
+        use rejection::RejectionKind;
+
+        let mut app = app()
+            .layer(
+                JsonConfig::new()
+                    .docs_slug(RejectionKind::SyntaxError, "invalid-json")
+                    .rejection_handler(presets::simple_json()),
+            )
+            .layer(GlobalRejectionConfig::new().docs_url("https://example.com/errors/"));
+
+        for (body, expected) in [
+            ("{", "https://example.com/errors/invalid-json"),
+            (
+                r#"{"id":"foo"}"#,
+                "https://example.com/errors/deserialize-error",
+            ),
+        ] {
+            let res = app
+                .call(
+                    Request::builder()
+                        .method(Method::POST)
+                        .uri("/")
+                        .header("content-type", "application/json")
+                        .body(Body::from(body))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+
+            let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+            let body = serde_json::from_slice::<Value>(&body[..]).unwrap();
+            assert_eq!(body["docs"], expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn verbose_errors_header() {
+        let mut app = app().layer(JsonConfig::new().verbose_errors_header(
+            HeaderName::from_static("x-debug-errors"),
+            HeaderValue::from_static("secret"),
+        ));
+
+        for (token, expected) in [
+            (
+                None,
+                "Failed to deserialize the JSON body into the target type",
+            ),
+            (
+                Some("nope"),
+                "Failed to deserialize the JSON body into the target type",
+            ),
+            (
+                Some("secret"),
+                "Failed to deserialize the JSON body into the target type: \
+                invalid type: string \"foo\", expected u32 at line 1 column 11",
+            ),
+        ] {
+            let mut req = Request::builder()
+                .method(Method::POST)
+                .uri("/")
+                .header("content-type", "application/json");
+            if let Some(token) = token {
+                req = req.header("x-debug-errors", token);
+            }
+            let res = app
+                .call(req.body(Body::from(r#"{"id":"foo"}"#)).unwrap())
+                .await
+                .unwrap();
+
+            let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+            let body = String::from_utf8(body.to_vec()).unwrap();
+            assert_eq!(body, expected, "{:?}", token);
+        }
+    }
+
+    #[tokio::test]
+    async fn rejection_headers() {
+        use axum::http::{header, HeaderValue};
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("text/html"));
+
+        let mut app = app().layer(JsonConfig::new().rejection_headers(headers));
+
+        let res = app
+            .call(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert_eq!(res.headers()["cache-control"], "no-store");
+        assert_eq!(res.headers()["content-type"], "text/plain; charset=utf-8");
+    }
+
+    #[tokio::test]
+    async fn global_rejection_config() {
+        use axum::routing::get;
+
+        #[derive(Deserialize)]
+        struct Params {
+            #[allow(dead_code)]
+            page: u32,
+        }
+
+        async fn handler(Query(_): Query<Params>) {}
+
+        fn global_handler<B>(
+            rejection: rejection::AnyRejection,
+            _ctx: RejectionContext<'_, B>,
+        ) -> String {
+            match rejection {
+                rejection::AnyRejection::Json(_) => "global json".to_owned(),
+                rejection::AnyRejection::Query(_) => "global query".to_owned(),
+                other => other.to_string(),
+            }
+        }
+
+        let mut app = Router::new()
+            .route("/", post(|Json(_): Json<Payload>| async {}))
+            .route("/query", get(handler))
+            .route(
+                "/form",
+                post(|Form(_): Form<HashMap<String, String>>| async {}),
+            )
+            .layer(FormConfig::new().rejection_handler(|_, _: RejectionContext<'_, _>| "form"))
+            .layer(GlobalRejectionConfig::new().rejection_handler(global_handler));
+
+        for (method, uri, expected) in [
+            (Method::POST, "/", "global json"),
+            (Method::GET, "/query?page=foo", "global query"),
+            (Method::POST, "/form", "form"),
+        ] {
+            let res = app
+                .call(
+                    Request::builder()
+                        .method(method)
+                        .uri(uri)
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+
+            let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+            let body = String::from_utf8(body.to_vec()).unwrap();
+            assert_eq!(body, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn rejection_info() {
+        let mut app = app()
+            .layer(JsonConfig::new().rejection_handler(|_, _: RejectionContext<'_, _>| "custom"));
+
+        let res = app
+            .call(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let info = res.extensions().get::<RejectionInfo>().unwrap();
+        assert_eq!(info.extractor(), "Json");
+        assert_eq!(
+            info.target_type_name(),
+            "axum_extractor_config::via_extensions::tests::Payload"
+        );
+        assert_eq!(
+            info.message(),
+            "Expected request with `Content-Type: application/json`"
+        );
+    }
+
+    #[tokio::test]
+    async fn rejection_message_formatted_when_read() {
+        let res = app()
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let info = res.extensions().get::<RejectionInfo>().unwrap();
+        assert!(!info.is_formatted());
+        assert_eq!(
+            info.message(),
+            "Expected request with `Content-Type: application/json`"
+        );
+        assert!(info.is_formatted());
+
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(
+            &body[..],
+            b"Expected request with `Content-Type: application/json`"
+        );
+    }
+
+    #[tokio::test]
+    async fn rejection_message_not_formatted_by_handler() {
+        let res = app()
+            .layer(
+                JsonConfig::new().rejection_handler(|_, ctx: RejectionContext<'_, _>| {
+                    match ctx.kind() {
+                        rejection::RejectionKind::InvalidContentType => {
+                            StatusCode::UNSUPPORTED_MEDIA_TYPE
+                        }
+                        _ => StatusCode::BAD_REQUEST,
+                    }
+                }),
+            )
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let info = res.extensions().get::<RejectionInfo>().unwrap();
+        assert!(!info.is_formatted());
+        assert!(info.rejection::<JsonRejection>().is_none());
+        assert_eq!(
+            info.message(),
+            "Expected request with `Content-Type: application/json`"
+        );
+        assert!(info.is_formatted());
+    }
+
+    #[tokio::test]
+    async fn rejection_in_middleware() {
+        use axum::middleware::{self, Next};
+
+        async fn missing_content_type<B>(req: Request<B>, next: Next<B>) -> Response {
+            let mut res = next.run(req).await;
+            let missing = res
+                .extensions()
+                .get::<RejectionInfo>()
+                .and_then(|info| info.rejection::<JsonRejection>())
+                .is_some_and(|rejection| {
+                    matches!(rejection, JsonRejection::MissingJsonContentType(_))
+                });
+            if missing {
+                res.headers_mut()
+                    .insert("accept-post", HeaderValue::from_static("application/json"));
+            }
+            res
+        }
+
+        let res = app()
+            .layer(middleware::from_fn(missing_content_type))
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert_eq!(res.headers()["accept-post"], "application/json");
+
+        // rejections from rejection handlers aren't kept
+        let res = app()
+            .layer(JsonConfig::new().rejection_handler(|_, _: RejectionContext<'_, _>| "custom"))
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let info = res.extensions().get::<RejectionInfo>().unwrap();
+        assert!(info.rejection::<JsonRejection>().is_none());
+    }
+
+    #[tokio::test]
+    async fn configured_rejection() {
+        let req = Request::builder()
+            .method(Method::POST)
+            .uri("/")
+            .body(Body::empty())
+            .unwrap();
+        let mut req = RequestParts::new(req);
+
+        let rejection = match Json::<Payload>::from_request(&mut req).await {
+            Ok(_) => panic!("expected a rejection"),
+            Err(rejection) => rejection,
+        };
+        assert!(matches!(
+            rejection.rejection(),
+            Some(JsonRejection::MissingJsonContentType(_))
+        ));
+        assert_eq!(
+            rejection.to_string(),
+            "Expected request with `Content-Type: application/json`"
+        );
+        assert_eq!(
+            rejection::status_of(rejection.rejection().unwrap()),
+            StatusCode::UNSUPPORTED_MEDIA_TYPE
+        );
+        assert_eq!(
+            rejection.into_response().status(),
+            StatusCode::UNSUPPORTED_MEDIA_TYPE
+        );
+    }
+
+    #[tokio::test]
+    async fn lenient_bools() {
+        use axum::routing::get;
+
+        #[derive(Deserialize)]
+        struct Params {
+            a: bool,
+            b: bool,
+            c: Option<bool>,
+        }
+
+        async fn handler(Query(query): Query<Params>, Form(form): Form<Params>) -> String {
+            format!(
+                "{} {} {:?} {} {} {:?}",
+                query.a, query.b, query.c, form.a, form.b, form.c
+            )
+        }
+
+        let mut app = Router::new()
+            .route("/", get(handler).post(handler))
+            .layer(QueryConfig::new().lenient_bools(true))
+            .layer(FormConfig::new().lenient_bools(true));
+
+        let res = app
+            .call(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/?a=on&b=0&c=YES")
+                    .header("content-type", "application/x-www-form-urlencoded")
+                    .body(Body::from("a=1&b=off"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert_eq!(body, "true false Some(true) true false None");
+
+        let res = app
+            .call(
+                Request::builder()
+                    .uri("/?a=on&b=maybe")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert_eq!(
+            body,
+            "Failed to deserialize query string: invalid value: string \"maybe\", expected a boolean"
+        );
+    }
+
+    #[tokio::test]
+    async fn empty_string_as_none() {
+        use axum::routing::get;
+
+        #[derive(Deserialize)]
+        struct Params {
+            name: Option<String>,
+            age: Option<u32>,
+        }
+
+        async fn handler(Query(params): Query<Params>) -> String {
+            format!("{:?} {:?}", params.name, params.age)
+        }
+
+        let mut app = Router::new()
+            .route("/", get(handler))
+            .layer(QueryConfig::new().empty_string_as_none(true));
+
+        for (uri, expected) in [
+            ("/?name=&age=", "None None"),
+            ("/?name=bob&age=30", "Some(\"bob\") Some(30)"),
+            ("/", "None None"),
+        ] {
+            let res = app
+                .call(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+
+            assert_eq!(res.status(), StatusCode::OK);
+            let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+            let body = String::from_utf8(body.to_vec()).unwrap();
+            assert_eq!(body, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn key_aliases() {
+        use axum::routing::get;
+
+        #[derive(Deserialize)]
+        struct Params {
+            page_size: u32,
+        }
+
+        async fn handler(Query(params): Query<Params>) -> String {
+            params.page_size.to_string()
+        }
+
+        let mut app = Router::new().route("/", get(handler)).layer(
+            QueryConfig::new()
+                .key_aliases([("PageSize", "page_size")])
+                .case_insensitive_keys(true),
+        );
+
+        for uri in [
+            "/?PageSize=10",
+            "/?page_size=10",
+            "/?PAGE_SIZE=10",
+            "/?pagesize=10",
+        ] {
+            let res = app
+                .call(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+
+            assert_eq!(res.status(), StatusCode::OK);
+            let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+            assert_eq!(&body[..], b"10");
+        }
+    }
+
+    #[tokio::test]
+    async fn scalar_parser() {
+        use axum::routing::get;
+
+        #[derive(Deserialize)]
+        struct Params {
+            since: Option<String>,
+        }
+
+        async fn handler(Query(params): Query<Params>) -> String {
+            format!("{:?}", params.since)
+        }
+
+        // accept `YYYYMMDD` in addition to `YYYY-MM-DD`
+        let parse_date = |value: &str| {
+            if value.contains('-') {
+                Ok(value.to_owned())
+            } else if value.len() == 8 && value.bytes().all(|b| b.is_ascii_digit()) {
+                Ok(format!("{}-{}-{}", &value[..4], &value[4..6], &value[6..]))
+            } else {
+                Err(format!("invalid date `{}`", value))
+            }
+        };
+
+        let mut app = Router::new()
+            .route("/", get(handler))
+            .layer(QueryConfig::new().scalar_parser("since", parse_date));
+
+        for (uri, status, expected) in [
+            ("/?since=20220714", StatusCode::OK, "Some(\"2022-07-14\")"),
+            ("/?since=2022-07-14", StatusCode::OK, "Some(\"2022-07-14\")"),
+            ("/", StatusCode::OK, "None"),
+            (
+                "/?since=yesterday",
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "Failed to deserialize query string: invalid date `yesterday`",
+            ),
+        ] {
+            let res = app
+                .call(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+
+            assert_eq!(res.status(), status);
+            let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+            let body = String::from_utf8(body.to_vec()).unwrap();
+            assert_eq!(body, expected);
+        }
+    }
+
+    #[cfg(feature = "qs")]
+    fn qs_app() -> Router<Body> {
+        use axum::routing::get;
+
+        #[derive(Deserialize)]
+        struct Params {
+            filter: Filter,
+        }
+
+        #[derive(Deserialize)]
+        struct Filter {
+            name: String,
+            age: u32,
+        }
+
+        async fn handler(Query(params): Query<Params>) -> String {
+            format!("{} {}", params.filter.name, params.filter.age)
+        }
+
+        Router::new()
+            .route("/", get(handler))
+            .layer(QueryConfig::new().parser(QueryParser::Qs { strict: true }))
+    }
+
+    #[cfg(feature = "qs")]
+    #[tokio::test]
+    async fn query_qs_parser() {
+        let mut app = qs_app();
+
+        let res = app
+            .call(
+                Request::builder()
+                    .uri("/?filter[name]=x&filter[age]=3")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert_eq!(body, "x 3");
+    }
+
+    #[cfg(feature = "qs")]
+    #[tokio::test]
+    async fn query_qs_parser_reports_key() {
+        let mut app = qs_app();
+
+        let res = app
+            .call(
+                Request::builder()
+                    .uri("/?filter[name]=x&filter[age]=foo")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert_eq!(
+            body,
+            "Failed to deserialize query string: `filter[age]`: invalid digit found in string"
+        );
+    }
+
+    #[cfg(feature = "qs")]
+    #[tokio::test]
+    async fn form_qs_parser() {
+        use axum::routing::post;
+
+        #[derive(Deserialize)]
+        struct Payload {
+            items: Vec<String>,
+            user: User,
+        }
+
+        #[derive(Deserialize)]
+        struct User {
+            name: String,
+        }
+
+        async fn handler(Form(payload): Form<Payload>) -> String {
+            format!("{:?} {}", payload.items, payload.user.name)
+        }
+
+        let mut app = Router::new()
+            .route("/", post(handler))
+            .layer(FormConfig::new().parser(QueryParser::Qs { strict: false }));
+
+        for (body, status, expected) in [
+            (
+                "items%5B%5D=a&items%5B%5D=b&user%5Bname%5D=x",
+                StatusCode::OK,
+                "[\"a\", \"b\"] x",
+            ),
+            (
+                "items%5B%5D=a&user%5Bname%5D=x&user=y",
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "Failed to deserialize query string: `user`: Multiple values for one key: \"user\"",
+            ),
+        ] {
+            let res = app
+                .call(
+                    Request::builder()
+                        .method(Method::POST)
+                        .uri("/")
+                        .header("content-type", "application/x-www-form-urlencoded")
+                        .body(Body::from(body))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+
+            assert_eq!(res.status(), status);
+            let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+            let body = String::from_utf8(body.to_vec()).unwrap();
+            assert_eq!(body, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn error_on_duplicate_config() {
+        let mut app = app().layer(JsonConfig::new()).layer(JsonConfig::new());
+
+        let res = app
+            .call(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(res.headers()["content-type"], "text/plain; charset=utf-8");
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert_eq!(
+            body,
+            "Config of type \"axum_extractor_config::via_extensions::\
+            JsonConfig<hyper::body::body::Body>\" was already added. \
+            Configs can you be added once"
+        );
+    }
+
+    #[tokio::test]
+    async fn json_content_type_checked_before_reading_body() {
+        let mut req = RequestParts::new(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/")
+                .header("content-type", "text/plain")
+                .body(Body::from(json!({ "id": 123 }).to_string()))
+                .unwrap(),
+        );
+
+        let rejection = req.extract::<Json<Payload>>().await.err().unwrap();
+        assert!(matches!(
+            rejection.rejection(),
+            Some(JsonRejection::MissingJsonContentType(_))
+        ));
+
+        let body = hyper::body::to_bytes(req.take_body().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(body, json!({ "id": 123 }).to_string());
+    }
+
+    #[tokio::test]
+    async fn extract_with_config() {
+        let mut req = RequestParts::new(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(json!({ "id": "foo" }).to_string()))
+                .unwrap(),
+        );
+        req.extensions_mut().insert(
+            JsonConfig::<Body>::new()
+                .override_status(StatusCode::BAD_REQUEST)
+                .into_config(),
+        );
+
+        let result = req
+            .extract_with_config::<Json<Payload>>(
+                JsonConfig::new().rejection_handler(|_, _| StatusCode::IM_A_TEAPOT),
+            )
+            .await;
+        let rejection = result.err().unwrap();
+        assert_eq!(rejection.into_response().status(), StatusCode::IM_A_TEAPOT);
+
+        // the config added to the request is kept
+        assert!(req
+            .extensions()
+            .get::<Config<JsonConfig<Body>, Body>>()
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn valid() {
+        #[derive(Deserialize)]
+        struct Signup {
+            username: String,
+        }
+
+        impl Validate for Signup {
+            fn validate(&self) -> Result<(), ValidationErrors> {
+                let mut errors = ValidationErrors::new();
+                if self.username.len() < 3 {
+                    errors.add("username", "too short");
+                }
+                errors.into_result()
+            }
+        }
+
+        async fn handler(Valid(Json(_)): Valid<Json<Signup>>) {}
+
+        let app = Router::new().route("/", post(handler));
+
+        let request = |username: &str| {
+            Request::builder()
+                .method(Method::POST)
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(json!({ "username": username }).to_string()))
+                .unwrap()
+        };
+
+        let res = app.clone().oneshot(request("bob")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = app.clone().oneshot(request("b")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        let body = serde_json::from_slice::<Value>(&body).unwrap();
+        assert_eq!(body, json!({ "username": ["too short"] }));
+
+        let res = app
+            .layer(
+                ValidationConfig::new().rejection_handler(|errors: ValidationErrors, _| {
+                    (StatusCode::BAD_REQUEST, errors.to_string())
+                }),
+            )
+            .oneshot(request("b"))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(body, "Validation failed: username: too short");
+    }
+
+    #[cfg(feature = "validator")]
+    #[tokio::test]
+    async fn valid_validator() {
+        // the derived code calls `validate` as a method, which is ambiguous with this crate's
+        // `Validate` in scope
+        mod types {
+            use serde::Deserialize;
+            use validator::Validate;
+
+            #[derive(Deserialize, Validate)]
+            pub(super) struct Signup {
+                #[validate(length(min = 3, message = "too short"))]
+                username: String,
+                #[validate]
+                address: Address,
+                #[validate]
+                tags: Vec<Tag>,
+            }
+
+            #[derive(Deserialize, Validate)]
+            struct Address {
+                #[validate(length(min = 1))]
+                city: String,
+            }
+
+            #[derive(Deserialize, Validate)]
+            struct Tag {
+                #[validate(length(max = 3))]
+                name: String,
+            }
+        }
+        use types::Signup;
+
+        async fn handler(Valid(Json(_)): Valid<Json<Signup>>) {}
+
+        let app = Router::new().route("/", post(handler));
+
+        let request = |body: Value| {
+            Request::builder()
+                .method(Method::POST)
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+
+        let res = app
+            .clone()
+            .oneshot(request(json!({
+                "username": "bob",
+                "address": { "city": "Oslo" },
+                "tags": [{ "name": "a" }],
+            })))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = app
+            .oneshot(request(json!({
+                "username": "b",
+                "address": { "city": "" },
+                "tags": [{ "name": "a" }, { "name": "long" }],
+            })))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        let body = serde_json::from_slice::<Value>(&body).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "username": ["too short"],
+                "address.city": ["length"],
+                "tags[1].name": ["length"],
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn post_deserialize() {
+        #[derive(Deserialize)]
+        struct Signup {
+            email: String,
+        }
+
+        async fn handler(Json(signup): Json<Signup>) -> String {
+            signup.email
+        }
+
+        let app =
+            Router::new()
+                .route("/", post(handler))
+                .layer(
+                    JsonConfig::new().post_deserialize(|signup: &mut Signup, _req| {
+                        signup.email = signup.email.trim().to_lowercase();
+                        if signup.email.contains('@') {
+                            Ok(())
+                        } else {
+                            Err(StatusCode::IM_A_TEAPOT)
+                        }
+                    }),
+                );
+
+        let request = |email: &str| {
+            Request::builder()
+                .method(Method::POST)
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(json!({ "email": email }).to_string()))
+                .unwrap()
+        };
+
+        let res = app
+            .clone()
+            .oneshot(request(" Bob@Example.com "))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(body, "bob@example.com");
+
+        let res = app.oneshot(request("bob")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::IM_A_TEAPOT);
+    }
+
+    #[tokio::test]
+    async fn transform_body() {
+        async fn handler(Json(payload): Json<Payload>) -> String {
+            payload.id.to_string()
+        }
+
+        // unwrap the payload from a `{"data": ...}` envelope
+        let app = Router::new()
+            .route("/", post(handler))
+            .layer(JsonConfig::new().transform_body(|bytes| {
+                let mut envelope =
+                    serde_json::from_slice::<Value>(&bytes).map_err(|_| StatusCode::IM_A_TEAPOT)?;
+                match envelope.get_mut("data") {
+                    Some(data) => Ok(Bytes::from(data.take().to_string())),
+                    None => Err(StatusCode::IM_A_TEAPOT),
+                }
+            }));
+
+        let request = |body: Value| {
+            Request::builder()
+                .method(Method::POST)
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+
+        let res = app
+            .clone()
+            .oneshot(request(json!({ "data": { "id": 1 } })))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(body, "1");
+
+        let res = app.oneshot(request(json!({ "id": 1 }))).await.unwrap();
+        assert_eq!(res.status(), StatusCode::IM_A_TEAPOT);
+    }
+
+    #[cfg(feature = "schemars")]
+    #[tokio::test]
+    async fn json_schema() {
+        #[derive(Deserialize, schemars::JsonSchema)]
+        struct Order {
+            #[allow(dead_code)]
+            items: Vec<Item>,
+        }
+
+        #[derive(Deserialize, schemars::JsonSchema)]
+        struct Item {
+            #[allow(dead_code)]
+            quantity: u32,
+        }
+
+        async fn handler(Json(_): Json<Order>) {}
+
+        let app = Router::new().route("/", post(handler)).layer(
+            JsonConfig::new()
+                .schema::<Order>()
+                .rejection_handler(presets::simple_json()),
+        );
+
+        let res = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/")
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        json!({ "items": [{ "quantity": "one" }] }).to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        let body = serde_json::from_slice::<Value>(&body).unwrap();
+        assert_eq!(body["pointer"], "/items/0/quantity");
+        assert_eq!(body["schema"]["type"], "integer");
+    }
+
+    #[tokio::test]
+    async fn all_errors() {
+        #[derive(Deserialize)]
+        #[allow(dead_code)]
+        struct Signup {
+            name: String,
+            age: u32,
+            address: Address,
+            tags: Vec<String>,
+        }
+
+        #[derive(Deserialize)]
+        #[allow(dead_code)]
+        struct Address {
+            city: String,
+            zip: u32,
+        }
+
+        fn rejection_handler<B>(_: JsonRejection, ctx: RejectionContext<'_, B>) -> Json<Value> {
+            let errors = ctx
+                .field_errors()
+                .unwrap()
+                .iter()
+                .map(|error| error.pointer().to_owned())
+                .collect::<Vec<_>>();
+            Json(json!(errors))
+        }
+
+        async fn handler(Json(_): Json<Signup>) {}
+
+        let app = Router::new().route("/", post(handler)).layer(
+            JsonConfig::new()
+                .all_errors(true)
+                .rejection_handler(rejection_handler),
+        );
+
+        let res = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/")
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        json!({
+                            "name": 1,
+                            "address": { "city": "Copenhagen", "zip": "foo" },
+                            "tags": ["a", 2],
+                        })
+                        .to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        let body = serde_json::from_slice::<Value>(&body).unwrap();
+        assert_eq!(body, json!(["/address/zip", "/name", "/tags/1", ""]));
+    }
+
+    #[tokio::test]
+    async fn optional() {
+        async fn handler(Optional(payload): Optional<Json<Payload>>) -> String {
+            match payload {
+                Some(Json(payload)) => payload.id.to_string(),
+                None => "none".to_owned(),
+            }
+        }
+
+        let app = Router::new().route("/", post(handler));
+
+        for (body, status, expected) in [
+            ("", StatusCode::OK, "none"),
+            (r#"{"id":1}"#, StatusCode::OK, "1"),
+            (r#"{"id":"#, StatusCode::BAD_REQUEST, ""),
+        ] {
+            let res = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .method(Method::POST)
+                        .uri("/")
+                        .header("content-type", "application/json")
+                        .body(Body::from(body))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(res.status(), status);
+            if status == StatusCode::OK {
+                let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+                assert_eq!(body, expected);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn json_or_default() {
+        #[derive(Deserialize, Default)]
+        struct Options {
+            #[serde(default)]
+            hard: bool,
+        }
+
+        async fn handler(JsonOrDefault(options): JsonOrDefault<Options>) -> String {
+            options.hard.to_string()
+        }
+
+        let app = Router::new().route("/", post(handler));
+
+        for (body, status, expected) in [
+            ("", StatusCode::OK, "false"),
+            (r#"{"hard":true}"#, StatusCode::OK, "true"),
+            (r#"{"hard":1}"#, StatusCode::UNPROCESSABLE_ENTITY, ""),
+        ] {
+            let res = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .method(Method::POST)
+                        .uri("/")
+                        .header("content-type", "application/json")
+                        .body(Body::from(body))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(res.status(), status);
+            if status == StatusCode::OK {
+                let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+                assert_eq!(body, expected);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn first_of() {
+        async fn handler(
+            FirstOf(payload): FirstOf<Either<Json<Payload>, Form<Payload>>>,
+        ) -> String {
+            match payload {
+                Either::E1(Json(payload)) => format!("json {}", payload.id),
+                Either::E2(Form(payload)) => format!("form {}", payload.id),
+            }
+        }
+
+        let app = Router::new().route("/", post(handler));
+
+        let request = |content_type: &str, body: &'static str| {
+            Request::builder()
+                .method(Method::POST)
+                .uri("/")
+                .header("content-type", content_type)
+                .body(Body::from(body))
+                .unwrap()
+        };
+
+        let res = app
+            .clone()
+            .oneshot(request("application/json", r#"{"id":1}"#))
+            .await
+            .unwrap();
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(body, "json 1");
+
+        let res = app
+            .clone()
+            .oneshot(request("application/x-www-form-urlencoded", "id=2"))
+            .await
+            .unwrap();
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(body, "form 2");
+
+        // the rejection of the last extractor
+        let res = app
+            .clone()
+            .oneshot(request("text/plain", "id=3"))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let res = app
+            .layer(FirstOfConfig::new().all_errors(true))
+            .oneshot(request("text/plain", "id=3"))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        let body = serde_json::from_slice::<Value>(&body).unwrap();
+        assert_eq!(body["errors"][0]["extractor"], "Json");
+        assert_eq!(body["errors"][1]["extractor"], "Form");
+    }
+
+    #[tokio::test]
+    async fn query_and_json() {
+        #[derive(Deserialize)]
+        struct Pagination {
+            page: u32,
+        }
+
+        async fn handler(
+            QueryAndJson(pagination, payload): QueryAndJson<Pagination, Payload>,
+        ) -> String {
+            format!("{} {}", pagination.page, payload.id)
+        }
+
+        let app = Router::new()
+            .route("/", post(handler))
+            .layer(QueryConfig::new().rejection_handler(|_, _| StatusCode::IM_A_TEAPOT));
+
+        let request = |uri: &str| {
+            Request::builder()
+                .method(Method::POST)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{"id":1}"#))
+                .unwrap()
+        };
+
+        let res = app.clone().oneshot(request("/?page=2")).await.unwrap();
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(body, "2 1");
+
+        let res = app.oneshot(request("/?page=foo")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::IM_A_TEAPOT);
+    }
+
+    #[tokio::test]
+    async fn request_id_header() {
+        let app = Router::new()
+            .route("/", post(|_: Json<Payload>| async {}))
+            .layer(JsonConfig::new().rejection_handler(presets::simple_json()))
+            .layer(
+                GlobalRejectionConfig::new()
+                    .request_id_header(HeaderName::from_static("x-request-id")),
+            );
+
+        let res = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/")
+                    .header("content-type", "application/json")
+                    .header("x-request-id", "abc-123")
+                    .body(Body::from("{}"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.headers()["x-request-id"], "abc-123");
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        let body = serde_json::from_slice::<Value>(&body).unwrap();
+        assert_eq!(body["request_id"], "abc-123");
+    }
+
+    #[tokio::test]
+    async fn sample_observers() {
+        use axum::routing::get;
+
+        let observed = Arc::new(AtomicU64::new(0));
+        let app = Router::new()
+            .route("/", get(|_: Query<Payload>| async {}))
+            .layer(
+                QueryConfig::new()
+                    .on_rejection({
+                        let observed = observed.clone();
+                        move |_, _| {
+                            observed.fetch_add(1, Ordering::Relaxed);
+                        }
+                    })
+                    .sample_observers(0.25)
+                    .rejection_handler(|_, _| StatusCode::IM_A_TEAPOT),
+            );
+
+        for _ in 0..8 {
+            let res = app
+                .clone()
+                .oneshot(Request::builder().uri("/").body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            // the rejection handler still runs for every rejection
+            assert_eq!(res.status(), StatusCode::IM_A_TEAPOT);
+        }
+        assert_eq!(observed.load(Ordering::Relaxed), 2);
+    }
+
+    #[tokio::test]
+    async fn borrowed_json() {
+        #[derive(Deserialize)]
+        struct Event<'a> {
+            kind: &'a str,
+        }
+
+        async fn handler(body: BorrowedJson) -> Response {
+            match body.deserialize::<Event<'_>>() {
+                Ok(event) => event.kind.to_owned().into_response(),
+                Err(err) => {
+                    assert_eq!(err.pointer(), "/kind");
+                    err.into_response()
+                }
+            }
+        }
+
+        let app = Router::new()
+            .route("/", post(handler))
+            .layer(JsonConfig::new().on_missing_content_type(|_, _| StatusCode::IM_A_TEAPOT));
+
+        let request = |content_type: &str, body: &'static str| {
+            Request::builder()
+                .method(Method::POST)
+                .uri("/")
+                .header("content-type", content_type)
+                .body(Body::from(body))
+                .unwrap()
+        };
+
+        let res = app
+            .clone()
+            .oneshot(request("application/json", r#"{"kind":"click"}"#))
+            .await
+            .unwrap();
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(body, "click");
+
+        let res = app
+            .clone()
+            .oneshot(request("application/json", r#"{"kind":1}"#))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let res = app
+            .oneshot(request("text/plain", r#"{"kind":"click"}"#))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::IM_A_TEAPOT);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[tokio::test]
+    async fn decompress() {
+        use std::io::Write;
+
+        let gzip = |body: &[u8]| {
+            let mut encoder =
+                flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(body).unwrap();
+            encoder.finish().unwrap()
+        };
+
+        let app = app().layer(
+            JsonConfig::new()
+                .decompress(Decompression::new().max_size(64))
+                .rejection_handler(|_, ctx| ctx.kind().as_str()),
+        );
+
+        let request = |encoding: &str, body: Vec<u8>| {
+            Request::builder()
+                .method(Method::POST)
+                .uri("/")
+                .header("content-type", "application/json")
+                .header("content-encoding", encoding)
+                .body(Body::from(body))
+                .unwrap()
+        };
+
+        let res = app
+            .clone()
+            .oneshot(request("gzip", gzip(br#"{"id":1}"#)))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = app
+            .clone()
+            .oneshot(request("identity", br#"{"id":1}"#.to_vec()))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        for (encoding, body, kind) in [
+            ("gzip", br#"{"id":1}"#.to_vec(), "body-decode-error"),
+            ("compress", br#"{"id":1}"#.to_vec(), "body-decode-error"),
+            ("gzip", gzip(&[b' '; 1024]), "body-too-large"),
+        ] {
+            let res = app.clone().oneshot(request(encoding, body)).await.unwrap();
+            let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+            assert_eq!(body, kind);
+        }
+    }
+
+    #[cfg(feature = "hmac")]
+    #[tokio::test]
+    async fn verified() {
+        use axum::http::HeaderName;
+        use hmac::Mac;
+
+        async fn handler(Verified(Json(payload)): Verified<Json<Payload>>) -> String {
+            payload.id.to_string()
+        }
+
+        let app = Router::new().route("/", post(handler)).layer(
+            VerifyConfig::new()
+                .header(HeaderName::from_static("x-hub-signature-256"))
+                .prefix("sha256=")
+                .key("secret"),
+        );
+
+        let sign = |body: &str| {
+            let mut mac = hmac::Hmac::<sha2::Sha256>::new_from_slice(b"secret").unwrap();
+            mac.update(body.as_bytes());
+            let signature = mac.finalize().into_bytes();
+            let hex = signature.iter().map(|byte| format!("{:02x}", byte));
+            format!("sha256={}", hex.collect::<String>())
+        };
+
+        let request = |body: &'static str, signature: Option<String>| {
+            let mut req = Request::builder()
+                .method(Method::POST)
+                .uri("/")
+                .header("content-type", "application/json");
+            if let Some(signature) = signature {
+                req = req.header("x-hub-signature-256", signature);
+            }
+            req.body(Body::from(body)).unwrap()
+        };
+
+        let res = app
+            .clone()
+            .oneshot(request(r#"{"id":1}"#, Some(sign(r#"{"id":1}"#))))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(body, "1");
+
+        for signature in [
+            None,
+            Some(sign(r#"{"id":2}"#)),
+            Some("sha256=zz".to_owned()),
+        ] {
+            let res = app
+                .clone()
+                .oneshot(request(r#"{"id":1}"#, signature))
+                .await
+                .unwrap();
+            assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        }
+
+        // the body is checked before it's deserialized
+        let res = app
+            .oneshot(request(r#"{"id":"foo"}"#, Some(sign(r#"{"id":"foo"}"#))))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn utf8_policy() {
+        #[derive(Deserialize)]
+        struct Named {
+            name: String,
+        }
+
+        async fn json_handler(Json(named): Json<Named>) -> String {
+            named.name
+        }
+
+        async fn form_handler(Form(named): Form<Named>) -> String {
+            named.name
+        }
+
+        let app = |policy| {
+            Router::new()
+                .route("/json", post(json_handler))
+                .route("/form", post(form_handler))
+                .layer(JsonConfig::new().utf8_policy(policy))
+                .layer(FormConfig::new().utf8_policy(policy))
+                .layer(GlobalRejectionConfig::new().rejection_handler(|_, ctx| {
+                    (StatusCode::BAD_REQUEST, ctx.kind().as_str()).into_response()
+                }))
+        };
+
+        let request = |uri: &str, content_type: &str, body: &'static [u8]| {
+            Request::builder()
+                .method(Method::POST)
+                .uri(uri)
+                .header("content-type", content_type)
+                .body(Body::from(body))
+                .unwrap()
+        };
+        let json = || request("/json", "application/json", b"{\"name\":\"a\xFFb\"}");
+        let form = || request("/form", "application/x-www-form-urlencoded", b"name=a%FFb");
+
+        for req in [json(), form()] {
+            let res = app(Utf8Policy::Reject).oneshot(req).await.unwrap();
+            assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+            let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+            assert_eq!(body, "invalid-utf8");
+        }
+
+        for req in [json(), form()] {
+            let res = app(Utf8Policy::Lossy).oneshot(req).await.unwrap();
+            assert_eq!(res.status(), StatusCode::OK);
+            let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+            assert_eq!(body, "a\u{FFFD}b");
+        }
+    }
+
+    #[cfg(feature = "yaml")]
+    #[tokio::test]
+    async fn negotiate_response() {
+        use axum::routing::get;
+
+        async fn handler() -> Negotiate<Value> {
+            Negotiate(json!({ "id": 1 }))
+        }
+
+        let app = Router::new()
+            .route("/", get(handler))
+            .layer(NegotiateConfig::new());
+
+        let request = |accept: Option<&str>| {
+            let mut req = Request::builder().uri("/");
+            if let Some(accept) = accept {
+                req = req.header("accept", accept);
+            }
+            req.body(Body::empty()).unwrap()
+        };
+
+        for (accept, content_type, body) in [
+            (None, "application/json", r#"{"id":1}"#),
+            (Some("*/*"), "application/json", r#"{"id":1}"#),
+            (Some("text/html"), "application/json", r#"{"id":1}"#),
+            (Some("application/yaml"), "application/yaml", "id: 1\n"),
+            (
+                Some("application/json;q=0.5, text/yaml"),
+                "application/yaml",
+                "id: 1\n",
+            ),
+        ] {
+            let res = app.clone().oneshot(request(accept)).await.unwrap();
+            assert_eq!(res.status(), StatusCode::OK);
+            assert_eq!(res.headers()["content-type"], content_type);
+            assert_eq!(res.headers()["vary"], "accept");
+            let bytes = hyper::body::to_bytes(res.into_body()).await.unwrap();
+            assert_eq!(bytes, body);
+        }
+    }
+
+    #[tokio::test]
+    async fn json_stream() {
+        use axum::routing::get;
+        use futures_util::stream;
+
+        #[derive(Serialize)]
+        struct Item {
+            id: u32,
+        }
+
+        let app = Router::new()
+            .route(
+                "/array",
+                get(|| async { JsonStream::new(stream::iter((1..=3).map(|id| Item { id }))) }),
+            )
+            .route(
+                "/empty",
+                get(|| async { JsonStream::new(stream::empty::<Item>()) }),
+            )
+            .route(
+                "/ndjson",
+                get(|| async {
+                    JsonStream::new(stream::iter((1..=2).map(|id| Item { id }))).ndjson()
+                }),
+            );
+
+        for (uri, content_type, body) in [
+            (
+                "/array",
+                "application/json",
+                r#"[{"id":1},{"id":2},{"id":3}]"#,
+            ),
+            ("/empty", "application/json", "[]"),
+            (
+                "/ndjson",
+                "application/x-ndjson",
+                "{\"id\":1}\n{\"id\":2}\n",
+            ),
+        ] {
+            let res = app
+                .clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(res.status(), StatusCode::OK);
+            assert_eq!(res.headers()["content-type"], content_type);
+            let bytes = hyper::body::to_bytes(res.into_body()).await.unwrap();
+            assert_eq!(bytes, body);
+        }
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn test_request() {
+        use testing::TestRequest;
+
+        let Json(payload) = TestRequest::post("/")
+            .json(&json!({ "id": 1 }))
+            .extract::<Json<Value>>()
+            .await
+            .unwrap();
+        assert_eq!(payload, json!({ "id": 1 }));
+
+        let rejection = TestRequest::post("/")
+            .json(&json!({ "id": "foo" }))
+            .layer(
+                JsonConfig::new().rejection_handler(|_, _: RejectionContext<'_, _>| {
+                    (
+                        StatusCode::BAD_REQUEST,
+                        axum::Json(json!({ "error": "custom" })),
+                    )
+                }),
+            )
+            .extract::<Json<Payload>>()
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(rejection.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(rejection.json::<Value>(), json!({ "error": "custom" }));
+
+        let rejection = TestRequest::get("/?id=foo")
+            .layer(QueryConfig::new().override_status(StatusCode::BAD_REQUEST))
+            .extract::<Query<Payload>>()
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(rejection.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn testing_rejections() {
+        use axum::extract::rejection::JsonRejection;
+
+        assert!(matches!(
+            testing::json_missing_content_type(),
+            JsonRejection::MissingJsonContentType(_)
+        ));
+        assert!(matches!(
+            testing::json_syntax_error(),
+            JsonRejection::JsonSyntaxError(_)
+        ));
+        assert!(matches!(
+            testing::json_data_error(),
+            JsonRejection::JsonDataError(_)
+        ));
+        assert!(matches!(
+            testing::query_deserialize_error(),
+            rejection::QueryRejection::FailedToDeserializeQueryString(_)
+        ));
+        assert!(matches!(
+            testing::form_missing_content_type(),
+            rejection::FormRejection::InvalidFormContentType(_)
+        ));
+        assert!(matches!(
+            testing::form_deserialize_error(),
+            rejection::FormRejection::FailedToDeserializeQueryString(_)
+        ));
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn assert_rejection() {
+        let app = app().layer(JsonConfig::new().rejection_handler(presets::problem_details()));
+
+        let res = testing::TestRequest::post("/")
+            .json(&json!({ "id": "foo" }))
+            .send(app)
+            .await;
+        crate::assert_rejection!(res, StatusCode::UNPROCESSABLE_ENTITY);
+        crate::assert_rejection!(
+            res,
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "application/problem+json",
+            { "status": 422, "pointer": "/id" },
+        );
+
+        let missing_field = std::panic::catch_unwind(|| {
+            res.assert_json_contains(&json!({ "status": 422, "missing": true }));
+        });
+        assert!(missing_field.is_err());
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn response_snapshot() {
+        let res = testing::TestRequest::post("/")
+            .json(&json!({ "id": "foo" }))
+            .send(app().layer(JsonConfig::new().rejection_handler(presets::problem_details())))
+            .await;
+
+        let snapshot = res.snapshot();
+        assert_eq!(snapshot.status(), 422);
+        assert_eq!(
+            snapshot.headers().keys().collect::<Vec<_>>(),
+            ["content-length", "content-type"]
+        );
+        assert_eq!(snapshot.body()["pointer"], "/id");
+        assert_eq!(snapshot, res.snapshot());
+    }
+
+    #[tokio::test]
+    async fn sort_by_and_filters() {
+        use axum::routing::get;
+
+        async fn handler(SortBy(sorts): SortBy, Filters(filters): Filters) -> String {
+            let mut filters = filters.into_iter().collect::<Vec<_>>();
+            filters.sort();
+            format!(
+                "{:?} {:?}",
+                sorts
+                    .iter()
+                    .map(|sort| (sort.field.as_str(), sort.direction))
+                    .collect::<Vec<_>>(),
+                filters
+            )
+        }
+
+        let app = Router::new()
+            .route("/", get(handler))
+            .layer(SortConfig::new().fields(["created_at", "name"]))
+            .layer(FilterConfig::new().fields(["status"]));
+
+        let res = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/?sort=-created_at,name&filter[status]=open&page=2")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(
+            body,
+            r#"[("created_at", Desc), ("name", Asc)] [("status", "open")]"#
+        );
+
+        for (uri, message) in [
+            ("/?sort=password", "Cannot sort by `password`"),
+            ("/?sort=name,", "Invalid sort parameter"),
+            ("/?filter[owner]=me", "Cannot filter by `owner`"),
+        ] {
+            let res = app
+                .clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+            let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+            assert_eq!(body, message);
+        }
+    }
+
+    #[tokio::test]
+    async fn audit() {
+        let records = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let audit = Audit::new({
+            let records = records.clone();
+            move |record| {
+                records.lock().unwrap().push((
+                    record.route().map(ToOwned::to_owned),
+                    record.info().kind(),
+                    record.excerpt().map(ToOwned::to_owned),
+                    record.is_truncated(),
+                ));
+            }
+        })
+        .redact(["password"])
+        .max_excerpt_len(30);
+
+        let app = Router::new()
+            .route("/login", post(|_: Json<Payload>| async {}))
+            .layer(JsonConfig::new().audit(audit));
+
+        let res = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/login")
+                    .header("content-type", "application/json")
+                    .body(Body::from(r#"{"password":"hunter2","user":"bob"}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let records = records.lock().unwrap();
+        assert_eq!(
+            *records,
+            [(
+                Some("/login".to_owned()),
+                rejection::RejectionKind::DeserializeError,
+                Some(r#"{"password":"[REDACTED]","user"#.to_owned()),
+                true,
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn lazy() {
+        async fn handler(payload: Lazy<Json<Payload>>) -> Response {
+            match payload.get().await {
+                Ok(Json(payload)) => payload.id.to_string().into_response(),
+                Err(rejection) => rejection.into_response(),
+            }
+        }
+
+        let app = Router::new()
+            .route("/", post(handler))
+            .layer(JsonConfig::new().rejection_handler(|_, _| StatusCode::IM_A_TEAPOT));
+
+        let request = |body: &'static str| {
+            Request::builder()
+                .method(Method::POST)
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .unwrap()
+        };
+
+        let res = app.clone().oneshot(request(r#"{"id":1}"#)).await.unwrap();
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(body, "1");
+
+        // the config is still used when the extractor runs
+        let res = app.oneshot(request("{")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::IM_A_TEAPOT);
+    }
+
+    #[tokio::test]
+    async fn cached() {
+        #[derive(Clone, Deserialize)]
+        struct Payload {
+            id: u32,
+        }
+
+        let req = Request::builder()
+            .method(Method::POST)
+            .uri("/")
+            .header("content-type", "application/json")
+            .body(Body::from(r#"{"id":1}"#))
+            .unwrap();
+        let mut req = RequestParts::new(req);
+
+        let Cached(Json(first)) = req.extract::<Cached<Json<Payload>>>().await.unwrap();
+        // the body has been read, so this would fail without the cache
+        let Cached(Json(second)) = req.extract::<Cached<Json<Payload>>>().await.unwrap();
+        assert_eq!(first.id, second.id);
+    }
+
+    #[test]
+    fn serde_passthrough() {
+        assert_eq!(
+            serde_json::to_value(Query(vec![1, 2])).unwrap(),
+            json!([1, 2])
+        );
+
+        let form =
+            serde_json::from_value::<Form<HashMap<String, u32>>>(json!({ "id": 1 })).unwrap();
+        assert_eq!(form, Form(HashMap::from([("id".to_owned(), 1)])));
+    }
+
+    #[test]
+    fn from_axum() {
+        let Json(payload) = Json::<Payload>::from(axum::Json(Payload { id: 1 }));
+        let axum::Json(payload) = axum::Json::<Payload>::from(Json(payload));
+        assert_eq!(payload.id, 1);
+    }
+
+    #[test]
+    fn map() {
+        let json = Json(Payload { id: 1 }).map(|payload| payload.id);
+        assert_eq!(json, Json(1));
+        assert_eq!(json.try_map(u8::try_from), Ok(Json(1)));
+        assert!(Json(-1).try_map(u8::try_from).is_err());
+    }
+
+    #[test]
+    fn deref_and_into_inner() {
+        let mut payload = Json::from(Payload { id: 1 });
+        payload.id += 1;
+        assert_eq!(payload.id, 2);
+        assert_eq!(payload.into_inner().id, 2);
+    }
+
+    #[tokio::test]
+    async fn valid_with_context() {
+        #[derive(Deserialize)]
+        struct Comment {
+            text: String,
+        }
+
+        impl ValidateWithContext for Comment {
+            type Context = usize;
+
+            fn validate_with(&self, max_length: &usize) -> Result<(), ValidationErrors> {
+                let mut errors = ValidationErrors::new();
+                if self.text.len() > *max_length {
+                    errors.add("text", "too long");
+                }
+                errors.into_result()
+            }
+        }
+
+        async fn handler(ValidWithContext(Json(_)): ValidWithContext<Json<Comment>>) {}
+
+        let app = Router::new().route("/", post(handler));
+
+        let request = |text: &str| {
+            Request::builder()
+                .method(Method::POST)
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(json!({ "text": text }).to_string()))
+                .unwrap()
+        };
+
+        let with_context = app.clone().layer(axum::Extension(3_usize));
+
+        let res = with_context.clone().oneshot(request("hi")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = with_context.oneshot(request("hello")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let res = app.oneshot(request("hi")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[cfg(feature = "garde")]
+    #[tokio::test]
+    async fn garde() {
+        #[derive(Clone)]
+        struct MaxLength(usize);
+
+        #[derive(Deserialize, garde::Validate)]
+        #[garde(context(MaxLength))]
+        struct Comment {
+            #[garde(custom(max_length))]
+            text: String,
+            #[garde(dive)]
+            tags: Vec<Tag>,
+        }
+
+        #[derive(Deserialize, garde::Validate)]
+        #[garde(context(MaxLength))]
+        struct Tag {
+            #[garde(length(min = 1))]
+            name: String,
+        }
+
+        fn max_length(text: &str, max: &MaxLength) -> garde::Result {
+            if text.len() > max.0 {
+                return Err(garde::Error::new("too long"));
+            }
+            Ok(())
+        }
+
+        async fn handler(Garde(Json(_)): Garde<Json<Comment>>) {}
+
+        let app = Router::new().route("/", post(handler));
+
+        let request = |body: Value| {
+            Request::builder()
+                .method(Method::POST)
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+
+        let with_context = app.clone().layer(axum::Extension(MaxLength(3)));
+
+        let res = with_context
+            .clone()
+            .oneshot(request(json!({ "text": "hi", "tags": [{ "name": "a" }] })))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = with_context
+            .clone()
+            .oneshot(request(
+                json!({ "text": "hello", "tags": [{ "name": "a" }, { "name": "" }] }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        let body = serde_json::from_slice::<Value>(&body).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "text": ["too long"],
+                "tags[1].name": ["length is lower than 1"],
+            })
+        );
+
+        let res = with_context
+            .layer(ValidationConfig::new().rejection_handler(
+                |errors: ValidationErrors, _: RejectionContext<'_, _>| {
+                    (StatusCode::BAD_REQUEST, errors.to_string())
+                },
+            ))
+            .oneshot(request(json!({ "text": "hello", "tags": [] })))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(&body[..], b"Validation failed: text: too long");
+
+        let res = app
+            .oneshot(request(json!({ "text": "hi", "tags": [] })))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[cfg(feature = "garde")]
+    #[tokio::test]
+    async fn garde_without_context() {
+        use axum::routing::get;
+
+        #[derive(Deserialize, garde::Validate)]
+        struct Signup {
+            #[garde(length(min = 3))]
+            username: String,
+        }
+
+        async fn handler(Garde(Query(_)): Garde<Query<Signup>>) {}
+
+        let app = Router::new().route("/", get(handler));
+
+        let res = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/?username=bob")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = app
+            .oneshot(
+                Request::builder()
+                    .uri("/?username=b")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn config_around_any_service() {
+        let svc = tower::ServiceBuilder::new()
+            .layer(JsonConfig::<Body>::new().into_config())
+            .layer(JsonConfig::<Body>::new().into_config())
+            .service_fn(|_: Request<Body>| async {
+                Ok::<_, std::convert::Infallible>(axum::http::Response::new(String::from("ok")))
+            });
+
+        let res = svc.oneshot(Request::new(Body::empty())).await.unwrap();
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(res.body().contains("was already added"));
+    }
+
+    #[tokio::test]
+    async fn allow_override_nested_config() {
+        let outer = JsonConfig::new()
+            .rejection_handler(|_, _| (StatusCode::BAD_REQUEST, "outer").into_response())
+            .allow_override();
+        let inner = JsonConfig::new()
+            .rejection_handler(|_, _| (StatusCode::BAD_REQUEST, "inner").into_response());
+
+        let mut app = Router::new()
+            .merge(app())
+            .nest("/nested", app().layer(inner))
+            .layer(outer);
+
+        for (uri, expected) in [("/", "outer"), ("/nested", "inner")] {
+            let res = app
+                .call(
+                    Request::builder()
+                        .method(Method::POST)
+                        .uri(uri)
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+
+            assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+            let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+            let body = String::from_utf8(body.to_vec()).unwrap();
+            assert_eq!(body, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn combined_configs() {
+        async fn handler(Query(_): Query<Payload>, Json(_): Json<Payload>) {}
+
+        async fn first_of_handler(_: FirstOf<Either<Json<Payload>, Form<Payload>>>) {}
+
+        let configs = ExtractorConfigBuilder::new()
+            .json(
+                JsonConfig::new().rejection_handler(|_, _| StatusCode::BAD_REQUEST.into_response()),
+            )
+            .first_of(FirstOfConfig::new().rejection_handler(|_, _| StatusCode::FORBIDDEN))
+            .global(
+                GlobalRejectionConfig::new().rejection_handler(|rejection, _| {
+                    (StatusCode::IM_A_TEAPOT, rejection.to_string()).into_response()
+                }),
+            )
+            .build_layer();
+
+        let mut app = Router::new()
+            .route("/", post(handler))
+            .route("/first-of", post(first_of_handler))
+            .layer(configs);
+
+        // `QueryConfig` isn't set so the global rejection handler is used
+        let res = app
+            .call(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/?id=foo")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::IM_A_TEAPOT);
+
+        let res = app
+            .call(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/?id=1")
+                    .header("content-type", "application/json")
+                    .body(Body::from("{"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        let res = app
+            .call(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/first-of")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn router_config_ext() {
+        let mut app = app().json_rejection_handler(|_, _| StatusCode::IM_A_TEAPOT);
+
+        let res = app
+            .call(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::IM_A_TEAPOT);
+    }
+
+    #[tokio::test]
+    async fn configured_handler() {
+        async fn handler(Json(_): Json<Payload>) {}
+
+        let mut app = Router::new()
+            .route("/", post(handler))
+            .route(
+                "/configured",
+                post(configured(
+                    handler,
+                    JsonConfig::new().override_status(StatusCode::IM_A_TEAPOT),
+                )),
+            )
+            .layer(JsonConfig::new().override_status(StatusCode::BAD_REQUEST));
+
+        for (uri, status) in [
+            ("/", StatusCode::BAD_REQUEST),
+            ("/configured", StatusCode::IM_A_TEAPOT),
+        ] {
+            let res = app
+                .call(
+                    Request::builder()
+                        .method(Method::POST)
+                        .uri(uri)
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(res.status(), status);
+        }
+    }
+
+    #[tokio::test]
+    async fn rejection_policy() {
+        let policy: RejectionPolicy = serde_json::from_value(json!({
+            "override_status": 400,
+            "preset": "simple-json",
+            "max_body_size": 16,
+        }))
+        .unwrap();
+
+        let mut app = app().layer(JsonConfig::from_policy(&policy));
+
+        for (body, status) in [
+            ("{", StatusCode::BAD_REQUEST),
+            (
+                "{\"id\": 1, \"padding\": \"aaaaaaaaaa\"}",
+                StatusCode::PAYLOAD_TOO_LARGE,
+            ),
+        ] {
+            let res = app
+                .call(
+                    Request::builder()
+                        .method(Method::POST)
+                        .uri("/")
+                        .header("content-type", "application/json")
+                        .body(Body::from(body))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+
+            assert_eq!(res.status(), status);
+            let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+            let body = serde_json::from_slice::<Value>(&body).unwrap();
+            assert!(body["error"].is_string());
+        }
+
+        let err = serde_json::from_value::<RejectionPolicy>(json!({ "override_status": 1000 }));
+        assert!(err.is_err());
+        let err = serde_json::from_value::<RejectionPolicy>(json!({ "unknown": true }));
+        assert!(err.is_err());
+    }
+
+    #[tokio::test]
+    async fn for_method() {
+        let mut app = Router::new()
+            .route(
+                "/",
+                post(|Json(_): Json<Value>| async {}).put(|Json(_): Json<Value>| async {}),
+            )
+            .layer(
+                JsonConfig::new()
+                    .max_body_size(64)
+                    .for_method(Method::PUT, |config| config.max_body_size(8)),
+            );
+
+        for (method, status) in [
+            (Method::POST, StatusCode::OK),
+            (Method::PUT, StatusCode::PAYLOAD_TOO_LARGE),
+        ] {
+            let res = app
+                .call(
+                    Request::builder()
+                        .method(method)
+                        .uri("/")
+                        .header("content-type", "application/json")
+                        .body(Body::from(json!({ "id": 123 }).to_string()))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(res.status(), status);
+        }
+    }
+
+    #[tokio::test]
+    async fn extractor_config_trait() {
+        fn install<C>(router: Router<Body>, config: C) -> Router<Body>
+        where
+            C: ExtractorConfig<Body>,
+        {
+            router.layer(
+                config
+                    .override_status(StatusCode::BAD_REQUEST)
+                    .into_config(),
+            )
+        }
+
+        let mut app = install(app(), JsonConfig::new());
+
+        let res = app
+            .call(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/")
+                    .header("content-type", "application/json")
+                    .body(Body::from("{}"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn unchecked_duplicate_config() {
+        let mut app = app()
+            .layer(JsonConfig::new().into_config().unchecked())
+            .layer(JsonConfig::new().into_config().unchecked());
+
+        let res = app
+            .call(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/")
+                    .header("content-type", "application/json")
+                    .body(Body::from(json!({ "id": 123 }).to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        // the check still runs in debug builds
+        if cfg!(debug_assertions) {
+            assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        } else {
+            assert_eq!(res.status(), StatusCode::OK);
+        }
+    }
+
+    #[tokio::test]
+    async fn peek_in_middleware() {
+        async fn validate(
+            req: Request<Body>,
+            next: axum::middleware::Next<Body>,
+        ) -> Result<Response, Response> {
+            let mut parts = RequestParts::new(req);
+            parts.extract::<Peek<Json<Payload>>>().await?;
+            let req = parts
+                .try_into_request()
+                .map_err(IntoResponse::into_response)?;
+            Ok(next.run(req).await)
+        }
+
+        let buffered = app()
+            .layer(axum::middleware::from_fn(validate))
+            .layer(BufferBody::new())
+            .layer(JsonConfig::new().rejection_handler(|_, _| StatusCode::IM_A_TEAPOT));
+
+        for (body, status) in [
+            (json!({ "id": 123 }), StatusCode::OK),
+            (json!({ "id": "foo" }), StatusCode::IM_A_TEAPOT),
+        ] {
+            let res = buffered
+                .clone()
+                .call(
+                    Request::builder()
+                        .method(Method::POST)
+                        .uri("/")
+                        .header("content-type", "application/json")
+                        .body(Body::from(body.to_string()))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(res.status(), status);
+        }
+
+        // without the layer there is no buffered body to peek at
+        let res = app()
+            .layer(axum::middleware::from_fn(validate))
+            .call(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/")
+                    .header("content-type", "application/json")
+                    .body(Body::from(json!({ "id": 123 }).to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
     }
 }
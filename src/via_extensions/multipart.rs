@@ -0,0 +1,1299 @@
+use super::{
+    config,
+    custom::{reject_custom, CustomRejectionHandler},
+    de::StrValue,
+    rejection::ConfiguredRejection,
+    Config, RejectionContext,
+};
+use axum::{
+    async_trait,
+    body::{Bytes, HttpBody},
+    extract::{
+        multipart::{self as axum_multipart, MultipartRejection},
+        FromRequest, RequestParts,
+    },
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    BoxError,
+};
+use serde::de::{
+    self,
+    value::{MapDeserializer, SeqDeserializer},
+    DeserializeOwned, Deserializer, IntoDeserializer, Visitor,
+};
+use std::{
+    borrow::Cow,
+    cell::RefCell,
+    collections::HashMap,
+    fmt, io,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+use tokio::io::AsyncWriteExt;
+
+/// Config type for [`Multipart`] and [`TypedMultipart`].
+///
+/// Fields can be given their own limits with [`field`](Self::field). Fields without their own
+/// [`FieldConfig`] use the one set with [`max_field_size`](Self::max_field_size).
+pub struct MultipartConfig<B> {
+    fields: Fields,
+    rejection_handler: Option<CustomRejectionHandler<MultipartError, B>>,
+    override_status: Option<StatusCode>,
+    allow_override: bool,
+}
+
+// The part of the config used while reading fields, after the request has been extracted.
+#[derive(Debug, Clone, Default)]
+struct Fields {
+    fields: Arc<HashMap<String, FieldConfig>>,
+    default: FieldConfig,
+    temp_dir: Option<Arc<Path>>,
+}
+
+impl Fields {
+    fn get(&self, name: Option<&str>) -> &FieldConfig {
+        name.and_then(|name| self.fields.get(name))
+            .unwrap_or(&self.default)
+    }
+}
+
+impl<B> MultipartConfig<B> {
+    /// Create a new `MultipartConfig`.
+    pub fn new() -> Self {
+        Self {
+            fields: Fields::default(),
+            rejection_handler: None,
+            override_status: None,
+            allow_override: false,
+        }
+    }
+
+    /// Set the config of a field.
+    pub fn field(mut self, name: &str, config: FieldConfig) -> Self {
+        Arc::make_mut(&mut self.fields.fields).insert(name.to_owned(), config);
+        self
+    }
+
+    /// Set the maximum size of fields without their own [`FieldConfig`], in bytes.
+    ///
+    /// Defaults to no maximum.
+    pub fn max_field_size(mut self, max_size: usize) -> Self {
+        self.fields.default.max_size = Some(max_size);
+        self
+    }
+
+    /// Set the directory the fields configured with [`FieldConfig::temp_file`] are written to.
+    ///
+    /// Defaults to [`std::env::temp_dir`].
+    pub fn temp_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.fields.temp_dir = Some(dir.into().into());
+        self
+    }
+
+    /// Set the rejection handler function.
+    pub fn rejection_handler<F, R>(mut self, f: F) -> Self
+    where
+        F: Fn(MultipartError, RejectionContext<'_, B>) -> R + Send + Sync + 'static,
+        R: IntoResponse,
+    {
+        self.rejection_handler = Some(Arc::new(move |error, ctx| f(error, ctx).into_response()));
+        self
+    }
+
+    /// Set the status code of the default rejection responses.
+    pub fn override_status(mut self, status: StatusCode) -> Self {
+        self.override_status = Some(status);
+        self
+    }
+
+    /// Allow layers further in to override this config.
+    ///
+    /// See [`Config::allow_override`].
+    pub fn allow_override(mut self) -> Self {
+        self.allow_override = true;
+        self
+    }
+
+    fn reject<T>(
+        &self,
+        error: MultipartError,
+        extractor: &'static str,
+        req: &RequestParts<B>,
+    ) -> ConfiguredRejection<MultipartError> {
+        reject_custom::<T, _, _>(
+            extractor,
+            error,
+            self.rejection_handler.as_ref(),
+            self.override_status,
+            req,
+        )
+    }
+}
+
+impl<B> Clone for MultipartConfig<B> {
+    fn clone(&self) -> Self {
+        Self {
+            fields: self.fields.clone(),
+            rejection_handler: self.rejection_handler.clone(),
+            override_status: self.override_status,
+            allow_override: self.allow_override,
+        }
+    }
+}
+
+impl<B> Default for MultipartConfig<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B> fmt::Debug for MultipartConfig<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MultipartConfig")
+            .field("fields", &self.fields.fields)
+            .field("max_field_size", &self.fields.default.max_size)
+            .field("temp_dir", &self.fields.temp_dir)
+            .field("override_status", &self.override_status)
+            .field("allow_override", &self.allow_override)
+            .finish()
+    }
+}
+
+impl<S, B> tower_layer::Layer<S> for MultipartConfig<B> {
+    type Service = <Config<Self, B> as tower_layer::Layer<S>>::Service;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        let mut config: Config<_, B> = Config::new(self.clone());
+        if self.allow_override {
+            config = config.allow_override();
+        }
+        config.layer(inner)
+    }
+}
+
+/// The limits of a field, set with [`MultipartConfig::field`].
+#[derive(Debug, Clone, Default)]
+pub struct FieldConfig {
+    max_size: Option<usize>,
+    content_types: Option<Arc<[String]>>,
+    temp_file: bool,
+}
+
+impl FieldConfig {
+    /// Create a new `FieldConfig`, without any limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum size of the field, in bytes.
+    ///
+    /// Defaults to no maximum.
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    /// Set the content types the field can have, such as `image/png` or `image/*`.
+    ///
+    /// Fields without a `Content-Type` are `text/plain`.
+    ///
+    /// Defaults to any content type.
+    pub fn content_types<I, S>(mut self, content_types: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.content_types = Some(
+            content_types
+                .into_iter()
+                .map(|content_type| content_type.into().to_ascii_lowercase())
+                .collect(),
+        );
+        self
+    }
+
+    /// Write the field to a temporary file rather than keeping it in memory.
+    ///
+    /// [`TypedMultipart`] can then only deserialize it into a [`TempFile`].
+    pub fn temp_file(mut self) -> Self {
+        self.temp_file = true;
+        self
+    }
+
+    fn check_content_type(
+        &self,
+        field: &str,
+        content_type: Option<&str>,
+    ) -> Result<(), MultipartError> {
+        let allowed = match &self.content_types {
+            Some(allowed) => allowed,
+            None => return Ok(()),
+        };
+        let content_type = content_type.unwrap_or("text/plain");
+        let essence = content_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+        let matches = |allowed: &String| match allowed.strip_suffix("/*") {
+            Some("*") => true,
+            Some(ty) => essence
+                .split_once('/')
+                .is_some_and(|(essence, _)| essence == ty),
+            None => *allowed == essence,
+        };
+        if allowed.iter().any(matches) {
+            Ok(())
+        } else {
+            Err(MultipartError::UnsupportedContentType {
+                field: field.to_owned(),
+                content_type: content_type.to_owned(),
+            })
+        }
+    }
+}
+
+/// Extractor that wraps `axum::extract::Multipart` and supports runtime configuration.
+///
+/// Can be configured using [`MultipartConfig`]. The size and content type of each field are
+/// checked against its [`FieldConfig`] while it's read, so handlers can stream large uploads
+/// without buffering them first.
+///
+/// Requires the `multipart` feature.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{
+///     FieldConfig, Multipart, MultipartConfig, MultipartError,
+/// };
+/// use axum::{routing::post, Router};
+///
+/// async fn upload(mut multipart: Multipart) -> Result<(), MultipartError> {
+///     while let Some(field) = multipart.next_field().await? {
+///         let name = field.name().unwrap_or_default().to_owned();
+///         let bytes = field.bytes().await?;
+///         println!("`{}` is {} bytes", name, bytes.len());
+///     }
+///     Ok(())
+/// }
+///
+/// let app = Router::new().route("/upload", post(upload)).layer(
+///     MultipartConfig::new()
+///         .max_field_size(1024)
+///         .field(
+///             "avatar",
+///             FieldConfig::new()
+///                 .max_size(5 * 1024 * 1024)
+///                 .content_types(["image/png", "image/jpeg"]),
+///         ),
+/// );
+/// # let _: Router = app;
+/// ```
+#[derive(Debug)]
+pub struct Multipart {
+    inner: axum_multipart::Multipart,
+    fields: Fields,
+}
+
+#[async_trait]
+impl<B> FromRequest<B> for Multipart
+where
+    B: HttpBody<Data = Bytes> + Send + 'static,
+    B::Error: Into<BoxError>,
+{
+    type Rejection = ConfiguredRejection<MultipartError>;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let config = get_config(req);
+        match axum_multipart::Multipart::from_request(req).await {
+            Ok(inner) => Ok(Self {
+                inner,
+                fields: config.fields.clone(),
+            }),
+            Err(rejection) => {
+                Err(config.reject::<Self>(MultipartError::Rejection(rejection), "Multipart", req))
+            }
+        }
+    }
+}
+
+fn get_config<B>(req: &RequestParts<B>) -> Arc<MultipartConfig<B>>
+where
+    B: 'static,
+{
+    config::get_shared::<MultipartConfig<B>, B>(req.extensions()).unwrap_or_default()
+}
+
+impl Multipart {
+    /// Get the next field, if any.
+    ///
+    /// Fields whose content type isn't allowed by their [`FieldConfig`] are rejected here.
+    pub async fn next_field(&mut self) -> Result<Option<MultipartField<'_>>, MultipartError> {
+        let field = match self
+            .inner
+            .next_field()
+            .await
+            .map_err(MultipartError::Parse)?
+        {
+            Some(field) => field,
+            None => return Ok(None),
+        };
+
+        let config = self.fields.get(field.name());
+        config.check_content_type(field.name().unwrap_or_default(), field.content_type())?;
+        Ok(Some(MultipartField {
+            config: config.clone(),
+            temp_dir: self.fields.temp_dir.clone(),
+            inner: field,
+            size: 0,
+        }))
+    }
+}
+
+/// A field of a [`Multipart`] request.
+#[derive(Debug)]
+pub struct MultipartField<'a> {
+    inner: axum_multipart::Field<'a>,
+    config: FieldConfig,
+    temp_dir: Option<Arc<Path>>,
+    size: usize,
+}
+
+impl<'a> MultipartField<'a> {
+    /// The name of the field, from its `Content-Disposition` header.
+    pub fn name(&self) -> Option<&str> {
+        self.inner.name()
+    }
+
+    /// The file name of the field, from its `Content-Disposition` header.
+    pub fn file_name(&self) -> Option<&str> {
+        self.inner.file_name()
+    }
+
+    /// The content type of the field.
+    pub fn content_type(&self) -> Option<&str> {
+        self.inner.content_type()
+    }
+
+    /// The headers of the field.
+    pub fn headers(&self) -> &HeaderMap {
+        self.inner.headers()
+    }
+
+    /// Get the next chunk of the field's data, if any.
+    ///
+    /// Fails once the field is larger than its maximum size.
+    pub async fn chunk(&mut self) -> Result<Option<Bytes>, MultipartError> {
+        let chunk = match self.inner.chunk().await.map_err(MultipartError::Parse)? {
+            Some(chunk) => chunk,
+            None => return Ok(None),
+        };
+
+        self.size += chunk.len();
+        match self.config.max_size {
+            Some(max_size) if self.size > max_size => Err(MultipartError::FieldTooLarge {
+                field: self.name().unwrap_or_default().to_owned(),
+                max_size,
+            }),
+            _ => Ok(Some(chunk)),
+        }
+    }
+
+    /// Read the whole field into memory.
+    pub async fn bytes(mut self) -> Result<Bytes, MultipartError> {
+        let mut bytes = Vec::new();
+        while let Some(chunk) = self.chunk().await? {
+            bytes.extend_from_slice(&chunk);
+        }
+        Ok(Bytes::from(bytes))
+    }
+
+    /// Write the field to a temporary file, in the directory set with
+    /// [`MultipartConfig::temp_dir`].
+    pub async fn temp_file(mut self) -> Result<TempFile, MultipartError> {
+        let dir = match &self.temp_dir {
+            Some(dir) => dir.to_path_buf(),
+            None => std::env::temp_dir(),
+        };
+        let (path, mut file) = create_temp_file(&dir).await.map_err(MultipartError::Io)?;
+        // removes the file if writing fails
+        let mut temp_file = TempFile {
+            file_name: self.file_name().map(str::to_owned),
+            content_type: self.content_type().map(str::to_owned),
+            path,
+            size: 0,
+        };
+
+        while let Some(chunk) = self.chunk().await? {
+            file.write_all(&chunk).await.map_err(MultipartError::Io)?;
+        }
+        file.flush().await.map_err(MultipartError::Io)?;
+        temp_file.size = self.size;
+        Ok(temp_file)
+    }
+
+    async fn into_part(self) -> Result<Part, MultipartError> {
+        if self.config.temp_file {
+            return self.temp_file().await.map(Part::Disk);
+        }
+        let file_name = self.file_name().map(str::to_owned);
+        let content_type = self.content_type().map(str::to_owned);
+        Ok(Part::Memory(UploadedFile {
+            file_name,
+            content_type,
+            bytes: self.bytes().await?,
+        }))
+    }
+}
+
+async fn create_temp_file(dir: &Path) -> io::Result<(PathBuf, tokio::fs::File)> {
+    static COUNT: AtomicU64 = AtomicU64::new(0);
+
+    loop {
+        let path = dir.join(format!(
+            "axum-multipart-{}-{}",
+            std::process::id(),
+            COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+        match tokio::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .await
+        {
+            Ok(file) => return Ok((path, file)),
+            // left behind by an earlier process with the same ID
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {}
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Extractor that deserializes the fields of a `multipart/form-data` request into `T`.
+///
+/// Fields are matched to the struct's fields by name. Text fields are parsed like the values of
+/// [`Form`](super::Form), files can be deserialized into [`UploadedFile`], or [`TempFile`] if
+/// they're configured with [`FieldConfig::temp_file`], and fields that appear more than once
+/// into a `Vec`. The fields are checked against their [`FieldConfig`] while they're read.
+///
+/// Requests that fail to deserialize are rejected with `422 Unprocessable Entity`.
+///
+/// Requires the `multipart` feature.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{
+///     FieldConfig, MultipartConfig, TempFile, TypedMultipart, UploadedFile,
+/// };
+/// use axum::{routing::post, Router};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Upload {
+///     title: String,
+///     thumbnail: Option<UploadedFile>,
+///     video: TempFile,
+/// }
+///
+/// async fn upload(TypedMultipart(upload): TypedMultipart<Upload>) {
+///     println!("{} is at {}", upload.title, upload.video.path().display());
+/// }
+///
+/// let app = Router::new().route("/upload", post(upload)).layer(
+///     MultipartConfig::new()
+///         .max_field_size(1024)
+///         .field(
+///             "thumbnail",
+///             FieldConfig::new()
+///                 .max_size(1024 * 1024)
+///                 .content_types(["image/*"]),
+///         )
+///         .field(
+///             "video",
+///             FieldConfig::new().content_types(["video/mp4"]).temp_file(),
+///         ),
+/// );
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TypedMultipart<T>(pub T);
+
+#[async_trait]
+impl<T, B> FromRequest<B> for TypedMultipart<T>
+where
+    T: DeserializeOwned,
+    B: HttpBody<Data = Bytes> + Send + 'static,
+    B::Error: Into<BoxError>,
+{
+    type Rejection = ConfiguredRejection<MultipartError>;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let config = get_config(req);
+        let mut multipart = match axum_multipart::Multipart::from_request(req).await {
+            Ok(inner) => Multipart {
+                inner,
+                fields: config.fields.clone(),
+            },
+            Err(rejection) => {
+                return Err(config.reject::<T>(
+                    MultipartError::Rejection(rejection),
+                    "TypedMultipart",
+                    req,
+                ))
+            }
+        };
+
+        match read_parts(&mut multipart).await.and_then(deserialize) {
+            Ok(value) => Ok(Self(value)),
+            Err(error) => Err(config.reject::<T>(error, "TypedMultipart", req)),
+        }
+    }
+}
+
+async fn read_parts(multipart: &mut Multipart) -> Result<Vec<(String, Part)>, MultipartError> {
+    let mut parts = Vec::new();
+    while let Some(field) = multipart.next_field().await? {
+        let name = match field.name() {
+            Some(name) => name.to_owned(),
+            None => continue,
+        };
+        parts.push((name, field.into_part().await?));
+    }
+    Ok(parts)
+}
+
+/// A file uploaded in a [`TypedMultipart`] request, kept in memory.
+#[derive(Debug, Clone)]
+pub struct UploadedFile {
+    file_name: Option<String>,
+    content_type: Option<String>,
+    bytes: Bytes,
+}
+
+impl UploadedFile {
+    /// The file name of the field.
+    pub fn file_name(&self) -> Option<&str> {
+        self.file_name.as_deref()
+    }
+
+    /// The content type of the field.
+    pub fn content_type(&self) -> Option<&str> {
+        self.content_type.as_deref()
+    }
+
+    /// The contents of the file.
+    pub fn bytes(&self) -> &Bytes {
+        &self.bytes
+    }
+
+    /// Consume `self` and get the contents of the file.
+    pub fn into_bytes(self) -> Bytes {
+        self.bytes
+    }
+}
+
+/// A file uploaded in a [`TypedMultipart`] request, written to a temporary file.
+///
+/// Used for fields configured with [`FieldConfig::temp_file`]. The file is deleted when this is
+/// dropped, unless it's kept with [`persist`](Self::persist) or [`keep`](Self::keep).
+#[derive(Debug)]
+pub struct TempFile {
+    file_name: Option<String>,
+    content_type: Option<String>,
+    path: PathBuf,
+    size: usize,
+}
+
+impl TempFile {
+    /// The file name of the field.
+    pub fn file_name(&self) -> Option<&str> {
+        self.file_name.as_deref()
+    }
+
+    /// The content type of the field.
+    pub fn content_type(&self) -> Option<&str> {
+        self.content_type.as_deref()
+    }
+
+    /// The path of the temporary file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The size of the file, in bytes.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Move the file to `path`, so it's no longer deleted.
+    pub async fn persist(mut self, path: impl AsRef<Path>) -> io::Result<()> {
+        tokio::fs::rename(&self.path, path).await?;
+        self.path = PathBuf::new();
+        Ok(())
+    }
+
+    /// Consume `self` without deleting the file, and get its path.
+    pub fn keep(mut self) -> PathBuf {
+        std::mem::take(&mut self.path)
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if !self.path.as_os_str().is_empty() {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Why a `multipart/form-data` request was rejected.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum MultipartError {
+    /// The request isn't a `multipart/form-data` request, or its body was already extracted.
+    Rejection(MultipartRejection),
+    /// The body couldn't be parsed.
+    Parse(axum_multipart::MultipartError),
+    /// A field is larger than its maximum size.
+    FieldTooLarge {
+        /// The name of the field.
+        field: String,
+        /// The maximum size that was allowed, in bytes.
+        max_size: usize,
+    },
+    /// The content type of a field isn't one of those allowed by its [`FieldConfig`].
+    UnsupportedContentType {
+        /// The name of the field.
+        field: String,
+        /// The content type of the field.
+        content_type: String,
+    },
+    /// The fields couldn't be deserialized into the target type of [`TypedMultipart`], with the
+    /// underlying error.
+    Deserialize(String),
+    /// A field couldn't be written to a temporary file.
+    Io(io::Error),
+}
+
+impl fmt::Display for MultipartError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Rejection(rejection) => write!(f, "{}", rejection),
+            Self::Parse(error) => match std::error::Error::source(error) {
+                Some(source) => write!(f, "{}: {}", error, source),
+                None => write!(f, "{}", error),
+            },
+            Self::FieldTooLarge { field, max_size } => {
+                write!(f, "Field `{}` is larger than {} bytes", field, max_size)
+            }
+            Self::UnsupportedContentType {
+                field,
+                content_type,
+            } => write!(
+                f,
+                "Field `{}` has an unsupported content type `{}`",
+                field, content_type
+            ),
+            Self::Deserialize(error) => {
+                write!(f, "Failed to deserialize the multipart form: {}", error)
+            }
+            Self::Io(_) => write!(f, "Failed to write a field to a temporary file"),
+        }
+    }
+}
+
+impl std::error::Error for MultipartError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Rejection(rejection) => Some(rejection),
+            Self::Parse(error) => Some(error),
+            Self::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl IntoResponse for MultipartError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            Self::Rejection(rejection) => return rejection.into_response(),
+            Self::Parse(_) => StatusCode::BAD_REQUEST,
+            Self::FieldTooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            Self::UnsupportedContentType { .. } => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            Self::Deserialize(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::Io(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, self.to_string()).into_response()
+    }
+}
+
+// A field read by `TypedMultipart`.
+#[derive(Debug)]
+enum Part {
+    Memory(UploadedFile),
+    Disk(TempFile),
+}
+
+thread_local! {
+    // The fields of the request `TypedMultipart` is deserializing, taken by the `Deserialize`
+    // impls of `UploadedFile` and `TempFile`. Serde cannot pass them through to those impls
+    // without copying them into a type serde supports.
+    static PARTS: RefCell<Vec<Option<Part>>> = const { RefCell::new(Vec::new()) };
+}
+
+// Passed to `deserialize_newtype_struct` to ask `PartDeserializer` for the field itself.
+const UPLOADED_FILE: &str = "$axum_extractor_config::UploadedFile";
+const TEMP_FILE: &str = "$axum_extractor_config::TempFile";
+
+fn deserialize<T>(parts: Vec<(String, Part)>) -> Result<T, MultipartError>
+where
+    T: DeserializeOwned,
+{
+    let mut fields = Vec::<(String, Vec<usize>)>::new();
+    let mut table = Vec::with_capacity(parts.len());
+    for (index, (name, part)) in parts.into_iter().enumerate() {
+        table.push(Some(part));
+        match fields.iter_mut().find(|(field, _)| *field == name) {
+            Some((_, indices)) => indices.push(index),
+            None => fields.push((name, vec![index])),
+        }
+    }
+
+    let previous = PARTS.with(|parts| parts.replace(table));
+    let result = T::deserialize(MapDeserializer::<_, de::value::Error>::new(
+        fields
+            .into_iter()
+            .map(|(name, indices)| (name, FieldDeserializer(indices))),
+    ));
+    // drop the fields that weren't deserialized, which deletes their temporary files
+    PARTS.with(|parts| parts.replace(previous));
+
+    result.map_err(|err| MultipartError::Deserialize(err.to_string()))
+}
+
+// All the fields with a name. It's a sequence if the target is one, otherwise the first field.
+struct FieldDeserializer(Vec<usize>);
+
+impl FieldDeserializer {
+    fn first(self) -> PartDeserializer {
+        PartDeserializer(self.0[0])
+    }
+}
+
+impl<'de> IntoDeserializer<'de, de::value::Error> for FieldDeserializer {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+macro_rules! forward_to_part {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                self.first().$method(visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for FieldDeserializer {
+    type Error = de::value::Error;
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(SeqDeserializer::new(
+            self.0.into_iter().map(PartDeserializer),
+        ))
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.first().deserialize_newtype_struct(name, visitor)
+    }
+
+    fn deserialize_unit_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.first().deserialize_unit_struct(name, visitor)
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.first().deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.first().deserialize_tuple_struct(name, len, visitor)
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.first().deserialize_struct(name, fields, visitor)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.first().deserialize_enum(name, variants, visitor)
+    }
+
+    forward_to_part! {
+        deserialize_any deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32
+        deserialize_i64 deserialize_i128 deserialize_u8 deserialize_u16 deserialize_u32
+        deserialize_u64 deserialize_u128 deserialize_f32 deserialize_f64 deserialize_char
+        deserialize_str deserialize_string deserialize_bytes deserialize_byte_buf
+        deserialize_unit deserialize_map deserialize_identifier deserialize_ignored_any
+    }
+}
+
+// A single field, by its index in `PARTS`. Deserializes from its text, unless it's asked for
+// the field itself by `UploadedFile` or `TempFile`.
+struct PartDeserializer(usize);
+
+impl PartDeserializer {
+    fn text(&self) -> Result<StrValue<'static, de::value::Error>, de::value::Error> {
+        PARTS.with(|parts| match parts.borrow().get(self.0) {
+            Some(Some(Part::Memory(file))) => match std::str::from_utf8(&file.bytes) {
+                Ok(text) => Ok(StrValue::new(Cow::Owned(text.to_owned()))),
+                Err(_) => Err(de::Error::custom("field isn't valid UTF-8")),
+            },
+            Some(Some(Part::Disk(_))) => Err(de::Error::custom(
+                "field is stored in a temporary file, so it can only be deserialized into a \
+                 `TempFile`",
+            )),
+            _ => Err(de::Error::custom("field was already deserialized")),
+        })
+    }
+}
+
+impl<'de> IntoDeserializer<'de, de::value::Error> for PartDeserializer {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+macro_rules! forward_to_text {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                self.text()?.$method(visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for PartDeserializer {
+    type Error = de::value::Error;
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if name == UPLOADED_FILE || name == TEMP_FILE {
+            visitor.visit_u64(self.0 as u64)
+        } else {
+            self.text()?.deserialize_newtype_struct(name, visitor)
+        }
+    }
+
+    fn deserialize_unit_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.text()?.deserialize_unit_struct(name, visitor)
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.text()?.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.text()?.deserialize_tuple_struct(name, len, visitor)
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.text()?.deserialize_struct(name, fields, visitor)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.text()?.deserialize_enum(name, variants, visitor)
+    }
+
+    forward_to_text! {
+        deserialize_any deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32
+        deserialize_i64 deserialize_i128 deserialize_u8 deserialize_u16 deserialize_u32
+        deserialize_u64 deserialize_u128 deserialize_f32 deserialize_f64 deserialize_char
+        deserialize_str deserialize_string deserialize_bytes deserialize_byte_buf
+        deserialize_option deserialize_unit deserialize_seq deserialize_map
+        deserialize_identifier deserialize_ignored_any
+    }
+}
+
+// Takes a field out of `PARTS` for the `Deserialize` impls of `UploadedFile` and `TempFile`.
+struct TakePart<T>(PhantomData<T>);
+
+impl<'de> Visitor<'de> for TakePart<UploadedFile> {
+    type Value = UploadedFile;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a field of a `TypedMultipart` request")
+    }
+
+    fn visit_u64<E>(self, index: u64) -> Result<UploadedFile, E>
+    where
+        E: de::Error,
+    {
+        match take_part(index)? {
+            Part::Memory(file) => Ok(file),
+            Part::Disk(_) => Err(E::custom(
+                "field is stored in a temporary file, so it can only be deserialized into a \
+                 `TempFile`",
+            )),
+        }
+    }
+}
+
+impl<'de> Visitor<'de> for TakePart<TempFile> {
+    type Value = TempFile;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a field of a `TypedMultipart` request")
+    }
+
+    fn visit_u64<E>(self, index: u64) -> Result<TempFile, E>
+    where
+        E: de::Error,
+    {
+        match take_part(index)? {
+            Part::Disk(file) => Ok(file),
+            Part::Memory(_) => Err(E::custom(
+                "field is kept in memory, configure it with `FieldConfig::temp_file` to \
+                 deserialize it into a `TempFile`",
+            )),
+        }
+    }
+}
+
+fn take_part<E>(index: u64) -> Result<Part, E>
+where
+    E: de::Error,
+{
+    PARTS
+        .with(|parts| {
+            parts
+                .borrow_mut()
+                .get_mut(index as usize)
+                .and_then(Option::take)
+        })
+        .ok_or_else(|| E::custom("field was already deserialized"))
+}
+
+impl<'de> de::Deserialize<'de> for UploadedFile {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(UPLOADED_FILE, TakePart::<Self>(PhantomData))
+    }
+}
+
+impl<'de> de::Deserialize<'de> for TempFile {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(TEMP_FILE, TakePart::<Self>(PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Request, routing::post, Router};
+    use serde::Deserialize;
+    use tower::ServiceExt;
+
+    const BOUNDARY: &str = "X-BOUNDARY";
+
+    // (name, file name, content type, data)
+    fn multipart_request(parts: &[(&str, Option<&str>, Option<&str>, &str)]) -> Request<Body> {
+        let mut body = String::new();
+        for (name, file_name, content_type, data) in parts {
+            body.push_str(&format!("--{}\r\n", BOUNDARY));
+            body.push_str(&format!(
+                "Content-Disposition: form-data; name=\"{}\"",
+                name
+            ));
+            if let Some(file_name) = file_name {
+                body.push_str(&format!("; filename=\"{}\"", file_name));
+            }
+            body.push_str("\r\n");
+            if let Some(content_type) = content_type {
+                body.push_str(&format!("Content-Type: {}\r\n", content_type));
+            }
+            body.push_str(&format!("\r\n{}\r\n", data));
+        }
+        body.push_str(&format!("--{}--\r\n", BOUNDARY));
+
+        Request::builder()
+            .method("POST")
+            .uri("/")
+            .header(
+                "content-type",
+                format!("multipart/form-data; boundary={}", BOUNDARY),
+            )
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    async fn send(app: Router, req: Request<Body>) -> (StatusCode, String) {
+        let res = app.oneshot(req).await.unwrap();
+        let status = res.status();
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[derive(Deserialize)]
+    struct Upload {
+        title: String,
+        count: u32,
+        tags: Vec<String>,
+        avatar: UploadedFile,
+        attachment: Option<TempFile>,
+    }
+
+    fn upload_app() -> Router {
+        async fn handler(TypedMultipart(upload): TypedMultipart<Upload>) -> String {
+            let attachment = upload.attachment.unwrap();
+            let contents = std::fs::read_to_string(attachment.path()).unwrap();
+            format!(
+                "{} {} {:?} {:?} {:?} {} {}",
+                upload.title,
+                upload.count,
+                upload.tags,
+                upload.avatar.file_name(),
+                upload.avatar.content_type(),
+                String::from_utf8_lossy(upload.avatar.bytes()),
+                contents,
+            )
+        }
+
+        Router::new().route("/", post(handler)).layer(
+            MultipartConfig::new()
+                .max_field_size(16)
+                .field(
+                    "avatar",
+                    FieldConfig::new().max_size(64).content_types(["image/*"]),
+                )
+                .field("attachment", FieldConfig::new().temp_file()),
+        )
+    }
+
+    #[tokio::test]
+    async fn typed_multipart() {
+        let req = multipart_request(&[
+            ("title", None, None, "hello"),
+            ("count", None, None, "3"),
+            ("tags", None, None, "a"),
+            ("tags", None, None, "b"),
+            ("avatar", Some("a.png"), Some("image/png"), "png data"),
+            ("attachment", Some("a.txt"), Some("text/plain"), "on disk"),
+        ]);
+        let (status, body) = send(upload_app(), req).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            body,
+            r#"hello 3 ["a", "b"] Some("a.png") Some("image/png") png data on disk"#
+        );
+    }
+
+    #[tokio::test]
+    async fn temp_file_is_deleted_on_drop() {
+        async fn handler(mut multipart: Multipart) -> String {
+            let field = multipart.next_field().await.unwrap().unwrap();
+            let file = field.temp_file().await.unwrap();
+            assert_eq!(file.size(), 4);
+            let path = file.path().to_owned();
+            assert!(path.exists());
+            drop(file);
+            (!path.exists()).to_string()
+        }
+
+        let app = Router::new().route("/", post(handler));
+        let req = multipart_request(&[("file", Some("a.txt"), None, "data")]);
+        assert_eq!(send(app, req).await, (StatusCode::OK, "true".to_owned()));
+    }
+
+    #[tokio::test]
+    async fn field_too_large() {
+        let req = multipart_request(&[
+            ("title", None, None, "a title that is too long"),
+            ("count", None, None, "3"),
+        ]);
+        let (status, body) = send(upload_app(), req).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(body, "Field `title` is larger than 16 bytes");
+    }
+
+    #[tokio::test]
+    async fn unsupported_content_type() {
+        let req = multipart_request(&[("avatar", Some("a.txt"), Some("text/plain"), "text")]);
+        let (status, body) = send(upload_app(), req).await;
+        assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert_eq!(
+            body,
+            "Field `avatar` has an unsupported content type `text/plain`"
+        );
+    }
+
+    #[tokio::test]
+    async fn deserialize_error() {
+        let req = multipart_request(&[
+            ("title", None, None, "hello"),
+            ("count", None, None, "three"),
+        ]);
+        let (status, _) = send(upload_app(), req).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+        // a field kept in memory can't become a `TempFile`
+        let req = multipart_request(&[
+            ("title", None, None, "hello"),
+            ("count", None, None, "3"),
+            ("avatar", Some("a.png"), Some("image/png"), "png"),
+            ("attachment", Some("a.txt"), None, "in memory"),
+        ]);
+        let app = Router::new().route(
+            "/",
+            post(|TypedMultipart(_): TypedMultipart<Upload>| async {}),
+        );
+        let (status, body) = send(app, req).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(body.contains("FieldConfig::temp_file"), "{}", body);
+    }
+
+    #[tokio::test]
+    async fn not_multipart() {
+        let app = upload_app();
+        let req = Request::builder()
+            .method("POST")
+            .uri("/")
+            .body(Body::empty())
+            .unwrap();
+        let (status, _) = send(app, req).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn rejection_handler() {
+        async fn handler(TypedMultipart(_): TypedMultipart<Upload>) {}
+
+        let app = Router::new().route("/", post(handler)).layer(
+            MultipartConfig::new()
+                .max_field_size(1)
+                .rejection_handler(|error, _| (StatusCode::IM_A_TEAPOT, error.to_string())),
+        );
+        let req = multipart_request(&[("title", None, None, "hello")]);
+        assert_eq!(
+            send(app, req).await,
+            (
+                StatusCode::IM_A_TEAPOT,
+                "Field `title` is larger than 1 bytes".to_owned()
+            )
+        );
+    }
+}
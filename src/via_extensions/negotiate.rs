@@ -0,0 +1,292 @@
+use super::presets::media_ranges;
+use axum::{
+    body::{self, Bytes},
+    http::{header, HeaderMap, HeaderValue, Request, StatusCode},
+    response::{IntoResponse, Response},
+};
+use futures_util::future::BoxFuture;
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    fmt,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Response that serializes `T` in the format the request's `Accept` header prefers.
+///
+/// The formats are chosen with the [`NegotiateConfig`] layer, which must be added for the
+/// response to be negotiated. Without it the response is JSON, like `axum::Json`.
+///
+/// `T` is first serialized into a [`serde_json::Value`], so it's limited to what JSON can
+/// represent. If serializing fails the response is `500 Internal Server Error`.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{Negotiate, NegotiateConfig, ResponseFormat};
+/// use axum::{routing::get, Router};
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct User {
+///     id: u32,
+/// }
+///
+/// async fn handler() -> Negotiate<User> {
+///     Negotiate(User { id: 1 })
+/// }
+///
+/// let app = Router::new()
+///     .route("/user", get(handler))
+///     .layer(NegotiateConfig::new().formats([ResponseFormat::Json]));
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Negotiate<T>(pub T);
+
+impl<T> IntoResponse for Negotiate<T>
+where
+    T: Serialize,
+{
+    fn into_response(self) -> Response {
+        let value = match serde_json::to_value(&self.0) {
+            Ok(value) => value,
+            Err(err) => {
+                return (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response();
+            }
+        };
+        let mut response = axum::Json(&value).into_response();
+        response
+            .extensions_mut()
+            .insert(Negotiated(Arc::new(value)));
+        response
+    }
+}
+
+// the value of a `Negotiate` response, for `NegotiateService` to serialize
+#[derive(Clone)]
+struct Negotiated(Arc<Value>);
+
+/// The formats [`Negotiate`] can respond with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ResponseFormat {
+    /// `application/json`.
+    Json,
+    /// `application/msgpack`.
+    ///
+    /// Requires the `msgpack` feature.
+    #[cfg(feature = "msgpack")]
+    MessagePack,
+    /// `application/yaml`.
+    ///
+    /// Requires the `yaml` feature.
+    #[cfg(feature = "yaml")]
+    Yaml,
+}
+
+impl ResponseFormat {
+    fn content_type(self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            #[cfg(feature = "msgpack")]
+            Self::MessagePack => "application/msgpack",
+            #[cfg(feature = "yaml")]
+            Self::Yaml => "application/yaml",
+        }
+    }
+
+    fn matches(self, media_type: &str) -> bool {
+        match self {
+            Self::Json => {
+                media_type == "application/json"
+                    || (media_type.starts_with("application/") && media_type.ends_with("+json"))
+            }
+            #[cfg(feature = "msgpack")]
+            Self::MessagePack => matches!(
+                media_type,
+                "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack"
+            ),
+            #[cfg(feature = "yaml")]
+            Self::Yaml => matches!(
+                media_type,
+                "application/yaml" | "application/x-yaml" | "text/yaml"
+            ),
+        }
+    }
+
+    fn serialize(self, value: &Value) -> Result<Bytes, String> {
+        match self {
+            Self::Json => serde_json::to_vec(value)
+                .map(Bytes::from)
+                .map_err(|err| err.to_string()),
+            #[cfg(feature = "msgpack")]
+            Self::MessagePack => rmp_serde::to_vec_named(value)
+                .map(Bytes::from)
+                .map_err(|err| err.to_string()),
+            #[cfg(feature = "yaml")]
+            Self::Yaml => serde_yaml::to_string(value)
+                .map(Bytes::from)
+                .map_err(|err| err.to_string()),
+        }
+    }
+}
+
+/// Layer that picks the format of [`Negotiate`] responses.
+///
+/// The format is the one of [`formats`](Self::formats) the `Accept` header prefers. If it
+/// doesn't prefer any of them, for example with `*/*` or no `Accept` header, the
+/// [`default_format`](Self::default_format) is used. Other responses are passed through
+/// unchanged.
+///
+/// See [`Negotiate`] for an example.
+#[derive(Debug, Clone)]
+pub struct NegotiateConfig {
+    formats: Arc<Vec<ResponseFormat>>,
+    default_format: ResponseFormat,
+}
+
+impl NegotiateConfig {
+    /// Create a new `NegotiateConfig` that supports every format whose feature is enabled.
+    pub fn new() -> Self {
+        Self {
+            formats: Arc::new(vec![
+                ResponseFormat::Json,
+                #[cfg(feature = "msgpack")]
+                ResponseFormat::MessagePack,
+                #[cfg(feature = "yaml")]
+                ResponseFormat::Yaml,
+            ]),
+            default_format: ResponseFormat::Json,
+        }
+    }
+
+    /// Set the supported formats.
+    ///
+    /// Formats earlier in the list win if the `Accept` header prefers several equally.
+    ///
+    /// Defaults to every format whose feature is enabled.
+    pub fn formats<I>(mut self, formats: I) -> Self
+    where
+        I: IntoIterator<Item = ResponseFormat>,
+    {
+        self.formats = Arc::new(formats.into_iter().collect());
+        self
+    }
+
+    /// Set the format used if the `Accept` header doesn't prefer one of the supported formats.
+    ///
+    /// Defaults to [`ResponseFormat::Json`].
+    pub fn default_format(mut self, format: ResponseFormat) -> Self {
+        self.default_format = format;
+        self
+    }
+
+    fn preferred_format(&self, headers: &HeaderMap) -> ResponseFormat {
+        let mut best: Option<(ResponseFormat, f32)> = None;
+        for (media_type, quality) in media_ranges(headers) {
+            let format = match self
+                .formats
+                .iter()
+                .find(|format| format.matches(&media_type))
+            {
+                Some(format) => *format,
+                None => continue,
+            };
+
+            // the first of equally preferred formats wins
+            if quality > 0.0 && !matches!(best, Some((_, best)) if best >= quality) {
+                best = Some((format, quality));
+            }
+        }
+        best.map_or(self.default_format, |(format, _)| format)
+    }
+}
+
+impl Default for NegotiateConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Layer<S> for NegotiateConfig {
+    type Service = NegotiateService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        NegotiateService {
+            inner,
+            config: self.clone(),
+        }
+    }
+}
+
+#[allow(unreachable_pub)]
+#[derive(Clone)]
+pub struct NegotiateService<S> {
+    inner: S,
+    config: NegotiateConfig,
+}
+
+impl<S> fmt::Debug for NegotiateService<S>
+where
+    S: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NegotiateService")
+            .field("inner", &self.inner)
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl<S, B> Service<Request<B>> for NegotiateService<S>
+where
+    S: Service<Request<B>> + Clone + Send + 'static,
+    S::Response: IntoResponse,
+    S::Future: Send,
+    B: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Response, S::Error>>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<B>) -> Self::Future {
+        let format = self.config.preferred_format(req.headers());
+        let future = self.inner.call(req);
+
+        Box::pin(async move {
+            let mut response = future.await?.into_response();
+            let value = match response.extensions_mut().remove::<Negotiated>() {
+                Some(Negotiated(value)) => value,
+                None => return Ok(response),
+            };
+            response
+                .headers_mut()
+                .append(header::VARY, HeaderValue::from_static("accept"));
+            if format == ResponseFormat::Json {
+                return Ok(response);
+            }
+
+            match format.serialize(&value) {
+                Ok(bytes) => {
+                    response.headers_mut().insert(
+                        header::CONTENT_TYPE,
+                        HeaderValue::from_static(format.content_type()),
+                    );
+                    response.headers_mut().remove(header::CONTENT_LENGTH);
+                    *response.body_mut() = body::boxed(body::Full::new(bytes));
+                    Ok(response)
+                }
+                Err(err) => Ok((StatusCode::INTERNAL_SERVER_ERROR, err).into_response()),
+            }
+        })
+    }
+}
@@ -0,0 +1,168 @@
+use super::{rejection::ConfiguredRejection, Form, Json, Query};
+use axum::{
+    async_trait,
+    body::{Bytes, HttpBody},
+    extract::{rejection::JsonRejection, FromRequest, RequestParts},
+    http::Method,
+    BoxError,
+};
+use serde::de::DeserializeOwned;
+
+/// Extractor that makes another extractor optional, without hiding malformed input.
+///
+/// Unlike `Option<E>`, which is `None` whenever `E` rejects the request, this is only `None` if
+/// the input is absent:
+///
+/// - [`Json`] and [`Form`] are absent if the request body is empty. [`Form`] reads `GET` requests
+///   from the query string, so for those the query string must be empty as well.
+/// - [`Query`] is absent if the request has no query string, or an empty one.
+///
+/// Otherwise the wrapped extractor runs as usual, so malformed input is rejected with its config.
+/// The body is only known to be empty if the request says so, for example with a
+/// `Content-Length: 0` header.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{Json, JsonConfig, Optional};
+/// use axum::{http::StatusCode, routing::patch, Router};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct UpdateUser {
+///     name: Option<String>,
+/// }
+///
+/// // requests without a body are fine, but invalid JSON is still rejected
+/// async fn handler(Optional(update): Optional<Json<UpdateUser>>) {
+///     if let Some(Json(update)) = update {
+///         // ...
+///     }
+/// }
+///
+/// let app = Router::new()
+///     .route("/users/:id", patch(handler))
+///     .layer(JsonConfig::new().override_status(StatusCode::BAD_REQUEST));
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Optional<E>(pub Option<E>);
+
+#[async_trait]
+impl<E, B> FromRequest<B> for Optional<E>
+where
+    E: FromRequest<B> + MaybeAbsent,
+    B: HttpBody + Send,
+{
+    type Rejection = E::Rejection;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        if E::is_absent(req) {
+            return Ok(Self(None));
+        }
+        E::from_request(req).await.map(|value| Self(Some(value)))
+    }
+}
+
+/// Extractor that deserializes JSON bodies like [`Json`], or uses `T::default()` if the body is
+/// empty.
+///
+/// This is useful for endpoints where the body is optional, such as `PATCH` or `DELETE`
+/// endpoints with optional parameters. Bodies that aren't empty are extracted with [`Json`], so
+/// malformed bodies are rejected with the [`JsonConfig`](super::JsonConfig) as usual. See
+/// [`Optional`] for when the body is considered empty.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::JsonOrDefault;
+/// use axum::{routing::delete, Router};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize, Default)]
+/// struct DeleteOptions {
+///     #[serde(default)]
+///     hard: bool,
+/// }
+///
+/// async fn handler(JsonOrDefault(options): JsonOrDefault<DeleteOptions>) {}
+///
+/// let app = Router::new().route("/users/:id", delete(handler));
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonOrDefault<T>(pub T);
+
+#[async_trait]
+impl<T, B> FromRequest<B> for JsonOrDefault<T>
+where
+    T: DeserializeOwned + Default + Send + 'static,
+    B: HttpBody<Data = Bytes> + Send + 'static,
+    B::Error: Into<BoxError>,
+{
+    type Rejection = ConfiguredRejection<JsonRejection>;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let Optional(value) = Optional::<Json<T>>::from_request(req).await?;
+        Ok(Self(value.map(Json::into_inner).unwrap_or_default()))
+    }
+}
+
+// The extractors supported by `Optional`.
+#[allow(unreachable_pub)]
+pub trait MaybeAbsent: sealed::Sealed {
+    fn is_absent<B>(req: &RequestParts<B>) -> bool
+    where
+        B: HttpBody;
+}
+
+impl<T> sealed::Sealed for Json<T> {}
+
+impl<T> MaybeAbsent for Json<T> {
+    fn is_absent<B>(req: &RequestParts<B>) -> bool
+    where
+        B: HttpBody,
+    {
+        body_is_empty(req)
+    }
+}
+
+impl<T> sealed::Sealed for Form<T> {}
+
+impl<T> MaybeAbsent for Form<T> {
+    fn is_absent<B>(req: &RequestParts<B>) -> bool
+    where
+        B: HttpBody,
+    {
+        body_is_empty(req) && (req.method() != Method::GET || query_is_empty(req))
+    }
+}
+
+impl<T> sealed::Sealed for Query<T> {}
+
+impl<T> MaybeAbsent for Query<T> {
+    fn is_absent<B>(req: &RequestParts<B>) -> bool
+    where
+        B: HttpBody,
+    {
+        query_is_empty(req)
+    }
+}
+
+// Bodies that have already been taken aren't empty, so the extractor rejects them as usual.
+fn body_is_empty<B>(req: &RequestParts<B>) -> bool
+where
+    B: HttpBody,
+{
+    req.body()
+        .is_some_and(|body| body.is_end_stream() || body.size_hint().exact() == Some(0))
+}
+
+fn query_is_empty<B>(req: &RequestParts<B>) -> bool {
+    req.uri().query().unwrap_or_default().is_empty()
+}
+
+mod sealed {
+    #[allow(unreachable_pub)]
+    pub trait Sealed {}
+}
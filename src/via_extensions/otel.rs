@@ -0,0 +1,29 @@
+use super::RejectionInfo;
+use axum::http::StatusCode;
+use opentelemetry::{trace::get_active_span, KeyValue};
+
+// Record a rejection on the active span, as attributes and as an event.
+pub(crate) fn record_rejection(info: &RejectionInfo, status: StatusCode) {
+    get_active_span(|span| {
+        // don't format the message for spans that aren't recorded
+        if !span.is_recording() {
+            return;
+        }
+        let attributes = vec![
+            KeyValue::new("extractor.name", info.extractor()),
+            KeyValue::new("extractor.rejection.kind", info.kind().as_str()),
+            KeyValue::new("extractor.target_type", info.target_type_name()),
+            KeyValue::new("http.response.status_code", i64::from(status.as_u16())),
+        ];
+        for attribute in &attributes {
+            span.set_attribute(attribute.clone());
+        }
+
+        let mut event = attributes;
+        event.push(KeyValue::new(
+            "extractor.rejection.message",
+            info.message().to_owned(),
+        ));
+        span.add_event("extractor rejected request", event);
+    });
+}
@@ -0,0 +1,140 @@
+use super::{
+    presets,
+    rejection::{DetailLevel, RejectionParts},
+    RejectionContext,
+};
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{error::Error, sync::Arc};
+
+/// Settings for the extractors that can be loaded from configuration files.
+///
+/// This allows changing how extractors reject requests without changing code, for example from a
+/// TOML or YAML file. Create configs from it with `from_policy`, such as
+/// [`JsonConfig::from_policy`](super::JsonConfig::from_policy).
+///
+/// Missing fields use the defaults of the configs and unknown fields are rejected.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{JsonConfig, RejectionPolicy};
+///
+/// let policy: RejectionPolicy = serde_json::from_str(r#"{
+///     "override_status": 400,
+///     "detail_level": "message",
+///     "preset": "problem-details",
+///     "max_body_size": 65536
+/// }"#).unwrap();
+///
+/// let config = JsonConfig::from_policy(&policy);
+/// # let _: JsonConfig<axum::body::Body> = config;
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+#[non_exhaustive]
+pub struct RejectionPolicy {
+    /// The status code of the default rejection responses.
+    ///
+    /// See [`JsonConfig::override_status`](super::JsonConfig::override_status).
+    #[serde(with = "status_code")]
+    pub override_status: Option<StatusCode>,
+
+    /// How much detail the default rejection responses include.
+    pub detail_level: DetailLevel,
+
+    /// The preset used as the rejection handler.
+    pub preset: Option<Preset>,
+
+    /// The maximum size of JSON and form bodies, in bytes.
+    pub max_body_size: Option<usize>,
+
+    /// The maximum length of query strings, in bytes.
+    pub max_query_length: Option<usize>,
+
+    /// The maximum number of parameters in query strings.
+    pub max_query_params: Option<usize>,
+}
+
+impl RejectionPolicy {
+    /// Create a new `RejectionPolicy` with the default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// The [`presets`] a [`RejectionPolicy`] can choose.
+///
+/// Serialized in kebab-case, such as `problem-details`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum Preset {
+    /// [`presets::problem_details`].
+    ProblemDetails,
+    /// [`presets::simple_json`].
+    SimpleJson,
+    /// [`presets::graphql`].
+    Graphql,
+    /// [`presets::negotiate`].
+    Negotiate,
+    /// `presets::html`.
+    ///
+    /// Requires the `html` feature.
+    #[cfg(feature = "html")]
+    Html,
+    /// `presets::dev_errors`.
+    ///
+    /// Requires the `dev-errors` feature.
+    #[cfg(feature = "dev-errors")]
+    DevErrors,
+}
+
+impl Preset {
+    pub(super) fn into_handler<R, B>(
+        self,
+    ) -> Arc<dyn Fn(R, RejectionContext<'_, B>) -> Response + Send + Sync>
+    where
+        R: Into<RejectionParts> + IntoResponse + Error + 'static,
+        B: 'static,
+    {
+        match self {
+            Self::ProblemDetails => Arc::new(presets::problem_details()),
+            Self::SimpleJson => Arc::new(presets::simple_json()),
+            Self::Graphql => Arc::new(presets::graphql()),
+            Self::Negotiate => Arc::new(presets::negotiate()),
+            #[cfg(feature = "html")]
+            Self::Html => Arc::new(presets::html()),
+            #[cfg(feature = "dev-errors")]
+            Self::DevErrors => Arc::new(presets::dev_errors()),
+        }
+    }
+}
+
+// `StatusCode` doesn't implement `Serialize` and `Deserialize` so use the numeric code.
+mod status_code {
+    use super::*;
+    use serde::de::Error as _;
+
+    pub(super) fn serialize<S>(
+        status: &Option<StatusCode>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        status.map(|status| status.as_u16()).serialize(serializer)
+    }
+
+    pub(super) fn deserialize<'de, D>(deserializer: D) -> Result<Option<StatusCode>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Option::<u16>::deserialize(deserializer)?
+            .map(|status| StatusCode::from_u16(status).map_err(D::Error::custom))
+            .transpose()
+    }
+}
@@ -0,0 +1,284 @@
+use super::{detail, escape, title};
+use crate::via_extensions::{rejection::RejectionParts, RejectionContext};
+use axum::{
+    http::header,
+    response::{Html, IntoResponse, Response},
+};
+use std::{error::Error, fmt::Write};
+
+const RED: &str = "\x1b[31m";
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+/// A rejection handler for local development that shows where JSON bodies failed to parse.
+///
+/// The response includes the lines of the body around the error, with the failing line and
+/// column highlighted. Clients that accept `text/html`, such as browsers, get an HTML page and
+/// others, such as `curl`, get text with ANSI colors.
+///
+/// The body is only available if it's captured with
+/// [`JsonConfig::capture_body`](crate::via_extensions::JsonConfig::capture_body). Rejections
+/// other than JSON syntax and deserialization errors are shown without a body excerpt.
+///
+/// The pages show the body back to the client, so they are only enabled in debug builds by
+/// default. Otherwise rejections get their default response. See [`DevErrors`] for changing that.
+///
+/// Requires the `dev-errors` feature.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{presets, JsonConfig};
+///
+/// let config = JsonConfig::new()
+///     .capture_body(64 * 1024)
+///     .rejection_handler(presets::dev_errors());
+/// # let _: JsonConfig<axum::body::Body> = config;
+/// ```
+pub fn dev_errors<R, B>(
+) -> impl Fn(R, RejectionContext<'_, B>) -> Response + Clone + Send + Sync + 'static
+where
+    R: Into<RejectionParts> + IntoResponse + Error + 'static,
+{
+    DevErrors::new().into_handler()
+}
+
+/// Configuration for the [`dev_errors`] rejection handler.
+///
+/// Requires the `dev-errors` feature.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{presets::DevErrors, JsonConfig};
+///
+/// let enabled = std::env::var("DEV_ERRORS").is_ok();
+/// let handler = DevErrors::new().enabled(enabled).into_handler();
+///
+/// let config = JsonConfig::new().capture_body(64 * 1024).rejection_handler(handler);
+/// # let _: JsonConfig<axum::body::Body> = config;
+/// ```
+#[derive(Debug, Clone)]
+pub struct DevErrors {
+    enabled: bool,
+    context_lines: usize,
+}
+
+impl Default for DevErrors {
+    fn default() -> Self {
+        Self {
+            enabled: cfg!(debug_assertions),
+            context_lines: 2,
+        }
+    }
+}
+
+impl DevErrors {
+    /// Create a new `DevErrors`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set whether the pages are shown.
+    ///
+    /// If disabled, rejections get their default response.
+    ///
+    /// Defaults to `true` in debug builds and `false` otherwise.
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Set how many lines before and after the failing line are shown.
+    ///
+    /// Defaults to 2.
+    pub fn context_lines(mut self, context_lines: usize) -> Self {
+        self.context_lines = context_lines;
+        self
+    }
+
+    /// Convert into a rejection handler.
+    pub fn into_handler<R, B>(
+        self,
+    ) -> impl Fn(R, RejectionContext<'_, B>) -> Response + Clone + Send + Sync + 'static
+    where
+        R: Into<RejectionParts> + IntoResponse + Error + 'static,
+    {
+        move |rejection, ctx| self.respond(rejection, ctx)
+    }
+
+    fn respond<R, B>(&self, rejection: R, ctx: RejectionContext<'_, B>) -> Response
+    where
+        R: Into<RejectionParts> + IntoResponse + Error + 'static,
+    {
+        if !self.enabled {
+            return rejection.into_response();
+        }
+
+        let location = json_error_location(&rejection);
+        let parts = rejection.into();
+
+        let mut message = title(&parts, ctx);
+        if let Some(detail) = detail(&parts, ctx) {
+            write!(message, ": {}", detail).unwrap();
+        }
+
+        let excerpt = match (location, ctx.body()) {
+            (Some((line, column)), Some(body)) => {
+                let body = String::from_utf8_lossy(body.bytes());
+                self.excerpt(&body, line, column)
+            }
+            (Some(_), None) => vec![ExcerptLine::Note(
+                "Enable `JsonConfig::capture_body` to see the body".to_owned(),
+            )],
+            (None, _) => Vec::new(),
+        };
+
+        let accepts_html = ctx
+            .headers()
+            .get_all(header::ACCEPT)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .any(|value| value.contains("text/html"));
+
+        if accepts_html {
+            (parts.status(), Html(render_html(&message, &excerpt))).into_response()
+        } else {
+            (parts.status(), render_ansi(&message, &excerpt)).into_response()
+        }
+    }
+
+    fn excerpt(&self, body: &str, line: usize, column: usize) -> Vec<ExcerptLine> {
+        let first = line.saturating_sub(self.context_lines).max(1);
+        let last = line + self.context_lines;
+
+        let mut excerpt = Vec::new();
+        for (number, text) in body.lines().enumerate().map(|(idx, text)| (idx + 1, text)) {
+            if number < first {
+                continue;
+            }
+            if number > last {
+                break;
+            }
+            excerpt.push(ExcerptLine::Source {
+                number,
+                text: text.to_owned(),
+                failing: number == line,
+            });
+            if number == line {
+                excerpt.push(ExcerptLine::Caret { column });
+            }
+        }
+        excerpt
+    }
+}
+
+enum ExcerptLine {
+    Source {
+        number: usize,
+        text: String,
+        failing: bool,
+    },
+    Caret {
+        column: usize,
+    },
+    Note(String),
+}
+
+/// The line and column of the `serde_json::Error` that caused the rejection, if any.
+fn json_error_location(err: &(dyn Error + 'static)) -> Option<(usize, usize)> {
+    let mut source = Some(err);
+    while let Some(err) = source {
+        if let Some(err) = err.downcast_ref::<serde_json::Error>() {
+            // serde_json uses line 0 for errors that don't have a location
+            return (err.line() > 0).then(|| (err.line(), err.column()));
+        }
+        source = err.source();
+    }
+    None
+}
+
+fn render_ansi(message: &str, excerpt: &[ExcerptLine]) -> String {
+    let mut out = format!("{}{}{}{}\n", BOLD, RED, message, RESET);
+    if !excerpt.is_empty() {
+        out.push('\n');
+    }
+    for line in excerpt {
+        match line {
+            ExcerptLine::Source {
+                number,
+                text,
+                failing,
+            } => {
+                let marker = if *failing { ">" } else { " " };
+                writeln!(out, "{}{} {:>4} |{} {}", DIM, marker, number, RESET, text).unwrap();
+            }
+            ExcerptLine::Caret { column } => {
+                writeln!(
+                    out,
+                    "{}       |{} {}{}^{}",
+                    DIM,
+                    RESET,
+                    " ".repeat(column.saturating_sub(1)),
+                    RED,
+                    RESET
+                )
+                .unwrap();
+            }
+            ExcerptLine::Note(note) => writeln!(out, "{}{}{}", DIM, note, RESET).unwrap(),
+        }
+    }
+    out
+}
+
+fn render_html(message: &str, excerpt: &[ExcerptLine]) -> String {
+    let mut pre = String::new();
+    for line in excerpt {
+        match line {
+            ExcerptLine::Source {
+                number,
+                text,
+                failing,
+            } => {
+                let text = format!("{:>4} | {}", number, escape(text));
+                if *failing {
+                    writeln!(pre, "<mark>{}</mark>", text).unwrap();
+                } else {
+                    writeln!(pre, "{}", text).unwrap();
+                }
+            }
+            ExcerptLine::Caret { column } => {
+                writeln!(
+                    pre,
+                    "     | {}<strong>^</strong>",
+                    " ".repeat(column.saturating_sub(1))
+                )
+                .unwrap();
+            }
+            ExcerptLine::Note(note) => writeln!(pre, "<em>{}</em>", escape(note)).unwrap(),
+        }
+    }
+
+    let pre = if pre.is_empty() {
+        String::new()
+    } else {
+        format!("\n<pre>{}</pre>", pre)
+    };
+
+    format!(
+        "<!DOCTYPE html>\n\
+        <html>\n\
+        <head>\n\
+        <meta charset=\"utf-8\">\n\
+        <title>Rejected request</title>\n\
+        <style>strong {{ color: red; }}</style>\n\
+        </head>\n\
+        <body>\n\
+        <h1>{message}</h1>{pre}\n\
+        </body>\n\
+        </html>\n",
+        message = escape(message),
+        pre = pre,
+    )
+}
@@ -0,0 +1,208 @@
+use super::{detail, escape, title};
+use crate::via_extensions::{rejection::RejectionParts, RejectionContext};
+use axum::{
+    http::StatusCode,
+    response::{Html, IntoResponse, Response},
+};
+use std::{fmt, sync::Arc};
+
+type TemplateFn = Arc<dyn Fn(&HtmlError<'_>) -> String + Send + Sync>;
+
+/// A rejection handler that responds with a small HTML error page.
+///
+/// This is meant for server-rendered applications, where users submit forms from a browser,
+/// rather than JSON APIs. See [`HtmlPage`] for using a different template.
+///
+/// Requires the `html` feature.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{presets, FormConfig};
+///
+/// let config = FormConfig::new().rejection_handler(presets::html());
+/// # let _: FormConfig<axum::body::Body> = config;
+/// ```
+pub fn html<R, B>(
+) -> impl Fn(R, RejectionContext<'_, B>) -> Response + Clone + Send + Sync + 'static
+where
+    R: Into<RejectionParts>,
+{
+    HtmlPage::new().into_handler()
+}
+
+/// Configuration for the [`html`] rejection handler.
+///
+/// The template is a function that renders an [`HtmlError`], so pages can be rendered with any
+/// template engine, such as minijinja or askama.
+///
+/// Requires the `html` feature.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{
+///     presets::{HtmlError, HtmlPage},
+///     FormConfig,
+/// };
+///
+/// fn template(error: &HtmlError<'_>) -> String {
+///     format!(
+///         "<p class=\"error\">{}</p><a href=\"{}\">Try again</a>",
+///         error.title_escaped(),
+///         error.path_escaped(),
+///     )
+/// }
+///
+/// let config = FormConfig::new().rejection_handler(HtmlPage::new().template(template).into_handler());
+/// # let _: FormConfig<axum::body::Body> = config;
+/// ```
+#[derive(Clone, Default)]
+pub struct HtmlPage {
+    template: Option<TemplateFn>,
+}
+
+impl HtmlPage {
+    /// Create a new `HtmlPage`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the function that renders the page.
+    ///
+    /// Defaults to a minimal page with the message and the underlying error.
+    pub fn template<F>(mut self, template: F) -> Self
+    where
+        F: Fn(&HtmlError<'_>) -> String + Send + Sync + 'static,
+    {
+        self.template = Some(Arc::new(template));
+        self
+    }
+
+    /// Convert into a rejection handler.
+    pub fn into_handler<R, B>(
+        self,
+    ) -> impl Fn(R, RejectionContext<'_, B>) -> Response + Clone + Send + Sync + 'static
+    where
+        R: Into<RejectionParts>,
+    {
+        move |rejection, ctx| self.respond(rejection.into(), ctx)
+    }
+
+    pub(super) fn respond<B>(
+        &self,
+        parts: RejectionParts,
+        ctx: RejectionContext<'_, B>,
+    ) -> Response {
+        let title = title(&parts, ctx);
+        let error = HtmlError {
+            status: parts.status(),
+            title: &title,
+            detail: detail(&parts, ctx),
+            path: ctx.uri().path(),
+            request_id: ctx.request_id(),
+        };
+
+        let page = match &self.template {
+            Some(template) => template(&error),
+            None => default_template(&error),
+        };
+
+        (parts.status(), Html(page)).into_response()
+    }
+}
+
+impl fmt::Debug for HtmlPage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HtmlPage").finish()
+    }
+}
+
+/// The rejection given to [`HtmlPage::template`].
+///
+/// The values are not escaped. Use the `_escaped` methods, or the escaping of the template engine,
+/// when including them in HTML.
+#[derive(Debug, Clone, Copy)]
+pub struct HtmlError<'a> {
+    status: StatusCode,
+    title: &'a str,
+    detail: Option<&'a str>,
+    path: &'a str,
+    request_id: Option<&'a str>,
+}
+
+impl<'a> HtmlError<'a> {
+    /// The status code of the response.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// The [localized message](RejectionContext::localized_message), if any, or the rejection's
+    /// message.
+    pub fn title(&self) -> &'a str {
+        self.title
+    }
+
+    /// The underlying error, if any.
+    pub fn detail(&self) -> Option<&'a str> {
+        self.detail
+    }
+
+    /// The request's path.
+    pub fn path(&self) -> &'a str {
+        self.path
+    }
+
+    /// The [ID of the request](RejectionContext::request_id), if any.
+    pub fn request_id(&self) -> Option<&'a str> {
+        self.request_id
+    }
+
+    /// [`title`](Self::title), escaped for use in HTML.
+    pub fn title_escaped(&self) -> String {
+        escape(self.title)
+    }
+
+    /// [`detail`](Self::detail), escaped for use in HTML.
+    pub fn detail_escaped(&self) -> Option<String> {
+        self.detail.map(escape)
+    }
+
+    /// [`path`](Self::path), escaped for use in HTML.
+    pub fn path_escaped(&self) -> String {
+        escape(self.path)
+    }
+
+    /// [`request_id`](Self::request_id), escaped for use in HTML.
+    pub fn request_id_escaped(&self) -> Option<String> {
+        self.request_id.map(escape)
+    }
+}
+
+fn default_template(error: &HtmlError<'_>) -> String {
+    let detail = error
+        .detail_escaped()
+        .map(|detail| format!("\n<p>{}</p>", detail))
+        .unwrap_or_default();
+    let request_id = error
+        .request_id_escaped()
+        .map(|request_id| format!("\n<p>Request ID: {}</p>", request_id))
+        .unwrap_or_default();
+
+    format!(
+        "<!DOCTYPE html>\n\
+        <html>\n\
+        <head>\n\
+        <meta charset=\"utf-8\">\n\
+        <title>{status}</title>\n\
+        </head>\n\
+        <body>\n\
+        <h1>{title}</h1>{detail}{request_id}\n\
+        </body>\n\
+        </html>\n",
+        status = error.status,
+        title = error.title_escaped(),
+        detail = detail,
+        request_id = request_id,
+    )
+}
@@ -0,0 +1,312 @@
+//! Ready-made rejection handlers.
+//!
+//! The handlers work with [`JsonConfig`](super::JsonConfig), [`QueryConfig`](super::QueryConfig),
+//! [`FormConfig`](super::FormConfig), and [`GlobalRejectionConfig`](super::GlobalRejectionConfig).
+//! They use the error codes, localized messages, documentation links, request IDs, and JSON
+//! Pointers from the [`RejectionContext`], if configured, and leave out the underlying error of
+//! redacted fields.
+
+use super::{rejection::RejectionParts, RejectionContext};
+use axum::{
+    http::{header, HeaderValue},
+    response::{IntoResponse, Response},
+};
+use serde_json::{Map, Value};
+use std::borrow::Cow;
+
+#[cfg(feature = "dev-errors")]
+mod dev;
+#[cfg(feature = "html")]
+mod html;
+mod negotiate;
+
+#[cfg(feature = "dev-errors")]
+pub use dev::{dev_errors, DevErrors};
+#[cfg(feature = "html")]
+pub use html::{html, HtmlError, HtmlPage};
+pub use negotiate::{negotiate, Format, Negotiate};
+
+pub(super) use negotiate::media_ranges;
+
+/// A rejection handler that responds with [RFC 7807] Problem Details.
+///
+/// See [`ProblemDetails`] for the format of the responses and for setting the base of the `type`
+/// URIs.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{presets, GlobalRejectionConfig};
+///
+/// let config = GlobalRejectionConfig::new().rejection_handler(presets::problem_details());
+/// # let _: GlobalRejectionConfig<axum::body::Body> = config;
+/// ```
+///
+/// [RFC 7807]: https://www.rfc-editor.org/rfc/rfc7807
+pub fn problem_details<R, B>(
+) -> impl Fn(R, RejectionContext<'_, B>) -> Response + Clone + Send + Sync + 'static
+where
+    R: Into<RejectionParts>,
+{
+    ProblemDetails::new().into_handler()
+}
+
+/// A rejection handler that responds with a simple JSON body.
+///
+/// The body looks like
+///
+/// ```json
+/// {
+///     "error": "Failed to deserialize the JSON body into the target type",
+///     "details": "invalid type: string \"foo\", expected u32 at line 1 column 11"
+/// }
+/// ```
+///
+/// `error` is the [localized message](RejectionContext::localized_message), if any, or the
+/// rejection's message, and `details` is the underlying error, if any. The
+/// [error code](RejectionContext::error_code) and
+/// [JSON Pointer](RejectionContext::json_pointer) are included as `code` and `pointer`, if any,
+/// as are the [errors of every field](RejectionContext::field_errors) as `errors`. With the `schemars` feature the part of the JSON Schema that didn't match is included as
+/// `schema`, if any. The status code is the same as the default rejection response.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{presets, JsonConfig};
+///
+/// let config = JsonConfig::new().rejection_handler(presets::simple_json());
+/// # let _: JsonConfig<axum::body::Body> = config;
+/// ```
+pub fn simple_json<R, B>(
+) -> impl Fn(R, RejectionContext<'_, B>) -> Response + Clone + Send + Sync + 'static
+where
+    R: Into<RejectionParts>,
+{
+    |rejection, ctx| simple_json_response(rejection.into(), ctx)
+}
+
+fn simple_json_response<B>(parts: RejectionParts, ctx: RejectionContext<'_, B>) -> Response {
+    let mut body = Map::new();
+    body.insert("error".to_owned(), title(&parts, ctx).into());
+    if let Some(detail) = detail(&parts, ctx) {
+        body.insert("details".to_owned(), detail.into());
+    }
+    extensions(&mut body, ctx);
+
+    (parts.status(), axum::Json(Value::Object(body))).into_response()
+}
+
+/// A rejection handler that responds with a GraphQL style error envelope.
+///
+/// This is useful for gateways that present errors from REST endpoints in the same format as
+/// errors from GraphQL endpoints. The body looks like
+///
+/// ```json
+/// {
+///     "errors": [
+///         {
+///             "message": "Failed to deserialize the JSON body into the target type",
+///             "extensions": {
+///                 "status": 422,
+///                 "detail": "invalid type: string \"foo\", expected u32 at line 1 column 11",
+///                 "code": "INVALID_BODY",
+///                 "pointer": "/id"
+///             }
+///         }
+///     ]
+/// }
+/// ```
+///
+/// `message` is the [localized message](RejectionContext::localized_message), if any, or the
+/// rejection's message. `detail`, `code`, and `pointer` are only included if there is an
+/// underlying error, [error code](RejectionContext::error_code), or
+/// [JSON Pointer](RejectionContext::json_pointer). The status code is the same as the default
+/// rejection response.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{presets, GlobalRejectionConfig};
+///
+/// let config = GlobalRejectionConfig::new().rejection_handler(presets::graphql());
+/// # let _: GlobalRejectionConfig<axum::body::Body> = config;
+/// ```
+pub fn graphql<R, B>(
+) -> impl Fn(R, RejectionContext<'_, B>) -> Response + Clone + Send + Sync + 'static
+where
+    R: Into<RejectionParts>,
+{
+    |rejection, ctx| {
+        let parts: RejectionParts = rejection.into();
+
+        let mut error_extensions = Map::new();
+        error_extensions.insert("status".to_owned(), parts.status().as_u16().into());
+        if let Some(detail) = detail(&parts, ctx) {
+            error_extensions.insert("detail".to_owned(), detail.into());
+        }
+        extensions(&mut error_extensions, ctx);
+
+        let mut error = Map::new();
+        error.insert("message".to_owned(), title(&parts, ctx).into());
+        error.insert("extensions".to_owned(), Value::Object(error_extensions));
+
+        let mut body = Map::new();
+        body.insert(
+            "errors".to_owned(),
+            Value::Array(vec![Value::Object(error)]),
+        );
+
+        (parts.status(), axum::Json(Value::Object(body))).into_response()
+    }
+}
+
+/// Configuration for the [`problem_details`] rejection handler.
+///
+/// Responses have the `application/problem+json` content type and a body such as
+///
+/// ```json
+/// {
+///     "type": "https://example.com/problems/deserialize-error",
+///     "title": "Failed to deserialize the JSON body into the target type",
+///     "status": 422,
+///     "detail": "invalid type: string \"foo\", expected u32 at line 1 column 11",
+///     "instance": "/users",
+///     "code": "INVALID_BODY",
+///     "pointer": "/id"
+/// }
+/// ```
+///
+/// - `type` is the `type` base followed by the kind of the rejection, or `about:blank` if no base
+///   is set.
+/// - `title` is the [localized message](RejectionContext::localized_message), if any, or the
+///   rejection's message.
+/// - `detail` is the underlying error, if any.
+/// - `instance` is the request's path.
+/// - `code` is the [error code](RejectionContext::error_code), if any.
+/// - `pointer` is the [JSON Pointer](RejectionContext::json_pointer) to the element that failed
+///   to deserialize, if any.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{presets::ProblemDetails, JsonConfig};
+///
+/// let handler = ProblemDetails::new()
+///     .type_base("https://example.com/problems/")
+///     .into_handler();
+///
+/// let config = JsonConfig::new().rejection_handler(handler);
+/// # let _: JsonConfig<axum::body::Body> = config;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ProblemDetails {
+    type_base: Option<Cow<'static, str>>,
+}
+
+impl ProblemDetails {
+    /// Create a new `ProblemDetails`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the base of the `type` URIs.
+    ///
+    /// The kind of the rejection, such as `deserialize-error`, is appended to it, so it usually
+    /// ends with a `/`.
+    ///
+    /// Defaults to no base, in which case `type` is `about:blank`.
+    pub fn type_base(mut self, type_base: impl Into<Cow<'static, str>>) -> Self {
+        self.type_base = Some(type_base.into());
+        self
+    }
+
+    /// Convert into a rejection handler.
+    pub fn into_handler<R, B>(
+        self,
+    ) -> impl Fn(R, RejectionContext<'_, B>) -> Response + Clone + Send + Sync + 'static
+    where
+        R: Into<RejectionParts>,
+    {
+        move |rejection, ctx| self.respond(rejection.into(), ctx)
+    }
+
+    fn respond<B>(&self, parts: RejectionParts, ctx: RejectionContext<'_, B>) -> Response {
+        let type_ = match &self.type_base {
+            Some(type_base) => format!("{}{}", type_base, ctx.kind().as_str()),
+            None => "about:blank".to_owned(),
+        };
+
+        let mut body = Map::new();
+        body.insert("type".to_owned(), type_.into());
+        body.insert("title".to_owned(), title(&parts, ctx).into());
+        body.insert("status".to_owned(), parts.status().as_u16().into());
+        if let Some(detail) = detail(&parts, ctx) {
+            body.insert("detail".to_owned(), detail.into());
+        }
+        body.insert("instance".to_owned(), ctx.uri().path().into());
+        extensions(&mut body, ctx);
+
+        let mut response = (parts.status(), axum::Json(Value::Object(body))).into_response();
+        response.headers_mut().insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/problem+json"),
+        );
+        response
+    }
+}
+
+fn title<B>(parts: &RejectionParts, ctx: RejectionContext<'_, B>) -> String {
+    ctx.localized_message()
+        .unwrap_or_else(|| parts.title().to_owned())
+}
+
+// the underlying error might contain the value of a redacted field
+fn detail<'a, B>(parts: &'a RejectionParts, ctx: RejectionContext<'_, B>) -> Option<&'a str> {
+    if ctx.details_redacted() {
+        None
+    } else {
+        parts.detail()
+    }
+}
+
+fn extensions<B>(body: &mut Map<String, Value>, ctx: RejectionContext<'_, B>) {
+    if let Some(code) = ctx.error_code() {
+        body.insert("code".to_owned(), serde_json::to_value(code).unwrap());
+    }
+    if let Some(pointer) = ctx.json_pointer() {
+        body.insert("pointer".to_owned(), pointer.into());
+    }
+    if let Some(docs) = ctx.docs_url() {
+        body.insert("docs".to_owned(), docs.into());
+    }
+    if let Some(request_id) = ctx.request_id() {
+        body.insert("request_id".to_owned(), request_id.into());
+    }
+    if let Some(errors) = ctx.field_errors() {
+        let errors = errors
+            .iter()
+            .map(|error| serde_json::json!({ "pointer": error.pointer(), "message": error.message() }))
+            .collect();
+        body.insert("errors".to_owned(), Value::Array(errors));
+    }
+    #[cfg(feature = "schemars")]
+    if let Some(schema) = ctx.json_schema() {
+        body.insert("schema".to_owned(), schema.clone());
+    }
+}
+
+#[cfg(any(feature = "html", feature = "dev-errors"))]
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
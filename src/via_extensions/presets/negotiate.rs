@@ -0,0 +1,174 @@
+use super::{detail, simple_json_response, title};
+use crate::via_extensions::{rejection::RejectionParts, RejectionContext};
+use axum::{
+    http::{header, HeaderMap},
+    response::{IntoResponse, Response},
+};
+
+#[cfg(feature = "html")]
+use super::HtmlPage;
+
+/// A rejection handler that picks the format of the response based on the `Accept` header.
+///
+/// JSON responses are the same as [`simple_json`](super::simple_json). HTML responses require the
+/// `html` feature and are the same as `presets::html`. If the client doesn't ask for one of the
+/// formats specifically, for example with `*/*`, the default format is used. See [`Negotiate`] for
+/// changing it.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{presets, GlobalRejectionConfig};
+///
+/// let config = GlobalRejectionConfig::new().rejection_handler(presets::negotiate());
+/// # let _: GlobalRejectionConfig<axum::body::Body> = config;
+/// ```
+pub fn negotiate<R, B>(
+) -> impl Fn(R, RejectionContext<'_, B>) -> Response + Clone + Send + Sync + 'static
+where
+    R: Into<RejectionParts>,
+{
+    Negotiate::new().into_handler()
+}
+
+/// The formats [`negotiate`] can respond with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Format {
+    /// `application/json`.
+    Json,
+    /// `text/plain`.
+    PlainText,
+    /// `text/html`.
+    ///
+    /// Requires the `html` feature.
+    #[cfg(feature = "html")]
+    Html,
+}
+
+/// Configuration for the [`negotiate`] rejection handler.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{
+///     presets::{Format, Negotiate},
+///     JsonConfig,
+/// };
+///
+/// let handler = Negotiate::new().default_format(Format::PlainText).into_handler();
+///
+/// let config = JsonConfig::new().rejection_handler(handler);
+/// # let _: JsonConfig<axum::body::Body> = config;
+/// ```
+#[derive(Debug, Clone)]
+pub struct Negotiate {
+    default_format: Format,
+    #[cfg(feature = "html")]
+    html: HtmlPage,
+}
+
+impl Default for Negotiate {
+    fn default() -> Self {
+        Self {
+            default_format: Format::Json,
+            #[cfg(feature = "html")]
+            html: HtmlPage::new(),
+        }
+    }
+}
+
+impl Negotiate {
+    /// Create a new `Negotiate`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the format used if the `Accept` header doesn't prefer one of the others.
+    ///
+    /// Defaults to [`Format::Json`].
+    pub fn default_format(mut self, format: Format) -> Self {
+        self.default_format = format;
+        self
+    }
+
+    /// Set how HTML responses are rendered.
+    ///
+    /// Requires the `html` feature.
+    #[cfg(feature = "html")]
+    pub fn html(mut self, html: HtmlPage) -> Self {
+        self.html = html;
+        self
+    }
+
+    /// Convert into a rejection handler.
+    pub fn into_handler<R, B>(
+        self,
+    ) -> impl Fn(R, RejectionContext<'_, B>) -> Response + Clone + Send + Sync + 'static
+    where
+        R: Into<RejectionParts>,
+    {
+        move |rejection, ctx| self.respond(rejection.into(), ctx)
+    }
+
+    fn respond<B>(&self, parts: RejectionParts, ctx: RejectionContext<'_, B>) -> Response {
+        match preferred_format(ctx.headers()).unwrap_or(self.default_format) {
+            Format::Json => simple_json_response(parts, ctx),
+            Format::PlainText => {
+                let title = title(&parts, ctx);
+                let body = match detail(&parts, ctx) {
+                    Some(detail) => format!("{}: {}", title, detail),
+                    None => title,
+                };
+                (parts.status(), body).into_response()
+            }
+            #[cfg(feature = "html")]
+            Format::Html => self.html.respond(parts, ctx),
+        }
+    }
+}
+
+/// The format the `Accept` header prefers, or `None` if it doesn't prefer any of them.
+fn preferred_format(headers: &HeaderMap) -> Option<Format> {
+    let mut best: Option<(Format, f32)> = None;
+
+    for (media_type, quality) in media_ranges(headers) {
+        let format = match media_type.as_str() {
+            "application/json" => Format::Json,
+            media_type
+                if media_type.starts_with("application/") && media_type.ends_with("+json") =>
+            {
+                Format::Json
+            }
+            "text/plain" | "text/*" => Format::PlainText,
+            #[cfg(feature = "html")]
+            "text/html" | "application/xhtml+xml" => Format::Html,
+            _ => continue,
+        };
+
+        // the first of equally preferred formats wins
+        if quality > 0.0 && !matches!(best, Some((_, best)) if best >= quality) {
+            best = Some((format, quality));
+        }
+    }
+
+    best.map(|(format, _)| format)
+}
+
+// The media ranges of the `Accept` header, lowercased, with their quality.
+pub(crate) fn media_ranges(headers: &HeaderMap) -> impl Iterator<Item = (String, f32)> + '_ {
+    headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|range| {
+            let mut parts = range.split(';');
+            let media_type = parts.next().unwrap_or_default().trim().to_ascii_lowercase();
+            let quality = parts
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .map_or(Some(1.0), |quality| quality.trim().parse::<f32>().ok())
+                .unwrap_or(0.0);
+            (media_type, quality)
+        })
+}
@@ -0,0 +1,354 @@
+use super::{
+    de,
+    policy::RejectionPolicy,
+    rejection::{
+        FailedToDeserializeQueryString, QueryRejection, QueryStringTooLong, TooManyQueryParams,
+    },
+    QueryConfig,
+};
+use axum::{extract::RequestParts, BoxError};
+use serde::de::DeserializeOwned;
+use std::{borrow::Cow, sync::Arc};
+
+/// The parser [`Query`](super::Query) and [`Form`](super::Form) use to deserialize query strings
+/// and form bodies.
+///
+/// Set it with [`QueryConfig::parser`] or [`FormConfig::parser`](super::FormConfig::parser).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum QueryParser {
+    /// Parse query strings with [`serde_urlencoded`].
+    ///
+    /// This is the same parser `axum::extract::Query` uses and is the default.
+    ///
+    /// [`serde_urlencoded`]: https://crates.io/crates/serde_urlencoded
+    #[default]
+    UrlEncoded,
+
+    /// Parse query strings with [`serde_html_form`].
+    ///
+    /// Unlike [`QueryParser::UrlEncoded`] this supports repeated keys, so `?tag=a&tag=b` can be
+    /// deserialized into a `Vec<String>`.
+    ///
+    /// [`serde_html_form`]: https://crates.io/crates/serde_html_form
+    #[cfg(feature = "html-form")]
+    HtmlForm,
+
+    /// Parse query strings with [`serde_qs`].
+    ///
+    /// This supports bracketed keys such as `?filter[name]=x&filter[age]=3`, which deserialize
+    /// into nested structs. If deserialization fails the rejection's message includes the
+    /// offending key, for example `` `filter[age]`: invalid digit found in string ``.
+    ///
+    /// Keys ending in `[]`, such as `?items[]=a&items[]=b`, deserialize into a `Vec`.
+    ///
+    /// In strict mode percent-encoded brackets (`%5B` and `%5D`) are not treated as brackets. Loose
+    /// mode accepts them, which is useful for clients that encode every special character. Note
+    /// that browsers percent-encode brackets in form bodies, so [`Form`](super::Form) usually needs
+    /// loose mode.
+    ///
+    /// [`serde_qs`]: https://crates.io/crates/serde_qs
+    #[cfg(feature = "qs")]
+    Qs {
+        /// Whether to use strict mode.
+        strict: bool,
+    },
+}
+
+impl QueryParser {
+    pub(super) fn deserialize<T>(
+        self,
+        input: &[u8],
+        options: &de::Options,
+    ) -> Result<T, FailedToDeserializeQueryString>
+    where
+        T: DeserializeOwned,
+    {
+        match self {
+            Self::UrlEncoded => {
+                let deserializer =
+                    serde_urlencoded::Deserializer::new(form_urlencoded::parse(input));
+                options
+                    .deserialize(deserializer)
+                    .map_err(failed_to_deserialize::<T, _>)
+            }
+            #[cfg(feature = "html-form")]
+            Self::HtmlForm => {
+                let deserializer = serde_html_form::Deserializer::from_bytes(input);
+                options
+                    .deserialize(deserializer)
+                    .map_err(failed_to_deserialize::<T, _>)
+            }
+            #[cfg(feature = "qs")]
+            Self::Qs { strict } => qs::deserialize(input, strict, options),
+        }
+    }
+}
+
+impl<B> QueryConfig<B> {
+    /// Set the parser used to deserialize query strings.
+    ///
+    /// Defaults to [`QueryParser::UrlEncoded`].
+    pub fn parser(mut self, parser: QueryParser) -> Self {
+        self.parser = parser;
+        self
+    }
+
+    /// Set whether `+` is decoded as a space.
+    ///
+    /// `application/x-www-form-urlencoded` encodes spaces as `+`, so that is the default. Clients
+    /// that strictly percent-encode spaces as `%20` might send literal `+` characters unescaped, in
+    /// which case this should be disabled so the `+` is kept as is.
+    ///
+    /// Defaults to `true`.
+    pub fn plus_as_space(mut self, plus_as_space: bool) -> Self {
+        self.plus_as_space = plus_as_space;
+        self
+    }
+
+    /// Set whether booleans can also be written as `1/0`, `on/off`, or `yes/no`.
+    ///
+    /// The values are matched case-insensitively.
+    ///
+    /// Defaults to `false`.
+    pub fn lenient_bools(mut self, lenient_bools: bool) -> Self {
+        self.options.lenient_bools = lenient_bools;
+        self
+    }
+
+    /// Set whether empty values deserialize into `None` for `Option` fields.
+    ///
+    /// For example `?name=` would deserialize into `None` for a `name: Option<u32>` field,
+    /// rather than failing because the empty string isn't a number. HTML forms submit empty
+    /// inputs as empty strings, so this is useful for optional fields in forms.
+    ///
+    /// Defaults to `false`.
+    pub fn empty_string_as_none(mut self, empty_string_as_none: bool) -> Self {
+        self.options.empty_string_as_none = empty_string_as_none;
+        self
+    }
+
+    /// Set aliases for keys, as pairs of `(alias, key)`.
+    ///
+    /// Values given for an alias populate the field named by the key. This is useful when
+    /// migrating clients between naming conventions, for example with
+    /// `key_aliases([("PageSize", "page_size")])` both `?PageSize=10` and `?page_size=10`
+    /// populate the `page_size` field.
+    ///
+    /// Defaults to no aliases.
+    pub fn key_aliases<I, K, V>(mut self, aliases: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.options.key_aliases = Arc::new(
+            aliases
+                .into_iter()
+                .map(|(alias, key)| (alias.into(), key.into()))
+                .collect(),
+        );
+        self
+    }
+
+    /// Set whether keys are matched against field names case-insensitively.
+    ///
+    /// This also applies to aliases set with [`key_aliases`](Self::key_aliases). Only ASCII
+    /// characters are compared case-insensitively.
+    ///
+    /// Defaults to `false`.
+    pub fn case_insensitive_keys(mut self, case_insensitive_keys: bool) -> Self {
+        self.options.case_insensitive_keys = case_insensitive_keys;
+        self
+    }
+
+    /// Add a parser for the values of a key.
+    ///
+    /// The parser receives the raw value and returns the string that is then deserialized into
+    /// the field. This allows accepting several formats for a field without wrapping its type in
+    /// a newtype. For example a timestamp field can accept both RFC 3339 and `YYYYMMDD`, by
+    /// converting the latter into RFC 3339 before it's given to `chrono` or `time`.
+    ///
+    /// Errors returned by the parser reject the request like other deserialization errors.
+    ///
+    /// `key` is matched against the field name, after applying
+    /// [`key_aliases`](Self::key_aliases) and [`case_insensitive_keys`](Self::case_insensitive_keys).
+    /// Adding a parser for a key that already has one replaces it.
+    pub fn scalar_parser<F, E>(mut self, key: impl Into<String>, parser: F) -> Self
+    where
+        F: Fn(&str) -> Result<String, E> + Send + Sync + 'static,
+        E: Into<BoxError>,
+    {
+        let parser: de::ScalarParser = Arc::new(move |value| parser(value).map_err(Into::into));
+        Arc::make_mut(&mut self.options.scalar_parsers).insert(key.into(), parser);
+        self
+    }
+
+    /// Set the maximum length of the query string, in bytes.
+    ///
+    /// Longer query strings are rejected with [`QueryStringTooLong`] before being deserialized.
+    ///
+    /// Defaults to no limit.
+    pub fn max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+
+    /// Set the maximum number of parameters in the query string.
+    ///
+    /// Query strings with more parameters are rejected with [`TooManyQueryParams`] before being
+    /// deserialized.
+    ///
+    /// Defaults to no limit.
+    pub fn max_params(mut self, max_params: usize) -> Self {
+        self.max_params = Some(max_params);
+        self
+    }
+
+    // Apply the limits of a `RejectionPolicy` that are specific to `Query`.
+    pub(super) fn apply_policy_limits(mut self, policy: &RejectionPolicy) -> Self {
+        self.max_length = policy.max_query_length;
+        self.max_params = policy.max_query_params;
+        self
+    }
+}
+
+pub(super) async fn extract<T, B>(
+    config: &QueryConfig<B>,
+    req: &mut RequestParts<B>,
+) -> Result<T, QueryRejection>
+where
+    T: DeserializeOwned,
+{
+    let query = req.uri().query().unwrap_or_default();
+
+    if let Some(max_length) = config.max_length {
+        if query.len() > max_length {
+            return Err(QueryStringTooLong::new(query.len(), max_length).into());
+        }
+    }
+
+    if let Some(max_params) = config.max_params {
+        let count = query.split('&').filter(|param| !param.is_empty()).count();
+        if count > max_params {
+            return Err(TooManyQueryParams::new(count, max_params).into());
+        }
+    }
+
+    let query = if config.plus_as_space {
+        Cow::Borrowed(query)
+    } else {
+        Cow::Owned(query.replace('+', "%2B"))
+    };
+
+    let value = config
+        .parser
+        .deserialize(query.as_bytes(), &config.options)?;
+    Ok(value)
+}
+
+fn failed_to_deserialize<T, E>(err: E) -> FailedToDeserializeQueryString
+where
+    E: Into<BoxError>,
+{
+    FailedToDeserializeQueryString::__private_new::<T, _>(err)
+}
+
+#[cfg(feature = "qs")]
+mod qs {
+    use super::{de, failed_to_deserialize, FailedToDeserializeQueryString};
+    use serde::{de::DeserializeOwned, Deserialize, Deserializer};
+    use serde_path_to_error::{Path, Segment};
+    use std::{cell::RefCell, fmt};
+
+    // serde_qs uses this as its default
+    const MAX_DEPTH: usize = 5;
+
+    thread_local! {
+        // serde_qs doesn't accept a `DeserializeSeed` so this is how `TrackKey` gets the options.
+        // Deserialization is synchronous so it's only set for the duration of the call to serde_qs.
+        static OPTIONS: RefCell<de::Options> = RefCell::new(de::Options::default());
+    }
+
+    pub(super) fn deserialize<T>(
+        input: &[u8],
+        strict: bool,
+        options: &de::Options,
+    ) -> Result<T, FailedToDeserializeQueryString>
+    where
+        T: DeserializeOwned,
+    {
+        let config = serde_qs::Config::new(MAX_DEPTH, strict);
+
+        let previous = OPTIONS.with(|cell| cell.replace(options.clone()));
+        let result = config.deserialize_bytes::<TrackKey<T>>(input);
+        OPTIONS.with(|cell| cell.replace(previous));
+
+        match result {
+            Ok(TrackKey(Ok(value))) => Ok(value),
+            Ok(TrackKey(Err(err))) => Err(failed_to_deserialize::<T, _>(err)),
+            Err(err) => Err(failed_to_deserialize::<T, _>(err)),
+        }
+    }
+
+    /// serde_qs doesn't expose its `Deserializer` so we cannot wrap it directly. Instead this
+    /// wraps the target type and tracks the path from within `Deserialize::deserialize`.
+    struct TrackKey<T>(Result<T, KeyError>);
+
+    impl<'de, T> Deserialize<'de> for TrackKey<T>
+    where
+        T: Deserialize<'de>,
+    {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let options = OPTIONS.with(|cell| cell.borrow().clone());
+            let mut track = serde_path_to_error::Track::new();
+            let deserializer = serde_path_to_error::Deserializer::new(deserializer, &mut track);
+            let result = options.deserialize(deserializer).map_err(|err| KeyError {
+                key: bracketed_key(&track.path()),
+                message: err.to_string(),
+            });
+            Ok(Self(result))
+        }
+    }
+
+    #[derive(Debug)]
+    struct KeyError {
+        key: String,
+        message: String,
+    }
+
+    impl fmt::Display for KeyError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            if self.key.is_empty() {
+                write!(f, "{}", self.message)
+            } else {
+                write!(f, "`{}`: {}", self.key, self.message)
+            }
+        }
+    }
+
+    impl std::error::Error for KeyError {}
+
+    fn bracketed_key(path: &Path) -> String {
+        let mut key = String::new();
+        for segment in path.iter() {
+            let segment = match segment {
+                Segment::Seq { index } => index.to_string(),
+                Segment::Map { key } => key.clone(),
+                Segment::Enum { variant } => variant.clone(),
+                Segment::Unknown => "?".to_owned(),
+            };
+            if key.is_empty() {
+                key = segment;
+            } else {
+                key.push('[');
+                key.push_str(&segment);
+                key.push(']');
+            }
+        }
+        key
+    }
+}
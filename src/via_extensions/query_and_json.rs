@@ -0,0 +1,83 @@
+use super::{
+    rejection::{ConfiguredRejection, QueryRejection},
+    Json, Query,
+};
+use axum::{
+    async_trait,
+    body::{Bytes, HttpBody},
+    extract::{rejection::JsonRejection, FromRequest, RequestParts},
+    response::{IntoResponse, Response},
+    BoxError,
+};
+use serde::de::DeserializeOwned;
+
+/// Extractor that deserializes the query string like [`Query`] and the body like [`Json`].
+///
+/// This is useful for endpoints that take parameters such as pagination from the query string and
+/// the payload from the body. The query string is extracted first, and each part is rejected with
+/// its own config, [`QueryConfig`](super::QueryConfig) or [`JsonConfig`](super::JsonConfig).
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::QueryAndJson;
+/// use axum::{routing::post, Router};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Pagination {
+///     page: u32,
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Search {
+///     text: String,
+/// }
+///
+/// async fn handler(QueryAndJson(pagination, search): QueryAndJson<Pagination, Search>) {}
+///
+/// let app = Router::new().route("/search", post(handler));
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct QueryAndJson<Q, T>(pub Q, pub T);
+
+#[async_trait]
+impl<Q, T, B> FromRequest<B> for QueryAndJson<Q, T>
+where
+    Q: DeserializeOwned + Send + 'static,
+    T: DeserializeOwned + Send + 'static,
+    B: HttpBody<Data = Bytes> + Send + 'static,
+    B::Error: Into<BoxError>,
+{
+    type Rejection = QueryAndJsonRejection;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let Query(query) = Query::from_request(req)
+            .await
+            .map_err(QueryAndJsonRejection::Query)?;
+        let Json(body) = Json::from_request(req)
+            .await
+            .map_err(QueryAndJsonRejection::Json)?;
+        Ok(Self(query, body))
+    }
+}
+
+/// Rejection used for [`QueryAndJson`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum QueryAndJsonRejection {
+    /// The query string was rejected.
+    Query(ConfiguredRejection<QueryRejection>),
+    /// The body was rejected.
+    Json(ConfiguredRejection<JsonRejection>),
+}
+
+impl IntoResponse for QueryAndJsonRejection {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Query(rejection) => rejection.into_response(),
+            Self::Json(rejection) => rejection.into_response(),
+        }
+    }
+}
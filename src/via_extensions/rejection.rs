@@ -0,0 +1,1405 @@
+//! Rejection response types.
+//!
+//! Extractors in this module can fail in more ways than the ones in axum, so some of them use
+//! the rejection types defined here rather than the ones from `axum::extract::rejection`.
+
+use super::{context::HandledRejection, RejectionInfo};
+use axum::{
+    body::Bytes,
+    extract::rejection::{
+        BodyAlreadyExtracted, FailedToBufferBody, JsonDataError, JsonSyntaxError, LengthLimitError,
+        MissingJsonContentType, UnknownBodyError,
+    },
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use http_body::SizeHint;
+use serde::{Deserialize, Serialize, Serializer};
+use std::{
+    borrow::Cow,
+    convert::Infallible,
+    fmt,
+    pin::Pin,
+    sync::{Arc, OnceLock},
+    task::{Context, Poll},
+    time::Duration,
+};
+
+pub use axum::extract::rejection::{
+    BytesRejection, FailedToDeserializeQueryString, InvalidFormContentType, JsonRejection,
+};
+
+macro_rules! composite_rejection {
+    (
+        $(#[$m:meta])*
+        pub enum $name:ident {
+            $($variant:ident),+
+            $(,)?
+        }
+    ) => {
+        $(#[$m])*
+        #[derive(Debug)]
+        #[non_exhaustive]
+        pub enum $name {
+            $(
+                #[allow(missing_docs)]
+                $variant($variant)
+            ),+
+        }
+
+        impl IntoResponse for $name {
+            fn into_response(self) -> Response {
+                match self {
+                    $(
+                        Self::$variant(inner) => inner.into_response(),
+                    )+
+                }
+            }
+        }
+
+        $(
+            impl From<$variant> for $name {
+                fn from(inner: $variant) -> Self {
+                    Self::$variant(inner)
+                }
+            }
+        )+
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                match self {
+                    $(
+                        Self::$variant(inner) => write!(f, "{}", inner),
+                    )+
+                }
+            }
+        }
+
+        impl std::error::Error for $name {
+            fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+                match self {
+                    $(
+                        Self::$variant(inner) => Some(inner),
+                    )+
+                }
+            }
+        }
+
+        impl RejectionStatus for $name {
+            fn status(&self) -> StatusCode {
+                match self {
+                    $(
+                        Self::$variant(inner) => inner.status(),
+                    )+
+                }
+            }
+        }
+
+        impl RejectionBody for $name {
+            fn body(&self) -> String {
+                match self {
+                    $(
+                        Self::$variant(inner) => inner.body(),
+                    )+
+                }
+            }
+        }
+
+        impl StaticMessage for $name {
+            fn static_message(&self) -> Option<&'static str> {
+                match self {
+                    $(
+                        Self::$variant(inner) => inner.static_message(),
+                    )+
+                }
+            }
+        }
+    };
+}
+
+/// Rejections whose status code is known without building their default response.
+///
+/// Use [`status_of`] to get it.
+pub trait RejectionStatus {
+    /// The status code of the rejection's default response.
+    fn status(&self) -> StatusCode;
+}
+
+/// Get the status code of a rejection's default response.
+///
+/// Unlike `rejection.into_response().status()` this doesn't format the rejection's message, so
+/// rejection handlers that write their own body can use it on the hot path.
+///
+/// This is the status the extractors in this module respond with, which isn't always the one of
+/// axum's response for the rejection. Bodies that took longer than
+/// [`JsonConfig::body_timeout`](super::JsonConfig::body_timeout) are rejected with
+/// `408 Request Timeout`, while axum responds to any error reading the body with
+/// `400 Bad Request`.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{
+///     rejection::status_of, JsonConfig, RejectionContext,
+/// };
+/// use axum::{
+///     extract::rejection::JsonRejection,
+///     response::{IntoResponse, Response},
+/// };
+///
+/// fn rejection_handler<B>(rejection: JsonRejection, ctx: RejectionContext<'_, B>) -> Response {
+///     (status_of(&rejection), ctx.kind().as_str()).into_response()
+/// }
+///
+/// let config = JsonConfig::new().rejection_handler(rejection_handler);
+/// # let _: JsonConfig<axum::body::Body> = config;
+/// ```
+pub fn status_of<R>(rejection: &R) -> StatusCode
+where
+    R: RejectionStatus + ?Sized,
+{
+    rejection.status()
+}
+
+// Rejections whose default response can be built without consuming them, so they can be kept
+// in the response's `RejectionInfo`.
+pub(crate) trait RejectionBody: RejectionStatus + std::error::Error {
+    // The body of the default response. Like axum's rejections it's the message followed by the
+    // underlying error, if any.
+    fn body(&self) -> String
+    where
+        Self: Sized,
+    {
+        // skip the layers of composite rejections, which have the message of their variant
+        let message = self.to_string();
+        let mut rejection: &dyn std::error::Error = self;
+        while let Some(source) = rejection.source() {
+            if source.to_string() != message {
+                return format!("{}: {}", message, source);
+            }
+            rejection = source;
+        }
+        message
+    }
+}
+
+// Build the default response of `rejection` and keep it in `info`. Neither its body nor its
+// message is formatted until it's read.
+pub(crate) fn render_into_info<R>(rejection: R, info: &mut RejectionInfo) -> Response
+where
+    R: RejectionBody + Send + Sync + 'static,
+{
+    let rejection = Arc::new(rejection);
+    let response = Response::builder()
+        .status(rejection.status())
+        .header(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("text/plain; charset=utf-8"),
+        )
+        .body(axum::body::boxed(LazyBody {
+            rejection: rejection.clone(),
+            body: OnceLock::new(),
+            done: false,
+        }))
+        .unwrap();
+    info.set_shared_source(rejection);
+    response
+}
+
+// The body of a default response, rendered when it's first polled or its size is asked for, so
+// it's never rendered if the response is replaced, for example by middleware.
+struct LazyBody<R> {
+    rejection: Arc<R>,
+    body: OnceLock<Bytes>,
+    done: bool,
+}
+
+impl<R> LazyBody<R>
+where
+    R: RejectionBody,
+{
+    fn rendered(&self) -> &Bytes {
+        self.body.get_or_init(|| Bytes::from(self.rejection.body()))
+    }
+}
+
+impl<R> http_body::Body for LazyBody<R>
+where
+    R: RejectionBody,
+{
+    type Data = Bytes;
+    type Error = Infallible;
+
+    fn poll_data(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        if self.done {
+            return Poll::Ready(None);
+        }
+        self.done = true;
+        Poll::Ready(Some(Ok(self.rendered().clone())))
+    }
+
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        Poll::Ready(Ok(None))
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.done
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        if self.done {
+            SizeHint::with_exact(0)
+        } else {
+            SizeHint::with_exact(self.rendered().len() as u64)
+        }
+    }
+}
+
+// The message of a rejection if it's the same for every rejection of its type, formatted once per
+// type. Lets the message be kept when a rejection handler takes the rejection, without formatting
+// it for every request.
+pub(crate) trait StaticMessage {
+    fn static_message(&self) -> Option<&'static str>;
+}
+
+macro_rules! static_message {
+    ($($ty:ty),+ $(,)?) => {
+        $(
+            impl StaticMessage for $ty {
+                fn static_message(&self) -> Option<&'static str> {
+                    static MESSAGE: OnceLock<String> = OnceLock::new();
+                    Some(MESSAGE.get_or_init(|| self.to_string()))
+                }
+            }
+        )+
+    };
+}
+
+static_message!(
+    JsonDataError,
+    JsonSyntaxError,
+    MissingJsonContentType,
+    BodyAlreadyExtracted,
+    LengthLimitError,
+    UnknownBodyError,
+    InvalidFormContentType,
+    QueryStringTooLong,
+    TooManyQueryParams,
+    FormBodyTooLarge,
+    UnsupportedFormCharset,
+    InvalidUtf8Body,
+);
+
+// its message contains the deserializer's error
+impl StaticMessage for FailedToDeserializeQueryString {
+    fn static_message(&self) -> Option<&'static str> {
+        None
+    }
+}
+
+impl StaticMessage for JsonRejection {
+    fn static_message(&self) -> Option<&'static str> {
+        match self {
+            Self::JsonDataError(inner) => inner.static_message(),
+            Self::JsonSyntaxError(inner) => inner.static_message(),
+            Self::MissingJsonContentType(inner) => inner.static_message(),
+            Self::BytesRejection(inner) => inner.static_message(),
+            _ => None,
+        }
+    }
+}
+
+impl StaticMessage for BytesRejection {
+    fn static_message(&self) -> Option<&'static str> {
+        match self {
+            Self::BodyAlreadyExtracted(inner) => inner.static_message(),
+            Self::FailedToBufferBody(FailedToBufferBody::LengthLimitError(inner)) => {
+                inner.static_message()
+            }
+            Self::FailedToBufferBody(FailedToBufferBody::UnknownBodyError(inner)) => {
+                inner.static_message()
+            }
+            _ => None,
+        }
+    }
+}
+
+impl StaticMessage for AnyRejection {
+    fn static_message(&self) -> Option<&'static str> {
+        match self {
+            Self::Json(inner) => inner.static_message(),
+            Self::Query(inner) => inner.static_message(),
+            Self::Form(inner) => inner.static_message(),
+        }
+    }
+}
+
+// Keep the message of `rejection` in `info` before a rejection handler takes it. Only messages
+// that differ between rejections of the same type are formatted here.
+pub(crate) fn keep_message<R>(rejection: &R, info: &mut RejectionInfo)
+where
+    R: StaticMessage + fmt::Display,
+{
+    match rejection.static_message() {
+        Some(message) => info.set_source(HandledRejection(message)),
+        None => info.format_message(rejection),
+    }
+}
+
+impl RejectionBody for JsonRejection {}
+
+impl RejectionBody for BytesRejection {}
+
+impl RejectionBody for FailedToDeserializeQueryString {}
+
+impl RejectionBody for InvalidFormContentType {}
+
+impl RejectionStatus for JsonRejection {
+    fn status(&self) -> StatusCode {
+        match self {
+            Self::JsonDataError(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::JsonSyntaxError(_) => StatusCode::BAD_REQUEST,
+            Self::MissingJsonContentType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            Self::BytesRejection(inner) => inner.status(),
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl RejectionStatus for BytesRejection {
+    fn status(&self) -> StatusCode {
+        match self {
+            Self::FailedToBufferBody(FailedToBufferBody::LengthLimitError(_)) => {
+                StatusCode::PAYLOAD_TOO_LARGE
+            }
+            Self::FailedToBufferBody(FailedToBufferBody::UnknownBodyError(_))
+                if has_source::<BodyTimeout>(self) =>
+            {
+                StatusCode::REQUEST_TIMEOUT
+            }
+            Self::FailedToBufferBody(FailedToBufferBody::UnknownBodyError(_)) => {
+                StatusCode::BAD_REQUEST
+            }
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl RejectionStatus for BodyAlreadyExtracted {
+    fn status(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
+
+impl RejectionStatus for FailedToDeserializeQueryString {
+    fn status(&self) -> StatusCode {
+        StatusCode::UNPROCESSABLE_ENTITY
+    }
+}
+
+impl RejectionStatus for InvalidFormContentType {
+    fn status(&self) -> StatusCode {
+        StatusCode::UNSUPPORTED_MEDIA_TYPE
+    }
+}
+
+/// Rejection type for [`Query`](super::Query) used if the query string is longer than
+/// [`QueryConfig::max_length`](super::QueryConfig::max_length).
+#[derive(Debug)]
+pub struct QueryStringTooLong {
+    length: usize,
+    max_length: usize,
+}
+
+impl QueryStringTooLong {
+    pub(crate) fn new(length: usize, max_length: usize) -> Self {
+        Self { length, max_length }
+    }
+
+    /// The length of the query string, in bytes.
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    /// The maximum length that was allowed, in bytes.
+    pub fn max_length(&self) -> usize {
+        self.max_length
+    }
+}
+
+impl IntoResponse for QueryStringTooLong {
+    fn into_response(self) -> Response {
+        (self.status(), self.body()).into_response()
+    }
+}
+
+impl RejectionBody for QueryStringTooLong {
+    fn body(&self) -> String {
+        format!(
+            "{}: {} bytes exceeds the limit of {} bytes",
+            self, self.length, self.max_length
+        )
+    }
+}
+
+impl fmt::Display for QueryStringTooLong {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Query string is too long")
+    }
+}
+
+impl std::error::Error for QueryStringTooLong {}
+
+impl RejectionStatus for QueryStringTooLong {
+    fn status(&self) -> StatusCode {
+        StatusCode::URI_TOO_LONG
+    }
+}
+
+/// Rejection type for [`Query`](super::Query) used if the query string contains more parameters
+/// than [`QueryConfig::max_params`](super::QueryConfig::max_params).
+#[derive(Debug)]
+pub struct TooManyQueryParams {
+    count: usize,
+    max_params: usize,
+}
+
+impl TooManyQueryParams {
+    pub(crate) fn new(count: usize, max_params: usize) -> Self {
+        Self { count, max_params }
+    }
+
+    /// The number of parameters in the query string.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// The maximum number of parameters that was allowed.
+    pub fn max_params(&self) -> usize {
+        self.max_params
+    }
+}
+
+impl IntoResponse for TooManyQueryParams {
+    fn into_response(self) -> Response {
+        (self.status(), self.body()).into_response()
+    }
+}
+
+impl RejectionBody for TooManyQueryParams {
+    fn body(&self) -> String {
+        format!(
+            "{}: {} parameters exceeds the limit of {}",
+            self, self.count, self.max_params
+        )
+    }
+}
+
+impl fmt::Display for TooManyQueryParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Query string has too many parameters")
+    }
+}
+
+impl std::error::Error for TooManyQueryParams {}
+
+impl RejectionStatus for TooManyQueryParams {
+    fn status(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+}
+
+/// Error returned by [`BorrowedJson::deserialize`](super::BorrowedJson::deserialize).
+///
+/// Responds like the rejections of [`Json`](super::Json), with `400 Bad Request` for bodies that
+/// aren't valid JSON and `422 Unprocessable Entity` for bodies that don't match the target type.
+#[derive(Debug)]
+pub struct JsonDeserializeError {
+    pointer: String,
+    error: serde_json::Error,
+}
+
+impl JsonDeserializeError {
+    pub(crate) fn new(pointer: String, error: serde_json::Error) -> Self {
+        Self { pointer, error }
+    }
+
+    /// The [JSON Pointer](https://www.rfc-editor.org/rfc/rfc6901) to the element that failed to
+    /// deserialize.
+    ///
+    /// This is empty if the error is about the body as a whole.
+    pub fn pointer(&self) -> &str {
+        &self.pointer
+    }
+
+    /// Whether the body isn't valid JSON, as opposed to not matching the target type.
+    pub fn is_syntax_error(&self) -> bool {
+        !self.error.is_data()
+    }
+}
+
+impl IntoResponse for JsonDeserializeError {
+    fn into_response(self) -> Response {
+        (self.status(), format!("{}: {}", self, self.error)).into_response()
+    }
+}
+
+impl RejectionStatus for JsonDeserializeError {
+    fn status(&self) -> StatusCode {
+        if self.is_syntax_error() {
+            StatusCode::BAD_REQUEST
+        } else {
+            StatusCode::UNPROCESSABLE_ENTITY
+        }
+    }
+}
+
+impl fmt::Display for JsonDeserializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_syntax_error() {
+            write!(f, "Failed to parse the request body as JSON")
+        } else {
+            write!(
+                f,
+                "Failed to deserialize the JSON body into the target type"
+            )
+        }
+    }
+}
+
+impl std::error::Error for JsonDeserializeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Error decompressing a body with [`Decompression`](super::Decompression).
+///
+/// It's the source of the `FailedToBufferBody::UnknownBodyError` the body is rejected with.
+#[derive(Debug)]
+pub struct BodyDecodeError {
+    unsupported_encoding: Option<String>,
+    error: Option<std::io::Error>,
+}
+
+impl BodyDecodeError {
+    pub(crate) fn unsupported(encoding: String) -> Self {
+        Self {
+            unsupported_encoding: Some(encoding),
+            error: None,
+        }
+    }
+
+    pub(crate) fn invalid(error: std::io::Error) -> Self {
+        Self {
+            unsupported_encoding: None,
+            error: Some(error),
+        }
+    }
+
+    /// The `Content-Encoding` that isn't supported, if that's why decompressing failed.
+    pub fn unsupported_encoding(&self) -> Option<&str> {
+        self.unsupported_encoding.as_deref()
+    }
+}
+
+impl fmt::Display for BodyDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.unsupported_encoding {
+            Some(encoding) => write!(f, "Unsupported `Content-Encoding`: `{}`", encoding),
+            None => write!(f, "Failed to decompress the request body"),
+        }
+    }
+}
+
+impl std::error::Error for BodyDecodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.error.as_ref().map(|error| error as _)
+    }
+}
+
+/// Rejection type used if a body isn't valid UTF-8 and the config's
+/// [`Utf8Policy`](super::Utf8Policy) is [`Reject`](super::Utf8Policy::Reject).
+///
+/// [`Form`](super::Form) is rejected with it directly, while for [`Json`](super::Json) it's the
+/// source of the `FailedToBufferBody::UnknownBodyError` the body is rejected with. Both have the
+/// status `400 Bad Request`.
+#[derive(Debug)]
+pub struct InvalidUtf8Body {
+    valid_up_to: usize,
+}
+
+impl InvalidUtf8Body {
+    pub(crate) fn new(valid_up_to: usize) -> Self {
+        Self { valid_up_to }
+    }
+
+    /// The length of the valid UTF-8 before the first invalid byte, in bytes.
+    ///
+    /// For forms this is counted after percent-decoding.
+    pub fn valid_up_to(&self) -> usize {
+        self.valid_up_to
+    }
+}
+
+impl IntoResponse for InvalidUtf8Body {
+    fn into_response(self) -> Response {
+        (self.status(), self.body()).into_response()
+    }
+}
+
+impl RejectionBody for InvalidUtf8Body {
+    fn body(&self) -> String {
+        format!("{}: invalid byte after {} bytes", self, self.valid_up_to)
+    }
+}
+
+impl fmt::Display for InvalidUtf8Body {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Request body didn't contain valid UTF-8")
+    }
+}
+
+impl std::error::Error for InvalidUtf8Body {}
+
+impl RejectionStatus for InvalidUtf8Body {
+    fn status(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+}
+
+/// Error reading a body for longer than
+/// [`JsonConfig::body_timeout`](super::JsonConfig::body_timeout).
+///
+/// It's the source of the `FailedToBufferBody::UnknownBodyError` the body is rejected with.
+#[derive(Debug)]
+pub struct BodyTimeout {
+    timeout: Duration,
+}
+
+impl BodyTimeout {
+    pub(crate) fn new(timeout: Duration) -> Self {
+        Self { timeout }
+    }
+
+    /// The timeout that was exceeded.
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+}
+
+impl fmt::Display for BodyTimeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Timed out reading the request body")
+    }
+}
+
+impl std::error::Error for BodyTimeout {}
+
+/// Rejection type for [`Form`](super::Form) used if the request body is larger than
+/// [`FormConfig::max_body_size`](super::FormConfig::max_body_size).
+#[derive(Debug)]
+pub struct FormBodyTooLarge {
+    max_size: usize,
+}
+
+impl FormBodyTooLarge {
+    pub(crate) fn new(max_size: usize) -> Self {
+        Self { max_size }
+    }
+
+    /// The maximum size that was allowed, in bytes.
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+}
+
+impl IntoResponse for FormBodyTooLarge {
+    fn into_response(self) -> Response {
+        (self.status(), self.body()).into_response()
+    }
+}
+
+impl RejectionBody for FormBodyTooLarge {
+    fn body(&self) -> String {
+        format!("{}: exceeds the limit of {} bytes", self, self.max_size)
+    }
+}
+
+impl fmt::Display for FormBodyTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Form body is too large")
+    }
+}
+
+impl std::error::Error for FormBodyTooLarge {}
+
+impl RejectionStatus for FormBodyTooLarge {
+    fn status(&self) -> StatusCode {
+        StatusCode::PAYLOAD_TOO_LARGE
+    }
+}
+
+/// Rejection type for [`Form`](super::Form) used if the body's `charset` isn't supported.
+///
+/// Requires the `charset` feature, without which the `charset` is ignored.
+#[derive(Debug)]
+pub struct UnsupportedFormCharset {
+    charset: String,
+}
+
+impl UnsupportedFormCharset {
+    #[cfg_attr(not(feature = "charset"), allow(dead_code))]
+    pub(crate) fn new(charset: String) -> Self {
+        Self { charset }
+    }
+
+    /// The `charset` from the `Content-Type` header.
+    pub fn charset(&self) -> &str {
+        &self.charset
+    }
+}
+
+impl IntoResponse for UnsupportedFormCharset {
+    fn into_response(self) -> Response {
+        (self.status(), self.body()).into_response()
+    }
+}
+
+impl RejectionBody for UnsupportedFormCharset {
+    fn body(&self) -> String {
+        format!("{}: `{}`", self, self.charset)
+    }
+}
+
+impl fmt::Display for UnsupportedFormCharset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Form body has an unsupported charset")
+    }
+}
+
+impl std::error::Error for UnsupportedFormCharset {}
+
+impl RejectionStatus for UnsupportedFormCharset {
+    fn status(&self) -> StatusCode {
+        StatusCode::UNSUPPORTED_MEDIA_TYPE
+    }
+}
+
+composite_rejection! {
+    /// Rejection used for [`Query`](super::Query).
+    ///
+    /// Contains one variant for each way the [`Query`](super::Query) extractor can fail.
+    pub enum QueryRejection {
+        FailedToDeserializeQueryString,
+        QueryStringTooLong,
+        TooManyQueryParams,
+    }
+}
+
+composite_rejection! {
+    /// Rejection used for [`Form`](super::Form).
+    ///
+    /// Contains one variant for each way the [`Form`](super::Form) extractor can fail.
+    pub enum FormRejection {
+        InvalidFormContentType,
+        FailedToDeserializeQueryString,
+        BytesRejection,
+        FormBodyTooLarge,
+        UnsupportedFormCharset,
+        InvalidUtf8Body,
+    }
+}
+
+/// Rejection used for [`GlobalRejectionConfig`](super::GlobalRejectionConfig).
+///
+/// Contains one variant for each extractor in this module.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum AnyRejection {
+    #[allow(missing_docs)]
+    Json(JsonRejection),
+    #[allow(missing_docs)]
+    Query(QueryRejection),
+    #[allow(missing_docs)]
+    Form(FormRejection),
+}
+
+impl IntoResponse for AnyRejection {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Json(inner) => inner.into_response(),
+            Self::Query(inner) => inner.into_response(),
+            Self::Form(inner) => inner.into_response(),
+        }
+    }
+}
+
+impl RejectionStatus for AnyRejection {
+    fn status(&self) -> StatusCode {
+        match self {
+            Self::Json(inner) => inner.status(),
+            Self::Query(inner) => inner.status(),
+            Self::Form(inner) => inner.status(),
+        }
+    }
+}
+
+impl From<JsonRejection> for AnyRejection {
+    fn from(inner: JsonRejection) -> Self {
+        Self::Json(inner)
+    }
+}
+
+impl From<QueryRejection> for AnyRejection {
+    fn from(inner: QueryRejection) -> Self {
+        Self::Query(inner)
+    }
+}
+
+impl From<FormRejection> for AnyRejection {
+    fn from(inner: FormRejection) -> Self {
+        Self::Form(inner)
+    }
+}
+
+impl fmt::Display for AnyRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(inner) => write!(f, "{}", inner),
+            Self::Query(inner) => write!(f, "{}", inner),
+            Self::Form(inner) => write!(f, "{}", inner),
+        }
+    }
+}
+
+impl std::error::Error for AnyRejection {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Json(inner) => Some(inner),
+            Self::Query(inner) => Some(inner),
+            Self::Form(inner) => Some(inner),
+        }
+    }
+}
+
+/// The parts of a rejection, for building custom rejection responses.
+///
+/// Rejections wrap the errors that caused them, sometimes several layers deep, so getting at the
+/// underlying message means walking [`Error::source`](std::error::Error::source). This does that
+/// once, so rejection handlers don't have to.
+///
+/// The messages are only formatted the first time [`title`](Self::title),
+/// [`detail`](Self::detail) or [`source_chain`](Self::source_chain) is called, so handlers that
+/// only need the [`status`](Self::status) don't pay for them.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{
+///     rejection::RejectionParts, JsonConfig, RejectionContext,
+/// };
+/// use axum::{
+///     extract::rejection::JsonRejection,
+///     response::{IntoResponse, Response},
+///     Json,
+/// };
+/// use serde_json::json;
+///
+/// fn rejection_handler<B>(rejection: JsonRejection, _ctx: RejectionContext<'_, B>) -> Response {
+///     let parts = RejectionParts::from(rejection);
+///     let body = json!({
+///         "message": parts.title(),
+///         "details": parts.detail(),
+///     });
+///     (parts.status(), Json(body)).into_response()
+/// }
+///
+/// let config = JsonConfig::new().rejection_handler(rejection_handler);
+/// # let _: JsonConfig<axum::body::Body> = config;
+/// ```
+#[derive(Clone)]
+pub struct RejectionParts {
+    status: StatusCode,
+    rejection: Arc<dyn std::error::Error + Send + Sync>,
+    messages: Arc<OnceLock<Messages>>,
+}
+
+// The messages of a rejection, formatted on first use.
+struct Messages {
+    title: String,
+    source_chain: Vec<String>,
+}
+
+impl RejectionParts {
+    fn new<R>(rejection: R) -> Self
+    where
+        R: std::error::Error + RejectionStatus + Send + Sync + 'static,
+    {
+        Self {
+            status: rejection.status(),
+            rejection: Arc::new(rejection),
+            messages: Arc::default(),
+        }
+    }
+
+    fn messages(&self) -> &Messages {
+        self.messages.get_or_init(|| {
+            let rejection = &*self.rejection;
+            let mut title = rejection.to_string();
+
+            // Composite rejections and `axum::Error` display the same message as the error they wrap,
+            // so skip those to only keep the messages that add something.
+            let mut source_chain = Vec::<String>::new();
+            let mut source = rejection.source();
+            while let Some(err) = source {
+                let message = err.to_string();
+                let last = source_chain.last().unwrap_or(&title);
+                if message != *last {
+                    source_chain.push(message);
+                }
+                source = err.source();
+            }
+
+            if let Some((message, detail)) = split_query_string_message(&title) {
+                source_chain.push(detail.to_owned());
+                title = message.to_owned();
+            }
+
+            Messages {
+                title,
+                source_chain,
+            }
+        })
+    }
+
+    /// The status code of the rejection's default response.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// The rejection's message, as returned by its `Display` implementation.
+    ///
+    /// For example `"Failed to deserialize the JSON body into the target type"`.
+    pub fn title(&self) -> &str {
+        &self.messages().title
+    }
+
+    /// The message of the innermost error that caused the rejection, if any.
+    ///
+    /// For example `"invalid type: string \"foo\", expected u32 at line 1 column 11"`.
+    pub fn detail(&self) -> Option<&str> {
+        self.messages().source_chain.last().map(String::as_str)
+    }
+
+    /// The messages of the errors that caused the rejection, from outermost to innermost.
+    ///
+    /// Messages identical to the one before them are omitted.
+    pub fn source_chain(&self) -> &[String] {
+        &self.messages().source_chain
+    }
+}
+
+impl fmt::Debug for RejectionParts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RejectionParts")
+            .field("status", &self.status)
+            .field("title", &self.title())
+            .field("source_chain", &self.source_chain())
+            .finish()
+    }
+}
+
+// `FailedToDeserializeQueryString` includes the underlying error in its message, rather than
+// returning it from `source`, so split it off.
+pub(crate) fn split_query_string_message(message: &str) -> Option<(&str, &str)> {
+    const MESSAGE: &str = "Failed to deserialize query string";
+    let detail = message.strip_prefix(MESSAGE)?.strip_prefix(": ")?;
+    Some((MESSAGE, detail))
+}
+
+impl From<JsonRejection> for RejectionParts {
+    fn from(rejection: JsonRejection) -> Self {
+        Self::new(rejection)
+    }
+}
+
+impl From<QueryRejection> for RejectionParts {
+    fn from(rejection: QueryRejection) -> Self {
+        Self::new(rejection)
+    }
+}
+
+impl From<FormRejection> for RejectionParts {
+    fn from(rejection: FormRejection) -> Self {
+        Self::new(rejection)
+    }
+}
+
+impl From<AnyRejection> for RejectionParts {
+    fn from(rejection: AnyRejection) -> Self {
+        Self::new(rejection)
+    }
+}
+
+/// The kinds of rejections, independent of the extractor that produced them.
+///
+/// Used to map rejections to application error codes with `error_code` on the configs, such as
+/// [`JsonConfig::error_code`](super::JsonConfig::error_code).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum RejectionKind {
+    /// The request's `Content-Type` is missing or not supported by the extractor.
+    InvalidContentType,
+    /// The body isn't syntactically valid, for example JSON with a missing bracket.
+    SyntaxError,
+    /// The input is syntactically valid but doesn't match the target type.
+    DeserializeError,
+    /// The body couldn't be buffered.
+    BufferBody,
+    /// The body's `Content-Encoding` isn't supported or the body failed to decompress.
+    BodyDecodeError,
+    /// Reading the body took longer than the configured timeout.
+    BodyTimeout,
+    /// The body is larger than the configured limit.
+    BodyTooLarge,
+    /// The body's `charset` isn't supported.
+    UnsupportedCharset,
+    /// The body isn't valid UTF-8 and the [`Utf8Policy`](super::Utf8Policy) rejects it.
+    InvalidUtf8,
+    /// The query string is longer than the configured limit.
+    QueryStringTooLong,
+    /// The query string has more parameters than the configured limit.
+    TooManyQueryParams,
+    /// A rejection that doesn't fit any of the other kinds.
+    Other,
+}
+
+impl RejectionKind {
+    /// The kind in kebab case, such as `"deserialize-error"`.
+    ///
+    /// This is used in the URLs of the documentation links and in `type` of the
+    /// [Problem Details](super::presets::problem_details) preset.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::InvalidContentType => "invalid-content-type",
+            Self::SyntaxError => "syntax-error",
+            Self::DeserializeError => "deserialize-error",
+            Self::BufferBody => "buffer-body",
+            Self::BodyDecodeError => "body-decode-error",
+            Self::BodyTimeout => "body-timeout",
+            Self::BodyTooLarge => "body-too-large",
+            Self::UnsupportedCharset => "unsupported-charset",
+            Self::InvalidUtf8 => "invalid-utf8",
+            Self::QueryStringTooLong => "query-string-too-long",
+            Self::TooManyQueryParams => "too-many-query-params",
+            Self::Other => "other",
+        }
+    }
+}
+
+impl From<&JsonRejection> for RejectionKind {
+    fn from(rejection: &JsonRejection) -> Self {
+        match rejection {
+            JsonRejection::MissingJsonContentType(_) => Self::InvalidContentType,
+            JsonRejection::JsonSyntaxError(_) => Self::SyntaxError,
+            JsonRejection::JsonDataError(_) => Self::DeserializeError,
+            JsonRejection::BytesRejection(BytesRejection::FailedToBufferBody(
+                FailedToBufferBody::LengthLimitError(_),
+            )) => Self::BodyTooLarge,
+            JsonRejection::BytesRejection(inner) if has_source::<BodyDecodeError>(inner) => {
+                Self::BodyDecodeError
+            }
+            JsonRejection::BytesRejection(inner) if has_source::<BodyTimeout>(inner) => {
+                Self::BodyTimeout
+            }
+            JsonRejection::BytesRejection(inner) if has_source::<InvalidUtf8Body>(inner) => {
+                Self::InvalidUtf8
+            }
+            JsonRejection::BytesRejection(_) => Self::BufferBody,
+            _ => Self::Other,
+        }
+    }
+}
+
+// Whether buffering failed because of an error of type `E`.
+fn has_source<E>(rejection: &BytesRejection) -> bool
+where
+    E: std::error::Error + 'static,
+{
+    let mut source = std::error::Error::source(rejection);
+    while let Some(err) = source {
+        if err.is::<E>() {
+            return true;
+        }
+        source = err.source();
+    }
+    false
+}
+
+impl From<&QueryRejection> for RejectionKind {
+    fn from(rejection: &QueryRejection) -> Self {
+        match rejection {
+            QueryRejection::FailedToDeserializeQueryString(_) => Self::DeserializeError,
+            QueryRejection::QueryStringTooLong(_) => Self::QueryStringTooLong,
+            QueryRejection::TooManyQueryParams(_) => Self::TooManyQueryParams,
+        }
+    }
+}
+
+impl From<&FormRejection> for RejectionKind {
+    fn from(rejection: &FormRejection) -> Self {
+        match rejection {
+            FormRejection::InvalidFormContentType(_) => Self::InvalidContentType,
+            FormRejection::FailedToDeserializeQueryString(_) => Self::DeserializeError,
+            FormRejection::BytesRejection(_) => Self::BufferBody,
+            FormRejection::FormBodyTooLarge(_) => Self::BodyTooLarge,
+            FormRejection::UnsupportedFormCharset(_) => Self::UnsupportedCharset,
+            FormRejection::InvalidUtf8Body(_) => Self::InvalidUtf8,
+        }
+    }
+}
+
+impl From<&AnyRejection> for RejectionKind {
+    fn from(rejection: &AnyRejection) -> Self {
+        match rejection {
+            AnyRejection::Json(inner) => inner.into(),
+            AnyRejection::Query(inner) => inner.into(),
+            AnyRejection::Form(inner) => inner.into(),
+        }
+    }
+}
+
+/// An application error code, such as `"INVALID_BODY"` or `4001`.
+///
+/// Serializes as a plain string or integer.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    #[allow(missing_docs)]
+    String(Cow<'static, str>),
+    #[allow(missing_docs)]
+    Int(i64),
+}
+
+impl From<&'static str> for ErrorCode {
+    fn from(code: &'static str) -> Self {
+        Self::String(Cow::Borrowed(code))
+    }
+}
+
+impl From<String> for ErrorCode {
+    fn from(code: String) -> Self {
+        Self::String(Cow::Owned(code))
+    }
+}
+
+impl From<i64> for ErrorCode {
+    fn from(code: i64) -> Self {
+        Self::Int(code)
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::String(code) => write!(f, "{}", code),
+            Self::Int(code) => write!(f, "{}", code),
+        }
+    }
+}
+
+impl Serialize for ErrorCode {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Self::String(code) => serializer.serialize_str(code),
+            Self::Int(code) => serializer.serialize_i64(*code),
+        }
+    }
+}
+
+/// How much detail the default rejection responses include.
+///
+/// Set it with `detail_level` on the configs, such as
+/// [`JsonConfig::detail_level`](super::JsonConfig::detail_level). Responses from rejection
+/// handlers are not changed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum DetailLevel {
+    /// Only the status code, with an empty body.
+    Minimal,
+
+    /// A generic message, such as `Failed to deserialize the JSON body into the target type`,
+    /// without the underlying error.
+    Message,
+
+    /// The message and the underlying error, such as `Failed to deserialize the JSON body into
+    /// the target type: missing field `id` at line 1 column 2`.
+    ///
+    /// This is the default and matches axum's rejections.
+    #[default]
+    Full,
+}
+
+/// Rejection used by the extractors in this module.
+///
+/// It contains the response produced by the rejection handlers, or the original rejection if
+/// no rejection handler handled it. That allows code that calls the extractors directly, for
+/// example to compose them into other extractors, to inspect what failed.
+pub struct ConfiguredRejection<R> {
+    handled: Result<Response, R>,
+    render: Option<fn(R, &mut RejectionInfo) -> Response>,
+    override_status: Option<StatusCode>,
+    headers: Arc<HeaderMap>,
+    info: RejectionInfo,
+    detail_level: DetailLevel,
+    #[cfg(feature = "tracing")]
+    trace: Option<Box<super::trace::RejectionTrace>>,
+}
+
+impl<R> ConfiguredRejection<R> {
+    pub(crate) fn new(
+        handled: Result<Response, R>,
+        override_status: Option<StatusCode>,
+        headers: Arc<HeaderMap>,
+        info: RejectionInfo,
+        detail_level: DetailLevel,
+    ) -> Self {
+        Self {
+            handled,
+            render: None,
+            override_status,
+            headers,
+            info,
+            detail_level,
+            #[cfg(feature = "tracing")]
+            trace: None,
+        }
+    }
+
+    // Emit an event when the rejection is turned into a response.
+    #[cfg(feature = "tracing")]
+    pub(crate) fn trace(mut self, trace: Option<super::trace::RejectionTrace>) -> Self {
+        self.trace = trace.map(Box::new);
+        self
+    }
+
+    // Build the default response with `render`, which keeps the rejection in the info instead of
+    // formatting its message up front.
+    pub(crate) fn render(mut self, render: fn(R, &mut RejectionInfo) -> Response) -> Self {
+        self.render = Some(render);
+        self
+    }
+
+    /// The original rejection, unless it was handled by a rejection handler.
+    pub fn rejection(&self) -> Option<&R> {
+        self.handled.as_ref().err()
+    }
+
+    /// Consume `self` and get the original rejection, unless it was handled by a rejection
+    /// handler.
+    pub fn into_rejection(self) -> Option<R> {
+        self.handled.err()
+    }
+
+    /// Information about the rejection.
+    ///
+    /// This formats the rejection's message, if it hasn't been already.
+    pub fn info(&self) -> &RejectionInfo
+    where
+        R: fmt::Display,
+    {
+        if let Err(rejection) = &self.handled {
+            self.info.format_message(rejection);
+        }
+        &self.info
+    }
+}
+
+impl<R> IntoResponse for ConfiguredRejection<R>
+where
+    R: IntoResponse + fmt::Display,
+{
+    fn into_response(mut self) -> Response {
+        let mut response = match self.handled {
+            Ok(response) => response,
+            Err(rejection) => {
+                let mut response = match self.render {
+                    Some(render) => render(rejection, &mut self.info),
+                    None => {
+                        self.info.format_message(&rejection);
+                        rejection.into_response()
+                    }
+                };
+                match self.detail_level {
+                    DetailLevel::Full => {}
+                    DetailLevel::Message => {
+                        let message = self.info.message();
+                        let message = split_query_string_message(message)
+                            .map_or(message, |(message, _)| message);
+                        response = (response.status(), message.to_owned()).into_response();
+                    }
+                    DetailLevel::Minimal => response = response.status().into_response(),
+                }
+                if let Some(status) = self.override_status {
+                    *response.status_mut() = status;
+                }
+                response
+            }
+        };
+
+        for (name, value) in self.headers.iter() {
+            if !response.headers().contains_key(name) {
+                response.headers_mut().insert(name, value.clone());
+            }
+        }
+        #[cfg(feature = "tracing")]
+        if let Some(trace) = &self.trace {
+            trace.emit(&self.info, response.status());
+        }
+        #[cfg(feature = "otel")]
+        super::otel::record_rejection(&self.info, response.status());
+        response.extensions_mut().insert(self.info);
+
+        response
+    }
+}
+
+impl<R> fmt::Debug for ConfiguredRejection<R>
+where
+    R: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConfiguredRejection")
+            .field("rejection", &self.rejection())
+            .field("info", &self.info)
+            .finish()
+    }
+}
+
+impl<R> fmt::Display for ConfiguredRejection<R>
+where
+    R: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.info().message())
+    }
+}
+
+impl<R> std::error::Error for ConfiguredRejection<R>
+where
+    R: std::error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.rejection().map(|rejection| rejection as _)
+    }
+}
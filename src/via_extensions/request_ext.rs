@@ -0,0 +1,93 @@
+use super::{config::RequestConfigs, Config};
+use axum::{
+    async_trait,
+    extract::{FromRequest, RequestParts},
+};
+
+/// Extractors that are configured with a config type, such as [`Json`](super::Json) with
+/// [`JsonConfig`](super::JsonConfig).
+///
+/// Used by [`RequestExt::extract_with_config`]. Extractors generated with
+/// [`make_configurable_extractor!`](crate::make_configurable_extractor) implement it as well.
+pub trait ConfigurableExtractor<B>: FromRequest<B> {
+    /// The config of the extractor.
+    type Config: Clone + Send + Sync + 'static;
+}
+
+/// Extension trait for running extractors with an explicit config.
+///
+/// This is useful in middleware and when extracting manually, where the config might not come
+/// from a layer. The given config is used instead of the one added to the request, if any.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{Json, JsonConfig, RequestExt};
+/// use axum::{
+///     body::Body,
+///     extract::RequestParts,
+///     http::{Request, StatusCode},
+///     response::{IntoResponse, Response},
+/// };
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Payload {}
+///
+/// async fn check_payload(req: Request<Body>) -> Result<Payload, Response> {
+///     let mut parts = RequestParts::new(req);
+///     let config = JsonConfig::new().override_status(StatusCode::BAD_REQUEST);
+///
+///     let Json(payload) = parts
+///         .extract_with_config::<Json<Payload>>(config)
+///         .await
+///         .map_err(IntoResponse::into_response)?;
+///
+///     Ok(payload)
+/// }
+/// ```
+#[async_trait]
+pub trait RequestExt<B>: sealed::Sealed {
+    /// Run the extractor `E` with `config`.
+    async fn extract_with_config<E>(&mut self, config: E::Config) -> Result<E, E::Rejection>
+    where
+        E: ConfigurableExtractor<B>;
+}
+
+#[async_trait]
+impl<B> RequestExt<B> for RequestParts<B>
+where
+    B: Send + 'static,
+{
+    async fn extract_with_config<E>(&mut self, config: E::Config) -> Result<E, E::Rejection>
+    where
+        E: ConfigurableExtractor<B>,
+    {
+        let previous = self.extensions().get::<RequestConfigs>().cloned();
+        self.extensions_mut().insert(RequestConfigs::with_config(
+            previous.clone(),
+            Config::<E::Config, B>::new(config),
+        ));
+
+        let result = E::from_request(self).await;
+
+        // put back the configs added by layers, if any
+        match previous {
+            Some(previous) => {
+                self.extensions_mut().insert(previous);
+            }
+            None => {
+                self.extensions_mut().remove::<RequestConfigs>();
+            }
+        }
+
+        result
+    }
+}
+
+mod sealed {
+    #[allow(unreachable_pub)]
+    pub trait Sealed {}
+
+    impl<B> Sealed for axum::extract::RequestParts<B> {}
+}
@@ -0,0 +1,108 @@
+use super::{
+    rejection::{AnyRejection, FormRejection, QueryRejection},
+    FormConfig, GlobalRejectionConfig, JsonConfig, QueryConfig, RejectionContext,
+};
+use axum::{body::HttpBody, extract::rejection::JsonRejection, response::IntoResponse, Router};
+
+/// Extension trait for configuring the extractors directly on a [`Router`].
+///
+/// Each method adds a layer with the corresponding config, so they can't be combined with adding
+/// the same config as a layer. Use the config types for settings other than the rejection
+/// handler.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{Json, RouterConfigExt};
+/// use axum::{Router, routing::post, http::StatusCode};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Payload {}
+///
+/// async fn handler(Json(payload): Json<Payload>) {}
+///
+/// let app = Router::new()
+///     .route("/", post(handler))
+///     .json_rejection_handler(|rejection, _ctx| {
+///         (StatusCode::BAD_REQUEST, rejection.to_string())
+///     });
+/// # let _: Router = app;
+/// ```
+pub trait RouterConfigExt<B>: sealed::Sealed {
+    /// Set the rejection handler used by [`Json`](super::Json).
+    ///
+    /// See [`JsonConfig::rejection_handler`].
+    fn json_rejection_handler<F, R>(self, f: F) -> Self
+    where
+        F: Fn(JsonRejection, RejectionContext<'_, B>) -> R + Send + Sync + 'static,
+        R: IntoResponse;
+
+    /// Set the rejection handler used by [`Query`](super::Query).
+    ///
+    /// See [`QueryConfig::rejection_handler`].
+    fn query_rejection_handler<F, R>(self, f: F) -> Self
+    where
+        F: Fn(QueryRejection, RejectionContext<'_, B>) -> R + Send + Sync + 'static,
+        R: IntoResponse;
+
+    /// Set the rejection handler used by [`Form`](super::Form).
+    ///
+    /// See [`FormConfig::rejection_handler`].
+    fn form_rejection_handler<F, R>(self, f: F) -> Self
+    where
+        F: Fn(FormRejection, RejectionContext<'_, B>) -> R + Send + Sync + 'static,
+        R: IntoResponse;
+
+    /// Set the rejection handler used by all extractors.
+    ///
+    /// See [`GlobalRejectionConfig::rejection_handler`].
+    fn global_rejection_handler<F, R>(self, f: F) -> Self
+    where
+        F: Fn(AnyRejection, RejectionContext<'_, B>) -> R + Send + Sync + 'static,
+        R: IntoResponse;
+}
+
+impl<B> RouterConfigExt<B> for Router<B>
+where
+    B: HttpBody + Send + 'static,
+{
+    fn json_rejection_handler<F, R>(self, f: F) -> Self
+    where
+        F: Fn(JsonRejection, RejectionContext<'_, B>) -> R + Send + Sync + 'static,
+        R: IntoResponse,
+    {
+        self.layer(JsonConfig::new().rejection_handler(f))
+    }
+
+    fn query_rejection_handler<F, R>(self, f: F) -> Self
+    where
+        F: Fn(QueryRejection, RejectionContext<'_, B>) -> R + Send + Sync + 'static,
+        R: IntoResponse,
+    {
+        self.layer(QueryConfig::new().rejection_handler(f))
+    }
+
+    fn form_rejection_handler<F, R>(self, f: F) -> Self
+    where
+        F: Fn(FormRejection, RejectionContext<'_, B>) -> R + Send + Sync + 'static,
+        R: IntoResponse,
+    {
+        self.layer(FormConfig::new().rejection_handler(f))
+    }
+
+    fn global_rejection_handler<F, R>(self, f: F) -> Self
+    where
+        F: Fn(AnyRejection, RejectionContext<'_, B>) -> R + Send + Sync + 'static,
+        R: IntoResponse,
+    {
+        self.layer(GlobalRejectionConfig::new().rejection_handler(f))
+    }
+}
+
+mod sealed {
+    #[allow(unreachable_pub)]
+    pub trait Sealed {}
+
+    impl<B> Sealed for axum::Router<B> {}
+}
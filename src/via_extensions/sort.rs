@@ -0,0 +1,287 @@
+use super::{
+    config,
+    custom::{reject_custom, CustomRejectionHandler},
+    rejection::ConfiguredRejection,
+    Config, RejectionContext,
+};
+use axum::{
+    async_trait,
+    extract::{FromRequest, RequestParts},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use std::{collections::HashSet, fmt, str::FromStr, sync::Arc};
+
+/// Config type for [`SortBy`].
+///
+/// Only the fields listed with [`fields`](Self::fields) can be sorted by, so clients cannot sort
+/// by columns that aren't indexed, or that they shouldn't see.
+pub struct SortConfig<B> {
+    fields: Arc<HashSet<String>>,
+    param: Arc<str>,
+    max_fields: Option<usize>,
+    rejection_handler: Option<CustomRejectionHandler<SortError, B>>,
+    override_status: Option<StatusCode>,
+    allow_override: bool,
+}
+
+impl<B> SortConfig<B> {
+    /// Create a new `SortConfig`.
+    ///
+    /// No fields can be sorted by until they're set with [`fields`](Self::fields).
+    pub fn new() -> Self {
+        Self {
+            fields: Arc::default(),
+            param: "sort".into(),
+            max_fields: None,
+            rejection_handler: None,
+            override_status: None,
+            allow_override: false,
+        }
+    }
+
+    /// Set the fields that can be sorted by.
+    pub fn fields<I, S>(mut self, fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.fields = Arc::new(fields.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Set the name of the query parameter.
+    ///
+    /// Defaults to `sort`.
+    pub fn param(mut self, param: &str) -> Self {
+        self.param = param.into();
+        self
+    }
+
+    /// Set the maximum number of fields to sort by.
+    ///
+    /// Defaults to no maximum.
+    pub fn max_fields(mut self, max_fields: usize) -> Self {
+        self.max_fields = Some(max_fields);
+        self
+    }
+
+    /// Set the rejection handler function.
+    pub fn rejection_handler<F, R>(mut self, f: F) -> Self
+    where
+        F: Fn(SortError, RejectionContext<'_, B>) -> R + Send + Sync + 'static,
+        R: IntoResponse,
+    {
+        self.rejection_handler = Some(Arc::new(move |error, ctx| f(error, ctx).into_response()));
+        self
+    }
+
+    /// Set the status code of the default rejection responses.
+    pub fn override_status(mut self, status: StatusCode) -> Self {
+        self.override_status = Some(status);
+        self
+    }
+
+    /// Allow layers further in to override this config.
+    ///
+    /// See [`Config::allow_override`].
+    pub fn allow_override(mut self) -> Self {
+        self.allow_override = true;
+        self
+    }
+
+    fn parse<T>(&self, query: &str) -> Result<Vec<Sort<T>>, SortError>
+    where
+        T: FromStr,
+    {
+        let params = serde_urlencoded::from_str::<Vec<(String, String)>>(query)
+            .map_err(|_| SortError::Invalid)?;
+
+        let mut sorts = Vec::new();
+        for (_, value) in params.iter().filter(|(name, _)| **name == *self.param) {
+            for field in value.split(',').map(str::trim) {
+                let (direction, field) = match field.as_bytes().first() {
+                    Some(b'-') => (SortDirection::Desc, &field[1..]),
+                    Some(b'+') => (SortDirection::Asc, &field[1..]),
+                    _ => (SortDirection::Asc, field),
+                };
+                if field.is_empty() {
+                    return Err(SortError::Invalid);
+                }
+                if !self.fields.contains(field) {
+                    return Err(SortError::NotSortable(field.to_owned()));
+                }
+                let field = field
+                    .parse()
+                    .map_err(|_| SortError::NotSortable(field.to_owned()))?;
+                sorts.push(Sort { field, direction });
+            }
+        }
+
+        match self.max_fields {
+            Some(max_fields) if sorts.len() > max_fields => Err(SortError::TooManyFields),
+            _ => Ok(sorts),
+        }
+    }
+}
+
+impl<B> Clone for SortConfig<B> {
+    fn clone(&self) -> Self {
+        Self {
+            fields: self.fields.clone(),
+            param: self.param.clone(),
+            max_fields: self.max_fields,
+            rejection_handler: self.rejection_handler.clone(),
+            override_status: self.override_status,
+            allow_override: self.allow_override,
+        }
+    }
+}
+
+impl<B> Default for SortConfig<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B> fmt::Debug for SortConfig<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SortConfig")
+            .field("fields", &self.fields)
+            .field("param", &self.param)
+            .field("max_fields", &self.max_fields)
+            .field("override_status", &self.override_status)
+            .field("allow_override", &self.allow_override)
+            .finish()
+    }
+}
+
+impl<S, B> tower_layer::Layer<S> for SortConfig<B> {
+    type Service = <Config<Self, B> as tower_layer::Layer<S>>::Service;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        let mut config: Config<_, B> = Config::new(self.clone());
+        if self.allow_override {
+            config = config.allow_override();
+        }
+        config.layer(inner)
+    }
+}
+
+/// Extractor for sorting parameters such as `?sort=-created_at,name`.
+///
+/// Fields are separated by commas, and sorted in descending order if they start with `-`. The
+/// fields are parsed into `T` with [`FromStr`], which can be an enum of the sortable fields.
+/// Requests without the parameter extract an empty list.
+///
+/// Fields that aren't allowed by [`SortConfig`] are rejected with `400 Bad Request`.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{SortBy, SortConfig, SortDirection};
+/// use axum::{routing::get, Router};
+///
+/// async fn handler(SortBy(sorts): SortBy) {
+///     for sort in sorts {
+///         let order = match sort.direction {
+///             SortDirection::Asc => "ASC",
+///             SortDirection::Desc => "DESC",
+///         };
+///         println!("ORDER BY {} {}", sort.field, order);
+///     }
+/// }
+///
+/// let app = Router::new()
+///     .route("/issues", get(handler))
+///     .layer(SortConfig::new().fields(["created_at", "name"]));
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SortBy<T = String>(pub Vec<Sort<T>>);
+
+/// A field to sort by, extracted by [`SortBy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sort<T> {
+    /// The field.
+    pub field: T,
+    /// Whether to sort in ascending or descending order.
+    pub direction: SortDirection,
+}
+
+/// The order to sort a field in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortDirection {
+    /// Ascending order, for fields without a prefix or starting with `+`.
+    Asc,
+    /// Descending order, for fields starting with `-`.
+    Desc,
+}
+
+#[async_trait]
+impl<T, B> FromRequest<B> for SortBy<T>
+where
+    T: FromStr + Send,
+    B: Send + 'static,
+{
+    type Rejection = ConfiguredRejection<SortError>;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let config = config::get_shared::<SortConfig<B>, B>(req.extensions());
+        let result = match &config {
+            Some(config) => config.parse(req.uri().query().unwrap_or_default()),
+            None => Err(SortError::MissingConfig),
+        };
+
+        result.map(Self).map_err(|error| {
+            let config = config.unwrap_or_default();
+            reject_custom::<Self, _, _>(
+                "SortBy",
+                error,
+                config.rejection_handler.as_ref(),
+                config.override_status,
+                req,
+            )
+        })
+    }
+}
+
+/// Why [`SortBy`] rejected the request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SortError {
+    /// The field isn't one of the fields allowed by [`SortConfig::fields`].
+    NotSortable(String),
+    /// The query string couldn't be parsed, or a field is empty.
+    Invalid,
+    /// More fields than [`SortConfig::max_fields`] were given.
+    TooManyFields,
+    /// [`SortConfig`] wasn't added, so no fields can be sorted by.
+    MissingConfig,
+}
+
+impl fmt::Display for SortError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotSortable(field) => write!(f, "Cannot sort by `{}`", field),
+            Self::Invalid => write!(f, "Invalid sort parameter"),
+            Self::TooManyFields => write!(f, "Too many sort fields"),
+            Self::MissingConfig => write!(
+                f,
+                "Missing `SortConfig`. `SortBy` requires the fields that can be sorted by"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SortError {}
+
+impl IntoResponse for SortError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            Self::NotSortable(_) | Self::Invalid | Self::TooManyFields => StatusCode::BAD_REQUEST,
+            Self::MissingConfig => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, self.to_string()).into_response()
+    }
+}
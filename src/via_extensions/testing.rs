@@ -0,0 +1,607 @@
+//! Helpers for testing extractors and rejection handlers without a `Router`.
+//!
+//! [`TestRequest`] builds a request, adds configs to it by running their layers, and runs an
+//! extractor on it. Rejections are turned into a [`TestResponse`] with the body already
+//! collected, so the response of a rejection handler can be checked directly.
+//!
+//! Requires the `testing` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use axum_extractor_config::via_extensions::{testing::TestRequest, Json, JsonConfig};
+//! use axum::http::StatusCode;
+//! use serde::Deserialize;
+//!
+//! #[derive(Debug, Deserialize)]
+//! struct Payload {
+//!     id: u32,
+//! }
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let rejection = TestRequest::post("/")
+//!     .body(r#"{"id":"foo"}"#)
+//!     .header("content-type", "application/json")
+//!     .layer(JsonConfig::new().override_status(StatusCode::BAD_REQUEST))
+//!     .extract::<Json<Payload>>()
+//!     .await
+//!     .unwrap_err();
+//!
+//! assert_eq!(rejection.status(), StatusCode::BAD_REQUEST);
+//! # }
+//! ```
+//!
+//! # Rejections
+//!
+//! axum's rejections cannot be constructed directly, so this module also has functions such as
+//! [`json_syntax_error`] that produce them by extracting malformed requests. Those can be passed
+//! to a rejection handler, or to code that matches on rejections, in unit tests.
+//!
+//! ```
+//! use axum_extractor_config::via_extensions::testing;
+//! use axum::{extract::rejection::JsonRejection, http::StatusCode};
+//!
+//! fn status(rejection: &JsonRejection) -> StatusCode {
+//!     match rejection {
+//!         JsonRejection::JsonSyntaxError(_) => StatusCode::BAD_REQUEST,
+//!         _ => StatusCode::UNPROCESSABLE_ENTITY,
+//!     }
+//! }
+//!
+//! assert_eq!(status(&testing::json_syntax_error()), StatusCode::BAD_REQUEST);
+//! assert_eq!(status(&testing::json_data_error()), StatusCode::UNPROCESSABLE_ENTITY);
+//! ```
+
+use super::{
+    rejection::{FormRejection, QueryRejection},
+    Form, Json, Query,
+};
+use axum::{
+    body::{Body, Bytes},
+    extract::{rejection::JsonRejection, FromRequest, RequestParts},
+    http::{
+        header::{self, HeaderName},
+        HeaderMap, HeaderValue, Method, Request, StatusCode, Uri,
+    },
+    response::{IntoResponse, Response},
+};
+use futures_util::{future, FutureExt};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    convert::Infallible,
+    fmt,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Builder for requests to run extractors on.
+///
+/// See the [module docs](self) for an example.
+#[derive(Debug)]
+pub struct TestRequest {
+    request: Request<Body>,
+}
+
+impl TestRequest {
+    /// Create a new `TestRequest` with the given method and URI.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `uri` isn't a valid URI.
+    pub fn new(method: Method, uri: &str) -> Self {
+        let mut request = Request::new(Body::empty());
+        *request.method_mut() = method;
+        *request.uri_mut() = uri
+            .parse::<Uri>()
+            .unwrap_or_else(|err| panic!("invalid URI {:?}: {}", uri, err));
+        Self { request }
+    }
+
+    /// Create a new `GET` request.
+    pub fn get(uri: &str) -> Self {
+        Self::new(Method::GET, uri)
+    }
+
+    /// Create a new `POST` request.
+    pub fn post(uri: &str) -> Self {
+        Self::new(Method::POST, uri)
+    }
+
+    /// Append a header.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name or value isn't a valid header name or value.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        let name = HeaderName::try_from(name)
+            .unwrap_or_else(|err| panic!("invalid header name {:?}: {}", name, err));
+        let value = HeaderValue::try_from(value)
+            .unwrap_or_else(|err| panic!("invalid header value {:?}: {}", value, err));
+        self.request.headers_mut().append(name, value);
+        self
+    }
+
+    /// Set the body.
+    pub fn body(mut self, body: impl Into<Body>) -> Self {
+        *self.request.body_mut() = body.into();
+        self
+    }
+
+    /// Set the body to `value` serialized as JSON, and the `Content-Type` to `application/json`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` fails to serialize.
+    pub fn json<T>(self, value: &T) -> Self
+    where
+        T: Serialize + ?Sized,
+    {
+        let body = serde_json::to_vec(value).expect("failed to serialize JSON body");
+        self.content_type("application/json").body(body)
+    }
+
+    /// Set the body to `value` serialized as a form, and the `Content-Type` to
+    /// `application/x-www-form-urlencoded`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` fails to serialize.
+    pub fn form<T>(self, value: &T) -> Self
+    where
+        T: Serialize + ?Sized,
+    {
+        let body = serde_urlencoded::to_string(value).expect("failed to serialize form body");
+        self.content_type("application/x-www-form-urlencoded")
+            .body(body)
+    }
+
+    fn content_type(mut self, content_type: &'static str) -> Self {
+        self.request
+            .headers_mut()
+            .insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+        self
+    }
+
+    /// Insert an extension, for extractors that read one.
+    pub fn extension<T>(mut self, extension: T) -> Self
+    where
+        T: Send + Sync + 'static,
+    {
+        self.request.extensions_mut().insert(extension);
+        self
+    }
+
+    /// Run the request through a layer, such as [`JsonConfig`](super::JsonConfig) or
+    /// [`ExtractorConfigBuilder::build_layer`](super::ExtractorConfigBuilder::build_layer), to add
+    /// its config.
+    ///
+    /// Layers are run in the order they're added, so the last one is the innermost.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the layer doesn't pass the request on right away, for example because the config
+    /// was already added.
+    pub fn layer<L>(self, layer: L) -> Self
+    where
+        L: Layer<Capture>,
+        L::Service: Service<Request<Body>, Error = Infallible>,
+        <L::Service as Service<Request<Body>>>::Response: IntoResponse,
+    {
+        let captured = Arc::new(Mutex::new(None));
+        let mut service = layer.layer(Capture {
+            captured: captured.clone(),
+        });
+
+        future::poll_fn(|cx| service.poll_ready(cx))
+            .now_or_never()
+            .expect("layer wasn't ready right away")
+            .unwrap_or_else(|err| match err {});
+        let response = service
+            .call(self.request)
+            .now_or_never()
+            .expect("layer didn't pass the request on right away");
+
+        let request = captured.lock().unwrap().take();
+        match request {
+            Some(request) => Self { request },
+            None => {
+                let response = response.unwrap_or_else(|err| match err {});
+                panic!(
+                    "layer responded instead of passing the request on: {}",
+                    response.into_response().status()
+                )
+            }
+        }
+    }
+
+    /// Get the [`RequestParts`], for running extractors manually.
+    pub fn into_request_parts(self) -> RequestParts<Body> {
+        RequestParts::new(self.request)
+    }
+
+    /// Run the extractor `E` on the request.
+    ///
+    /// If the request is rejected the rejection is turned into a [`TestResponse`].
+    pub async fn extract<E>(self) -> Result<E, TestResponse>
+    where
+        E: FromRequest<Body>,
+        E::Rejection: IntoResponse,
+    {
+        match E::from_request(&mut self.into_request_parts()).await {
+            Ok(value) => Ok(value),
+            Err(rejection) => Err(TestResponse::from_response(rejection.into_response()).await),
+        }
+    }
+
+    /// Send the request to a service, such as a `Router`, and collect the response.
+    pub async fn send<S>(self, mut service: S) -> TestResponse
+    where
+        S: Service<Request<Body>, Error = Infallible>,
+        S::Response: IntoResponse,
+    {
+        future::poll_fn(|cx| service.poll_ready(cx))
+            .await
+            .unwrap_or_else(|err| match err {});
+        let response = service
+            .call(self.request)
+            .await
+            .unwrap_or_else(|err| match err {});
+        TestResponse::from_response(response.into_response()).await
+    }
+}
+
+/// Service used by [`TestRequest::layer`] to get the request back from the layer.
+#[derive(Debug, Clone)]
+pub struct Capture {
+    captured: Arc<Mutex<Option<Request<Body>>>>,
+}
+
+impl Service<Request<Body>> for Capture {
+    type Response = Response;
+    type Error = Infallible;
+    type Future = future::Ready<Result<Response, Infallible>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        *self.captured.lock().unwrap() = Some(req);
+        future::ready(Ok(Response::default()))
+    }
+}
+
+/// A response with its body collected, returned by [`TestRequest::extract`] for rejections.
+///
+/// The `assert_*` methods panic with the body in the message, and return `self` so they can be
+/// chained. See also [`assert_rejection!`](crate::assert_rejection).
+pub struct TestResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+impl TestResponse {
+    /// Collect the body of `response`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the body fails to be collected.
+    pub async fn from_response(response: Response) -> Self {
+        let (parts, body) = response.into_parts();
+        let body = Bytes::from_request(&mut RequestParts::new(Request::new(body)))
+            .await
+            .expect("failed to collect response body");
+        Self {
+            status: parts.status,
+            headers: parts.headers,
+            body,
+        }
+    }
+
+    /// The status code.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// The headers.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// The body.
+    pub fn body(&self) -> &Bytes {
+        &self.body
+    }
+
+    /// The body as text. Invalid UTF-8 is replaced with `U+FFFD`.
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    /// The body deserialized from JSON.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the body isn't valid JSON for `T`.
+    pub fn json<T>(&self) -> T
+    where
+        T: DeserializeOwned,
+    {
+        serde_json::from_slice(&self.body)
+            .unwrap_or_else(|err| panic!("invalid JSON response body {:?}: {}", self.text(), err))
+    }
+
+    /// A deterministic summary of the response, for snapshot tests.
+    pub fn snapshot(&self) -> ResponseSnapshot {
+        let mut headers = BTreeMap::<String, Vec<String>>::new();
+        for (name, value) in &self.headers {
+            headers
+                .entry(name.as_str().to_owned())
+                .or_default()
+                .push(String::from_utf8_lossy(value.as_bytes()).into_owned());
+        }
+
+        let body = if self.body.is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_slice(&self.body).unwrap_or_else(|_| Value::String(self.text()))
+        };
+
+        ResponseSnapshot {
+            status: self.status.as_u16(),
+            headers,
+            body,
+        }
+    }
+
+    /// Assert that the status code is `status`.
+    #[track_caller]
+    pub fn assert_status(&self, status: StatusCode) -> &Self {
+        assert_eq!(
+            self.status,
+            status,
+            "unexpected status, body: {:?}",
+            self.text()
+        );
+        self
+    }
+
+    /// Assert that the media type of the `Content-Type` is `content_type`, ignoring parameters
+    /// such as `charset`.
+    #[track_caller]
+    pub fn assert_content_type(&self, content_type: &str) -> &Self {
+        let actual = self
+            .headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.split(';').next().unwrap_or_default().trim());
+        assert!(
+            actual.is_some_and(|actual| actual.eq_ignore_ascii_case(content_type)),
+            "expected content type {:?}, got {:?}, body: {:?}",
+            content_type,
+            actual,
+            self.text()
+        );
+        self
+    }
+
+    /// Assert that the body is JSON that contains `expected`.
+    ///
+    /// Objects in the body may have fields that `expected` doesn't, so only the fields a test
+    /// cares about need to be listed. Other values, including arrays, must be equal.
+    #[track_caller]
+    pub fn assert_json_contains(&self, expected: &Value) -> &Self {
+        let actual = self.json::<Value>();
+        assert!(
+            json_contains(&actual, expected),
+            "expected JSON body to contain {}, got {}",
+            expected,
+            actual
+        );
+        self
+    }
+}
+
+/// A deterministic, serializable summary of a response, made with [`TestResponse::snapshot`].
+///
+/// This is intended for snapshot testing rejections, for example with
+/// [`insta`](https://docs.rs/insta)'s `assert_json_snapshot!` or `assert_debug_snapshot!`.
+///
+/// Headers are sorted by name, and the values of each header are kept in the order they were
+/// added. Bodies that are JSON are parsed, so their fields are sorted too, other bodies are kept
+/// as text, and empty bodies are `null`.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{testing::TestRequest, Json};
+/// use serde_json::{json, Value};
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let rejection = TestRequest::post("/")
+///     .body("{")
+///     .extract::<Json<Value>>()
+///     .await
+///     .err()
+///     .unwrap();
+///
+/// assert_eq!(
+///     serde_json::to_value(rejection.snapshot()).unwrap(),
+///     json!({
+///         "status": 415,
+///         "headers": {
+///             "content-type": ["text/plain; charset=utf-8"],
+///         },
+///         "body": "Expected request with `Content-Type: application/json`",
+///     }),
+/// );
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ResponseSnapshot {
+    status: u16,
+    headers: BTreeMap<String, Vec<String>>,
+    body: Value,
+}
+
+impl ResponseSnapshot {
+    /// The status code.
+    pub fn status(&self) -> u16 {
+        self.status
+    }
+
+    /// The headers, by name.
+    pub fn headers(&self) -> &BTreeMap<String, Vec<String>> {
+        &self.headers
+    }
+
+    /// The body.
+    pub fn body(&self) -> &Value {
+        &self.body
+    }
+}
+
+fn json_contains(actual: &Value, expected: &Value) -> bool {
+    match (actual, expected) {
+        (Value::Object(actual), Value::Object(expected)) => {
+            expected.iter().all(|(key, expected)| {
+                actual
+                    .get(key)
+                    .is_some_and(|actual| json_contains(actual, expected))
+            })
+        }
+        _ => actual == expected,
+    }
+}
+
+/// Assert on the status, content type, and JSON body of a [`TestResponse`].
+///
+/// The content type and body are optional. The body is an object or array written like for
+/// `serde_json::json!`, and is checked with [`TestResponse::assert_json_contains`].
+///
+/// Requires the `testing` feature.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::{
+///     assert_rejection,
+///     via_extensions::{presets, testing::TestRequest, Json, JsonConfig},
+/// };
+/// use axum::http::StatusCode;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Payload {
+///     id: u32,
+/// }
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let rejection = TestRequest::post("/")
+///     .body("{")
+///     .header("content-type", "application/json")
+///     .layer(JsonConfig::new().rejection_handler(presets::problem_details()))
+///     .extract::<Json<Payload>>()
+///     .await
+///     .err()
+///     .unwrap();
+///
+/// assert_rejection!(rejection, StatusCode::BAD_REQUEST);
+/// assert_rejection!(
+///     rejection,
+///     StatusCode::BAD_REQUEST,
+///     "application/problem+json",
+///     { "status": 400 }
+/// );
+/// # }
+/// ```
+#[macro_export]
+macro_rules! assert_rejection {
+    ($response:expr, $status:expr $(,)?) => {{
+        $crate::via_extensions::testing::TestResponse::assert_status(&$response, $status);
+    }};
+    ($response:expr, $status:expr, $content_type:expr $(,)?) => {{
+        $crate::via_extensions::testing::TestResponse::assert_status(&$response, $status)
+            .assert_content_type($content_type);
+    }};
+    ($response:expr, $status:expr, $content_type:expr, $json:tt $(,)?) => {{
+        $crate::via_extensions::testing::TestResponse::assert_status(&$response, $status)
+            .assert_content_type($content_type)
+            .assert_json_contains(&$crate::__private::serde_json::json!($json));
+    }};
+}
+
+impl fmt::Debug for TestResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TestResponse")
+            .field("status", &self.status)
+            .field("headers", &self.headers)
+            .field("body", &self.text())
+            .finish()
+    }
+}
+
+// The type the rejection constructors fail to extract.
+#[derive(Deserialize)]
+struct Target {
+    #[allow(dead_code)]
+    id: u32,
+}
+
+// Extract `E` from `req` without a config, and get the original rejection.
+fn rejection<E, R>(req: TestRequest) -> R
+where
+    E: FromRequest<Body, Rejection = super::rejection::ConfiguredRejection<R>>,
+{
+    E::from_request(&mut req.into_request_parts())
+        .now_or_never()
+        .expect("extraction didn't finish right away")
+        .err()
+        .expect("extraction should have been rejected")
+        .into_rejection()
+        .expect("rejection shouldn't be handled without a config")
+}
+
+/// A [`JsonRejection::MissingJsonContentType`], for a body without a JSON `Content-Type`.
+pub fn json_missing_content_type() -> JsonRejection {
+    rejection::<Json<Target>, _>(TestRequest::post("/").body(r#"{"id":1}"#))
+}
+
+/// A [`JsonRejection::JsonSyntaxError`], for a body that isn't valid JSON.
+pub fn json_syntax_error() -> JsonRejection {
+    rejection::<Json<Target>, _>(
+        TestRequest::post("/")
+            .header("content-type", "application/json")
+            .body("{"),
+    )
+}
+
+/// A [`JsonRejection::JsonDataError`], for a body that has the wrong type for a field.
+pub fn json_data_error() -> JsonRejection {
+    rejection::<Json<Target>, _>(TestRequest::post("/").json(&serde_json::json!({ "id": "foo" })))
+}
+
+/// A [`QueryRejection::FailedToDeserializeQueryString`], for a query string that has the wrong
+/// type for a parameter.
+pub fn query_deserialize_error() -> QueryRejection {
+    rejection::<Query<Target>, _>(TestRequest::get("/?id=foo"))
+}
+
+/// A [`FormRejection::InvalidFormContentType`], for a body without a form `Content-Type`.
+pub fn form_missing_content_type() -> FormRejection {
+    rejection::<Form<Target>, _>(TestRequest::post("/").body("id=1"))
+}
+
+/// A [`FormRejection::FailedToDeserializeQueryString`], for a body that has the wrong type for a
+/// field.
+pub fn form_deserialize_error() -> FormRejection {
+    rejection::<Form<Target>, _>(TestRequest::post("/").form(&[("id", "foo")]))
+}
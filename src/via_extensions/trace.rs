@@ -0,0 +1,49 @@
+use super::RejectionInfo;
+use axum::http::StatusCode;
+use tracing::Level;
+
+// The target of the events for rejections.
+const TARGET: &str = "axum_extractor_config::rejection";
+
+// What's needed to emit an event for a rejection once its status is known.
+#[derive(Debug)]
+pub(crate) struct RejectionTrace {
+    level: Level,
+    route: Option<String>,
+}
+
+impl RejectionTrace {
+    pub(crate) fn new(level: Level, route: Option<&str>) -> Self {
+        Self {
+            level,
+            route: route.map(ToOwned::to_owned),
+        }
+    }
+
+    pub(crate) fn emit(&self, info: &RejectionInfo, status: StatusCode) {
+        // the level of `tracing` events must be a constant
+        macro_rules! emit {
+            ($level:expr) => {
+                tracing::event!(
+                    target: TARGET,
+                    $level,
+                    extractor = info.extractor(),
+                    kind = info.kind().as_str(),
+                    route = self.route.as_deref(),
+                    status = status.as_u16(),
+                    target_type = info.target_type_name(),
+                    "{}",
+                    info.message(),
+                )
+            };
+        }
+
+        match self.level {
+            Level::ERROR => emit!(Level::ERROR),
+            Level::WARN => emit!(Level::WARN),
+            Level::INFO => emit!(Level::INFO),
+            Level::DEBUG => emit!(Level::DEBUG),
+            _ => emit!(Level::TRACE),
+        }
+    }
+}
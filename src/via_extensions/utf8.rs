@@ -0,0 +1,72 @@
+use super::rejection::InvalidUtf8Body;
+use axum::body::Bytes;
+
+/// How bodies that aren't valid UTF-8 are handled, set with `utf8_policy` on the configs, such as
+/// [`JsonConfig::utf8_policy`](super::JsonConfig::utf8_policy).
+///
+/// Without a policy the extractors handle invalid UTF-8 like axum's: [`Json`](super::Json)
+/// rejects it as a syntax error and [`Form`](super::Form) replaces it with `U+FFFD`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Utf8Policy {
+    /// Reject bodies that aren't valid UTF-8 with [`InvalidUtf8Body`].
+    ///
+    /// For forms, percent-encoded bytes are checked after they've been decoded.
+    Reject,
+    /// Replace bytes that aren't valid UTF-8 with `U+FFFD REPLACEMENT CHARACTER`.
+    Lossy,
+}
+
+impl Utf8Policy {
+    // Apply the policy to a JSON body.
+    pub(super) fn apply_json(self, bytes: Bytes) -> Result<Bytes, InvalidUtf8Body> {
+        match std::str::from_utf8(&bytes) {
+            Ok(_) => Ok(bytes),
+            Err(err) => match self {
+                Self::Reject => Err(InvalidUtf8Body::new(err.valid_up_to())),
+                Self::Lossy => Ok(Bytes::from(
+                    String::from_utf8_lossy(&bytes).into_owned().into_bytes(),
+                )),
+            },
+        }
+    }
+
+    // Apply the policy to a form. Forms are always decoded lossily, so only rejecting needs any
+    // work.
+    pub(super) fn check_form(self, input: &[u8]) -> Result<(), InvalidUtf8Body> {
+        match self {
+            Self::Reject => {
+                let decoded = percent_decode(input);
+                std::str::from_utf8(&decoded)
+                    .map(|_| ())
+                    .map_err(|err| InvalidUtf8Body::new(err.valid_up_to()))
+            }
+            Self::Lossy => Ok(()),
+        }
+    }
+}
+
+// Decode the percent-encoded bytes of a form. The separators stay in place, which is fine since
+// they're ASCII and don't affect whether the rest is valid UTF-8.
+fn percent_decode(input: &[u8]) -> Vec<u8> {
+    let mut decoded = Vec::with_capacity(input.len());
+    let mut bytes = input.iter();
+    while let Some(&byte) = bytes.next() {
+        if byte == b'%' {
+            let digits = bytes.as_slice();
+            let high = digits
+                .first()
+                .and_then(|digit| (*digit as char).to_digit(16));
+            let low = digits
+                .get(1)
+                .and_then(|digit| (*digit as char).to_digit(16));
+            if let (Some(high), Some(low)) = (high, low) {
+                decoded.push((high * 16 + low) as u8);
+                bytes.nth(1);
+                continue;
+            }
+        }
+        decoded.push(byte);
+    }
+    decoded
+}
@@ -0,0 +1,633 @@
+use super::{
+    config,
+    custom::{reject_custom, CustomRejectionHandler},
+    rejection::ConfiguredRejection,
+    Config, Form, Json, Query, RejectionContext,
+};
+use axum::{
+    async_trait,
+    extract::{rejection::ExtensionRejection, FromRequest, RequestParts},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension,
+};
+use serde::Serialize;
+use std::{borrow::Cow, collections::BTreeMap, fmt, sync::Arc};
+
+/// Types that can be checked after they've been deserialized.
+///
+/// Used by [`Valid`]. With the `validator` feature it's implemented for types that implement
+/// `validator::Validate`, whose errors are converted into [`ValidationErrors`]. Types that use
+/// other crates can implement it by converting their errors.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{Validate, ValidationErrors};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Signup {
+///     username: String,
+/// }
+///
+/// impl Validate for Signup {
+///     fn validate(&self) -> Result<(), ValidationErrors> {
+///         let mut errors = ValidationErrors::new();
+///         if self.username.is_empty() {
+///             errors.add("username", "must not be empty");
+///         }
+///         errors.into_result()
+///     }
+/// }
+/// ```
+pub trait Validate {
+    /// Check the value, returning the errors of each invalid field.
+    fn validate(&self) -> Result<(), ValidationErrors>;
+}
+
+/// Requires the `validator` feature.
+///
+/// The code derived for nested fields with `#[validate]` calls `validate` as a method, so such
+/// structs must be defined where this trait isn't in scope.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{Json, Valid};
+/// use serde::Deserialize;
+/// use validator::Validate;
+///
+/// #[derive(Deserialize, Validate)]
+/// struct Signup {
+///     #[validate(length(min = 3, message = "must be at least 3 characters"))]
+///     username: String,
+///     #[validate(email)]
+///     email: String,
+/// }
+///
+/// async fn handler(Valid(Json(signup)): Valid<Json<Signup>>) {}
+/// ```
+#[cfg(feature = "validator")]
+impl<T> Validate for T
+where
+    T: validator::Validate,
+{
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        validator::Validate::validate(self).map_err(ValidationErrors::from)
+    }
+}
+
+/// Types that can be checked after they've been deserialized, using a context.
+///
+/// Like [`Validate`] but for checks that need state, such as the usernames that are taken. Used
+/// by [`ValidWithContext`], which obtains the context from the request extensions. This matches
+/// validators with a context, such as those of the `garde` crate.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{ValidateWithContext, ValidationErrors};
+/// use serde::Deserialize;
+/// use std::{collections::HashSet, sync::Arc};
+///
+/// #[derive(Clone)]
+/// struct TakenUsernames(Arc<HashSet<String>>);
+///
+/// #[derive(Deserialize)]
+/// struct Signup {
+///     username: String,
+/// }
+///
+/// impl ValidateWithContext for Signup {
+///     type Context = TakenUsernames;
+///
+///     fn validate_with(&self, taken: &TakenUsernames) -> Result<(), ValidationErrors> {
+///         let mut errors = ValidationErrors::new();
+///         if taken.0.contains(&self.username) {
+///             errors.add("username", "is taken");
+///         }
+///         errors.into_result()
+///     }
+/// }
+/// ```
+pub trait ValidateWithContext {
+    /// The context given to [`validate_with`](Self::validate_with).
+    type Context: Clone + Send + Sync + 'static;
+
+    /// Check the value using `context`, returning the errors of each invalid field.
+    fn validate_with(&self, context: &Self::Context) -> Result<(), ValidationErrors>;
+}
+
+/// The errors of a value that failed [`Validate::validate`], by field.
+///
+/// Responds with `422 Unprocessable Entity` and the errors as JSON, such as
+/// `{"username":["must not be empty"]}`. Use [`ValidationConfig`] to customize the response.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct ValidationErrors {
+    fields: BTreeMap<Cow<'static, str>, Vec<Cow<'static, str>>>,
+}
+
+impl ValidationErrors {
+    /// Create an empty `ValidationErrors`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an error for a field.
+    pub fn add(
+        &mut self,
+        field: impl Into<Cow<'static, str>>,
+        message: impl Into<Cow<'static, str>>,
+    ) {
+        self.fields
+            .entry(field.into())
+            .or_default()
+            .push(message.into());
+    }
+
+    /// Whether there are no errors.
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// The errors of each field.
+    pub fn fields(&self) -> impl Iterator<Item = (&str, &[Cow<'static, str>])> {
+        self.fields
+            .iter()
+            .map(|(field, messages)| (&**field, &**messages))
+    }
+
+    /// `Ok(())` if there are no errors, otherwise `Err(self)`.
+    pub fn into_result(self) -> Result<(), Self> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+/// Requires the `validator` feature.
+///
+/// Errors of nested structs and lists are added to their path, such as `address.city` or
+/// `items[0].name`. The message is the error's message, or its code, such as `length`, if it
+/// has none.
+#[cfg(feature = "validator")]
+impl From<validator::ValidationErrors> for ValidationErrors {
+    fn from(errors: validator::ValidationErrors) -> Self {
+        let mut this = Self::new();
+        this.add_validator_errors(None, errors);
+        this
+    }
+}
+
+#[cfg(feature = "validator")]
+impl ValidationErrors {
+    fn add_validator_errors(&mut self, path: Option<&str>, errors: validator::ValidationErrors) {
+        use validator::ValidationErrorsKind;
+
+        for (field, kind) in errors.into_errors() {
+            let field = match path {
+                Some(path) => Cow::Owned(format!("{}.{}", path, field)),
+                None => Cow::Borrowed(field),
+            };
+            match kind {
+                ValidationErrorsKind::Field(errors) => {
+                    for error in errors {
+                        self.add(field.clone(), error.message.unwrap_or(error.code));
+                    }
+                }
+                ValidationErrorsKind::Struct(errors) => {
+                    self.add_validator_errors(Some(&field), *errors);
+                }
+                ValidationErrorsKind::List(list) => {
+                    for (index, errors) in list {
+                        let path = format!("{}[{}]", field, index);
+                        self.add_validator_errors(Some(&path), *errors);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Requires the `garde` feature.
+///
+/// Errors are added to their path, such as `address.city` or `items[0].name`. Errors of the value
+/// itself are added to the field `""`.
+#[cfg(feature = "garde")]
+impl From<garde::Report> for ValidationErrors {
+    fn from(report: garde::Report) -> Self {
+        let mut this = Self::new();
+        for (path, error) in report.into_inner() {
+            this.add(path.to_string(), error.message().to_owned());
+        }
+        this
+    }
+}
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Validation failed")?;
+        let mut separator = ": ";
+        for (field, messages) in &self.fields {
+            for message in messages {
+                write!(f, "{}{}: {}", separator, field, message)?;
+                separator = ", ";
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
+impl IntoResponse for ValidationErrors {
+    fn into_response(self) -> Response {
+        (StatusCode::UNPROCESSABLE_ENTITY, axum::Json(self)).into_response()
+    }
+}
+
+/// Config type for [`Valid`].
+pub struct ValidationConfig<B> {
+    rejection_handler: Option<CustomRejectionHandler<ValidationErrors, B>>,
+    override_status: Option<StatusCode>,
+    allow_override: bool,
+}
+
+impl<B> ValidationConfig<B> {
+    /// Create a new `ValidationConfig`.
+    pub fn new() -> Self {
+        Self {
+            rejection_handler: None,
+            override_status: None,
+            allow_override: false,
+        }
+    }
+
+    /// Set the rejection handler function.
+    pub fn rejection_handler<F, R>(mut self, f: F) -> Self
+    where
+        F: Fn(ValidationErrors, RejectionContext<'_, B>) -> R + Send + Sync + 'static,
+        R: IntoResponse,
+    {
+        self.rejection_handler = Some(Arc::new(move |errors, ctx| f(errors, ctx).into_response()));
+        self
+    }
+
+    /// Set the status code of the default rejection responses.
+    pub fn override_status(mut self, status: StatusCode) -> Self {
+        self.override_status = Some(status);
+        self
+    }
+
+    /// Allow layers further in to override this config.
+    ///
+    /// See [`Config::allow_override`].
+    pub fn allow_override(mut self) -> Self {
+        self.allow_override = true;
+        self
+    }
+}
+
+impl<B> Clone for ValidationConfig<B> {
+    fn clone(&self) -> Self {
+        Self {
+            rejection_handler: self.rejection_handler.clone(),
+            override_status: self.override_status,
+            allow_override: self.allow_override,
+        }
+    }
+}
+
+impl<B> Default for ValidationConfig<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B> fmt::Debug for ValidationConfig<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ValidationConfig")
+            .field("override_status", &self.override_status)
+            .field("allow_override", &self.allow_override)
+            .finish()
+    }
+}
+
+impl<S, B> tower_layer::Layer<S> for ValidationConfig<B> {
+    type Service = <Config<Self, B> as tower_layer::Layer<S>>::Service;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        let mut config: Config<_, B> = Config::new(self.clone());
+        if self.allow_override {
+            config = config.allow_override();
+        }
+        config.layer(inner)
+    }
+}
+
+/// Extractor that runs [`Validate::validate`] on the value extracted by another extractor.
+///
+/// Supports [`Json`], [`Query`], and [`Form`]. Rejections of the wrapped extractor are handled
+/// by its own config, while validation errors are handled by [`ValidationConfig`].
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{
+///     Json, Valid, Validate, ValidationConfig, ValidationErrors,
+/// };
+/// use axum::{http::StatusCode, routing::post, Router};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Signup {
+///     username: String,
+/// }
+///
+/// impl Validate for Signup {
+///     fn validate(&self) -> Result<(), ValidationErrors> {
+///         let mut errors = ValidationErrors::new();
+///         if self.username.len() < 3 {
+///             errors.add("username", "must be at least 3 characters");
+///         }
+///         errors.into_result()
+///     }
+/// }
+///
+/// async fn handler(Valid(Json(signup)): Valid<Json<Signup>>) {}
+///
+/// let app = Router::new()
+///     .route("/", post(handler))
+///     .layer(ValidationConfig::new().override_status(StatusCode::BAD_REQUEST));
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Valid<E>(pub E);
+
+#[async_trait]
+impl<E, B> FromRequest<B> for Valid<E>
+where
+    E: FromRequest<B> + Validated,
+    E::Target: Validate,
+    B: Send + 'static,
+{
+    type Rejection = ValidRejection<E::Rejection>;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let value = E::from_request(req)
+            .await
+            .map_err(ValidRejection::Extractor)?;
+
+        match value.target().validate() {
+            Ok(()) => Ok(Self(value)),
+            Err(errors) => Err(reject_invalid::<E::Target, _, _>("Valid", errors, req)),
+        }
+    }
+}
+
+/// Extractor that runs [`ValidateWithContext::validate_with`] on the value extracted by another
+/// extractor.
+///
+/// The context is obtained from the request extensions, so it must be added with
+/// [`Extension`](axum::Extension). Requests without it are rejected with `500 Internal Server
+/// Error`. Otherwise this works like [`Valid`].
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{
+///     Json, ValidWithContext, ValidateWithContext, ValidationErrors,
+/// };
+/// use axum::{routing::post, Extension, Router};
+/// use serde::Deserialize;
+///
+/// #[derive(Clone)]
+/// struct MaxLength(usize);
+///
+/// #[derive(Deserialize)]
+/// struct Comment {
+///     text: String,
+/// }
+///
+/// impl ValidateWithContext for Comment {
+///     type Context = MaxLength;
+///
+///     fn validate_with(&self, max: &MaxLength) -> Result<(), ValidationErrors> {
+///         let mut errors = ValidationErrors::new();
+///         if self.text.len() > max.0 {
+///             errors.add("text", "is too long");
+///         }
+///         errors.into_result()
+///     }
+/// }
+///
+/// async fn handler(ValidWithContext(Json(comment)): ValidWithContext<Json<Comment>>) {}
+///
+/// let app = Router::new()
+///     .route("/", post(handler))
+///     .layer(Extension(MaxLength(280)));
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ValidWithContext<E>(pub E);
+
+#[async_trait]
+impl<E, B> FromRequest<B> for ValidWithContext<E>
+where
+    E: FromRequest<B> + Validated,
+    E::Target: ValidateWithContext,
+    B: Send + 'static,
+{
+    type Rejection = ValidRejection<E::Rejection>;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let Extension(context) =
+            Extension::<<E::Target as ValidateWithContext>::Context>::from_request(req)
+                .await
+                .map_err(ValidRejection::MissingContext)?;
+
+        let value = E::from_request(req)
+            .await
+            .map_err(ValidRejection::Extractor)?;
+
+        match value.target().validate_with(&context) {
+            Ok(()) => Ok(Self(value)),
+            Err(errors) => Err(reject_invalid::<E::Target, _, _>(
+                "ValidWithContext",
+                errors,
+                req,
+            )),
+        }
+    }
+}
+
+/// Extractor that runs `garde::Validate::validate_with` on the value extracted by another
+/// extractor.
+///
+/// Requires the `garde` feature.
+///
+/// The context is obtained from the request extensions, so it must be added with
+/// [`Extension`](axum::Extension), except for types without a context, whose context is `()`.
+/// Requests without it are rejected with `500 Internal Server Error`. The errors are converted
+/// into [`ValidationErrors`] and handled by [`ValidationConfig`], like those of [`Valid`].
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{Garde, Json, ValidationConfig};
+/// use axum::{http::StatusCode, routing::post, Extension, Router};
+/// use serde::Deserialize;
+///
+/// #[derive(Clone)]
+/// struct MaxLength(usize);
+///
+/// #[derive(Deserialize, garde::Validate)]
+/// #[garde(context(MaxLength))]
+/// struct Comment {
+///     #[garde(custom(max_length))]
+///     text: String,
+/// }
+///
+/// fn max_length(text: &str, max: &MaxLength) -> garde::Result {
+///     if text.len() > max.0 {
+///         return Err(garde::Error::new("is too long"));
+///     }
+///     Ok(())
+/// }
+///
+/// async fn handler(Garde(Json(comment)): Garde<Json<Comment>>) {}
+///
+/// let app = Router::new()
+///     .route("/", post(handler))
+///     .layer(Extension(MaxLength(280)))
+///     .layer(ValidationConfig::new().override_status(StatusCode::BAD_REQUEST));
+/// # let _: Router = app;
+/// ```
+#[cfg(feature = "garde")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Garde<E>(pub E);
+
+#[cfg(feature = "garde")]
+#[async_trait]
+impl<E, B> FromRequest<B> for Garde<E>
+where
+    E: FromRequest<B> + Validated,
+    E::Target: garde::Validate,
+    <E::Target as garde::Validate>::Context: Clone + Send + Sync + 'static,
+    B: Send + 'static,
+{
+    type Rejection = ValidRejection<E::Rejection>;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let context = match unit_context() {
+            Some(context) => context,
+            None => {
+                let Extension(context) = Extension::from_request(req)
+                    .await
+                    .map_err(ValidRejection::MissingContext)?;
+                context
+            }
+        };
+
+        let value = E::from_request(req)
+            .await
+            .map_err(ValidRejection::Extractor)?;
+
+        match garde::Validate::validate_with(value.target(), &context) {
+            Ok(()) => Ok(Self(value)),
+            Err(report) => Err(reject_invalid::<E::Target, _, _>(
+                "Garde",
+                report.into(),
+                req,
+            )),
+        }
+    }
+}
+
+// `()`, the context of types that don't need one, doesn't have to be added to the request.
+#[cfg(feature = "garde")]
+fn unit_context<C>() -> Option<C>
+where
+    C: Clone + 'static,
+{
+    (&() as &dyn std::any::Any).downcast_ref::<C>().cloned()
+}
+
+fn reject_invalid<T, R, B>(
+    extractor: &'static str,
+    errors: ValidationErrors,
+    req: &RequestParts<B>,
+) -> ValidRejection<R>
+where
+    B: 'static,
+{
+    let config = config::get_shared::<ValidationConfig<B>, B>(req.extensions()).unwrap_or_default();
+
+    ValidRejection::Invalid(reject_custom::<T, _, _>(
+        extractor,
+        errors,
+        config.rejection_handler.as_ref(),
+        config.override_status,
+        req,
+    ))
+}
+
+/// Rejection used for [`Valid`], [`ValidWithContext`], and `Garde`.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ValidRejection<R> {
+    /// The wrapped extractor rejected the request.
+    Extractor(R),
+    /// The extracted value is invalid.
+    Invalid(ConfiguredRejection<ValidationErrors>),
+    /// The context of [`ValidWithContext`] or `Garde` wasn't added to the request.
+    MissingContext(ExtensionRejection),
+}
+
+impl<R> IntoResponse for ValidRejection<R>
+where
+    R: IntoResponse,
+{
+    fn into_response(self) -> Response {
+        match self {
+            Self::Extractor(rejection) => rejection.into_response(),
+            Self::Invalid(rejection) => rejection.into_response(),
+            Self::MissingContext(rejection) => rejection.into_response(),
+        }
+    }
+}
+
+// The extractors supported by `Valid`, `ValidWithContext`, and `Garde`.
+#[allow(unreachable_pub)]
+pub trait Validated: sealed::Sealed {
+    type Target;
+
+    fn target(&self) -> &Self::Target;
+}
+
+macro_rules! impl_validated {
+    ($($ident:ident),*) => {
+        $(
+            impl<T> sealed::Sealed for $ident<T> {}
+
+            impl<T> Validated for $ident<T> {
+                type Target = T;
+
+                fn target(&self) -> &T {
+                    &self.0
+                }
+            }
+        )*
+    };
+}
+
+impl_validated!(Json, Query, Form);
+
+mod sealed {
+    #[allow(unreachable_pub)]
+    pub trait Sealed {}
+}
@@ -0,0 +1,374 @@
+use super::{
+    config,
+    custom::{reject_custom, CustomRejectionHandler},
+    rejection::ConfiguredRejection,
+    Config, RejectionContext,
+};
+use axum::{
+    async_trait,
+    body::{Bytes, HttpBody},
+    extract::{rejection::BytesRejection, FromRequest, RequestParts},
+    http::{HeaderName, StatusCode},
+    response::{IntoResponse, Response},
+    BoxError,
+};
+use hmac::{Hmac, Mac};
+use std::{fmt, sync::Arc};
+
+type KeyProviderFn<B> = Arc<dyn Fn(&RequestParts<B>) -> Option<Bytes> + Send + Sync>;
+
+/// The hash functions supported by [`VerifyConfig::algorithm`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum HmacAlgorithm {
+    /// HMAC-SHA1, used by older webhooks such as GitHub's `X-Hub-Signature`.
+    Sha1,
+    /// HMAC-SHA256.
+    #[default]
+    Sha256,
+    /// HMAC-SHA512.
+    Sha512,
+}
+
+impl HmacAlgorithm {
+    fn verify(self, key: &[u8], body: &[u8], signature: &[u8]) -> bool {
+        fn verify<M>(key: &[u8], body: &[u8], signature: &[u8]) -> bool
+        where
+            M: Mac + hmac::digest::KeyInit,
+        {
+            let mut mac = <M as Mac>::new_from_slice(key).expect("HMAC accepts keys of any length");
+            mac.update(body);
+            mac.verify_slice(signature).is_ok()
+        }
+
+        match self {
+            Self::Sha1 => verify::<Hmac<sha1::Sha1>>(key, body, signature),
+            Self::Sha256 => verify::<Hmac<sha2::Sha256>>(key, body, signature),
+            Self::Sha512 => verify::<Hmac<sha2::Sha512>>(key, body, signature),
+        }
+    }
+}
+
+/// Config type for [`Verified`].
+///
+/// The signature is read from [`header`](Self::header), with an optional
+/// [`prefix`](Self::prefix) such as `sha256=`, and must be the hex encoded HMAC of the body.
+pub struct VerifyConfig<B> {
+    header: HeaderName,
+    prefix: Option<Arc<str>>,
+    algorithm: HmacAlgorithm,
+    key_provider: Option<KeyProviderFn<B>>,
+    rejection_handler: Option<CustomRejectionHandler<SignatureError, B>>,
+    override_status: Option<StatusCode>,
+    allow_override: bool,
+}
+
+impl<B> VerifyConfig<B> {
+    /// Create a new `VerifyConfig`.
+    ///
+    /// A key must be set with [`key`](Self::key) or [`key_provider`](Self::key_provider),
+    /// otherwise every request is rejected.
+    pub fn new() -> Self {
+        Self {
+            header: HeaderName::from_static("x-signature"),
+            prefix: None,
+            algorithm: HmacAlgorithm::default(),
+            key_provider: None,
+            rejection_handler: None,
+            override_status: None,
+            allow_override: false,
+        }
+    }
+
+    /// Set the header that contains the signature.
+    ///
+    /// Defaults to `X-Signature`.
+    pub fn header(mut self, header: HeaderName) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// Set a prefix the signature starts with, such as `sha256=`.
+    ///
+    /// Signatures without the prefix are rejected.
+    ///
+    /// Defaults to no prefix.
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefix = Some(prefix.into());
+        self
+    }
+
+    /// Set the hash function of the HMAC.
+    ///
+    /// Defaults to [`HmacAlgorithm::Sha256`].
+    pub fn algorithm(mut self, algorithm: HmacAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Set the secret key.
+    pub fn key(self, key: impl Into<Bytes>) -> Self {
+        let key = key.into();
+        self.key_provider(move |_| Some(key.clone()))
+    }
+
+    /// Set a function that returns the secret key for a request.
+    ///
+    /// This allows using a different key per tenant, or rotating keys without rebuilding the
+    /// router. Requests the function returns `None` for are rejected.
+    pub fn key_provider<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&RequestParts<B>) -> Option<Bytes> + Send + Sync + 'static,
+    {
+        self.key_provider = Some(Arc::new(f));
+        self
+    }
+
+    /// Set the rejection handler function.
+    pub fn rejection_handler<F, R>(mut self, f: F) -> Self
+    where
+        F: Fn(SignatureError, RejectionContext<'_, B>) -> R + Send + Sync + 'static,
+        R: IntoResponse,
+    {
+        self.rejection_handler = Some(Arc::new(move |error, ctx| f(error, ctx).into_response()));
+        self
+    }
+
+    /// Set the status code of the default rejection responses.
+    pub fn override_status(mut self, status: StatusCode) -> Self {
+        self.override_status = Some(status);
+        self
+    }
+
+    /// Allow layers further in to override this config.
+    ///
+    /// See [`Config::allow_override`].
+    pub fn allow_override(mut self) -> Self {
+        self.allow_override = true;
+        self
+    }
+
+    fn verify(&self, req: &RequestParts<B>, body: &[u8]) -> Result<(), SignatureError> {
+        let signature = req
+            .headers()
+            .get(&self.header)
+            .ok_or(SignatureError::Missing)?
+            .to_str()
+            .map_err(|_| SignatureError::Invalid)?
+            .trim();
+        let signature = match &self.prefix {
+            Some(prefix) => signature
+                .strip_prefix(&**prefix)
+                .ok_or(SignatureError::Invalid)?,
+            None => signature,
+        };
+        let signature = decode_hex(signature).ok_or(SignatureError::Invalid)?;
+
+        let key = self
+            .key_provider
+            .as_ref()
+            .and_then(|key_provider| key_provider(req))
+            .ok_or(SignatureError::Invalid)?;
+
+        if self.algorithm.verify(&key, body, &signature) {
+            Ok(())
+        } else {
+            Err(SignatureError::Invalid)
+        }
+    }
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    hex.as_bytes()
+        .chunks(2)
+        .map(|pair| match pair {
+            [high, low] => Some((hex_digit(*high)? << 4) | hex_digit(*low)?),
+            _ => None,
+        })
+        .collect()
+}
+
+fn hex_digit(digit: u8) -> Option<u8> {
+    (digit as char).to_digit(16).map(|digit| digit as u8)
+}
+
+impl<B> Clone for VerifyConfig<B> {
+    fn clone(&self) -> Self {
+        Self {
+            header: self.header.clone(),
+            prefix: self.prefix.clone(),
+            algorithm: self.algorithm,
+            key_provider: self.key_provider.clone(),
+            rejection_handler: self.rejection_handler.clone(),
+            override_status: self.override_status,
+            allow_override: self.allow_override,
+        }
+    }
+}
+
+impl<B> Default for VerifyConfig<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B> fmt::Debug for VerifyConfig<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VerifyConfig")
+            .field("header", &self.header)
+            .field("prefix", &self.prefix)
+            .field("algorithm", &self.algorithm)
+            .field("override_status", &self.override_status)
+            .field("allow_override", &self.allow_override)
+            .finish()
+    }
+}
+
+impl<S, B> tower_layer::Layer<S> for VerifyConfig<B> {
+    type Service = <Config<Self, B> as tower_layer::Layer<S>>::Service;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        let mut config: Config<_, B> = Config::new(self.clone());
+        if self.allow_override {
+            config = config.allow_override();
+        }
+        config.layer(inner)
+    }
+}
+
+/// Extractor that checks the HMAC signature of the body before running another extractor.
+///
+/// This is how webhook receivers, such as those of GitHub or Stripe, check that a request was
+/// sent by who they expect. The body is buffered and checked against the signature header
+/// configured with [`VerifyConfig`], and only then given to the wrapped extractor, which is
+/// rejected with its own config as usual. Requests with a missing or invalid signature are
+/// rejected with `401 Unauthorized`.
+///
+/// Requires the `hmac` feature.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_extensions::{HmacAlgorithm, Json, Verified, VerifyConfig};
+/// use axum::{http::HeaderName, routing::post, Router};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct PushEvent {
+///     after: String,
+/// }
+///
+/// async fn handler(Verified(Json(event)): Verified<Json<PushEvent>>) {}
+///
+/// let app = Router::new().route("/webhooks/github", post(handler)).layer(
+///     VerifyConfig::new()
+///         .header(HeaderName::from_static("x-hub-signature-256"))
+///         .prefix("sha256=")
+///         .algorithm(HmacAlgorithm::Sha256)
+///         .key("webhook secret"),
+/// );
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Verified<E>(pub E);
+
+#[async_trait]
+impl<E, B> FromRequest<B> for Verified<E>
+where
+    E: FromRequest<B>,
+    B: HttpBody + From<Bytes> + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    type Rejection = VerifiedRejection<E::Rejection>;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let bytes = Bytes::from_request(req)
+            .await
+            .map_err(VerifiedRejection::Body)?;
+
+        let config = config::get_shared::<VerifyConfig<B>, B>(req.extensions());
+        let result = match &config {
+            Some(config) => config.verify(req, &bytes),
+            None => Err(SignatureError::MissingConfig),
+        };
+        if let Err(error) = result {
+            let config = config.unwrap_or_default();
+            return Err(VerifiedRejection::Signature(reject_custom::<E, _, _>(
+                "Verified",
+                error,
+                config.rejection_handler.as_ref(),
+                config.override_status,
+                req,
+            )));
+        }
+
+        *req.body_mut() = Some(B::from(bytes));
+        E::from_request(req)
+            .await
+            .map(Self)
+            .map_err(VerifiedRejection::Extractor)
+    }
+}
+
+/// Why the signature checked by [`Verified`] was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SignatureError {
+    /// The request doesn't have the signature header.
+    Missing,
+    /// The signature isn't a valid signature of the body with the key.
+    Invalid,
+    /// [`VerifyConfig`] wasn't added, so there's no key to check the signature with.
+    MissingConfig,
+}
+
+impl fmt::Display for SignatureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing => write!(f, "Missing request signature"),
+            Self::Invalid => write!(f, "Invalid request signature"),
+            Self::MissingConfig => write!(
+                f,
+                "Missing `VerifyConfig`. `Verified` requires a key to check signatures with"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SignatureError {}
+
+impl IntoResponse for SignatureError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            Self::Missing | Self::Invalid => StatusCode::UNAUTHORIZED,
+            Self::MissingConfig => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, self.to_string()).into_response()
+    }
+}
+
+/// Rejection used for [`Verified`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum VerifiedRejection<R> {
+    /// The body couldn't be buffered.
+    Body(BytesRejection),
+    /// The signature is missing or invalid.
+    Signature(ConfiguredRejection<SignatureError>),
+    /// The wrapped extractor rejected the request.
+    Extractor(R),
+}
+
+impl<R> IntoResponse for VerifiedRejection<R>
+where
+    R: IntoResponse,
+{
+    fn into_response(self) -> Response {
+        match self {
+            Self::Body(rejection) => rejection.into_response(),
+            Self::Signature(rejection) => rejection.into_response(),
+            Self::Extractor(rejection) => rejection.into_response(),
+        }
+    }
+}
@@ -0,0 +1,285 @@
+//! Extractors that are configured via router state.
+//!
+//! The configs are obtained from the state with [`FromRef`], so applications that already keep
+//! their configuration in a state struct don't need to add a layer per config. This requires
+//! axum 0.6 and the `axum-06` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use axum_extractor_config::via_state::{Json, JsonConfig};
+//! use axum_06::{
+//!     extract::FromRef,
+//!     routing::post,
+//!     http::StatusCode,
+//!     Router,
+//! };
+//! use serde::Deserialize;
+//!
+//! #[derive(Clone)]
+//! struct AppState {
+//!     json_config: JsonConfig,
+//! }
+//!
+//! impl FromRef<AppState> for JsonConfig {
+//!     fn from_ref(state: &AppState) -> Self {
+//!         state.json_config.clone()
+//!     }
+//! }
+//!
+//! #[derive(Deserialize)]
+//! struct Payload {}
+//!
+//! async fn handler(Json(payload): Json<Payload>) {}
+//!
+//! let state = AppState {
+//!     json_config: JsonConfig::new().rejection_handler(|rejection| {
+//!         (StatusCode::BAD_REQUEST, rejection.to_string())
+//!     }),
+//! };
+//!
+//! let app = Router::new()
+//!     .route("/", post(handler))
+//!     .with_state(state);
+//! # let _: Router = app;
+//! ```
+
+use axum_06::{
+    async_trait,
+    body::HttpBody,
+    extract::{FromRef, FromRequest, FromRequestParts},
+    http::{request::Parts, Request},
+    response::{IntoResponse, Response},
+    BoxError,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{fmt, sync::Arc};
+
+type RejectionToResponseFn<T> = Option<Arc<dyn Fn(T) -> Response + Send + Sync>>;
+
+macro_rules! make_config {
+    (
+        $ident:ident,
+        $rejection:ident,
+        $config:ident $(,)?
+    ) => {
+        #[doc = concat!("Config type for `", stringify!($ident), "`")]
+        ///
+        /// Obtained from the state with [`FromRef`]. Defaults to the rejection's own response if
+        /// the state doesn't set a rejection handler.
+        #[derive(Clone, Default)]
+        pub struct $config {
+            rejection_handler: RejectionToResponseFn<axum_06::extract::rejection::$rejection>,
+        }
+
+        impl $config {
+            #[doc = concat!("Create a new `", stringify!($config), "`")]
+            pub fn new() -> Self {
+                Self::default()
+            }
+
+            /// Set the rejection handler function.
+            pub fn rejection_handler<F, R>(mut self, f: F) -> Self
+            where
+                F: Fn(axum_06::extract::rejection::$rejection) -> R + Send + Sync + 'static,
+                R: IntoResponse,
+            {
+                self.rejection_handler =
+                    Some(Arc::new(move |rejection| f(rejection).into_response()));
+                self
+            }
+
+            fn handle_rejection(
+                &self,
+                rejection: axum_06::extract::rejection::$rejection,
+            ) -> Response {
+                match &self.rejection_handler {
+                    Some(rejection_handler) => rejection_handler(rejection),
+                    None => rejection.into_response(),
+                }
+            }
+        }
+
+        impl fmt::Debug for $config {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_struct(stringify!($config)).finish()
+            }
+        }
+    };
+}
+
+macro_rules! make_body_wrapper {
+    (
+        $(#[$m:meta])*
+        $ident:ident,
+        $rejection:ident,
+        $config:ident $(,)?
+    ) => {
+        $(#[$m])*
+        #[derive(Clone, Copy, Debug)]
+        pub struct $ident<T>(pub T);
+
+        make_config!($ident, $rejection, $config);
+
+        #[async_trait]
+        impl<T, S, B> FromRequest<S, B> for $ident<T>
+        where
+            T: DeserializeOwned,
+            $config: FromRef<S>,
+            S: Send + Sync,
+            B: HttpBody + Send + 'static,
+            B::Data: Send,
+            B::Error: Into<BoxError>,
+        {
+            type Rejection = Response;
+
+            async fn from_request(req: Request<B>, state: &S) -> Result<Self, Self::Rejection> {
+                match axum_06::extract::$ident::<T>::from_request(req, state).await {
+                    Ok(axum_06::extract::$ident(value)) => Ok(Self(value)),
+                    Err(rejection) => Err($config::from_ref(state).handle_rejection(rejection)),
+                }
+            }
+        }
+
+        impl<T> IntoResponse for $ident<T>
+        where
+            T: Serialize,
+        {
+            fn into_response(self) -> Response {
+                axum_06::extract::$ident(self.0).into_response()
+            }
+        }
+    };
+}
+
+make_body_wrapper! {
+    /// Extractor that wraps `axum::extract::Json` and is configured via router state.
+    ///
+    /// Can be configured using [`JsonConfig`]. See the [module docs](self) for an example.
+    Json,
+    JsonRejection,
+    JsonConfig,
+}
+
+make_body_wrapper! {
+    /// Extractor that wraps `axum::extract::Form` and is configured via router state.
+    ///
+    /// Can be configured using [`FormConfig`]. See the [module docs](self) for an example.
+    Form,
+    FormRejection,
+    FormConfig,
+}
+
+/// Extractor that wraps `axum::extract::Query` and is configured via router state.
+///
+/// Can be configured using [`QueryConfig`]. See the [module docs](self) for an example.
+#[derive(Clone, Copy, Debug)]
+pub struct Query<T>(pub T);
+
+make_config!(Query, QueryRejection, QueryConfig);
+
+#[async_trait]
+impl<T, S> FromRequestParts<S> for Query<T>
+where
+    T: DeserializeOwned,
+    QueryConfig: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        match axum_06::extract::Query::<T>::from_request_parts(parts, state).await {
+            Ok(axum_06::extract::Query(value)) => Ok(Self(value)),
+            Err(rejection) => Err(QueryConfig::from_ref(state).handle_rejection(rejection)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum_06::{
+        body::Body,
+        http::{Method, StatusCode},
+        routing::post,
+        Router,
+    };
+    use serde::Deserialize;
+    use tower::ServiceExt;
+
+    #[derive(Deserialize)]
+    struct Payload {
+        #[allow(dead_code)]
+        id: u32,
+    }
+
+    #[derive(Clone, Default)]
+    struct AppState {
+        json_config: JsonConfig,
+        query_config: QueryConfig,
+    }
+
+    impl FromRef<AppState> for JsonConfig {
+        fn from_ref(state: &AppState) -> Self {
+            state.json_config.clone()
+        }
+    }
+
+    impl FromRef<AppState> for QueryConfig {
+        fn from_ref(state: &AppState) -> Self {
+            state.query_config.clone()
+        }
+    }
+
+    fn app(state: AppState) -> Router {
+        async fn handler(Query(_): Query<Payload>, Json(_): Json<Payload>) {}
+
+        Router::new().route("/", post(handler)).with_state(state)
+    }
+
+    async fn call(app: Router, uri: &str, body: &'static str) -> (StatusCode, String) {
+        let res = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri(uri)
+                    .header("content-type", "application/json")
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let status = res.status();
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn default_rejections() {
+        let (status, _) = call(app(AppState::default()), "/?id=1", "{").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, _) = call(app(AppState::default()), "/", "{\"id\":1}").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, _) = call(app(AppState::default()), "/?id=1", "{\"id\":1}").await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn rejection_handlers_from_state() {
+        let state = AppState {
+            json_config: JsonConfig::new().rejection_handler(|_| (StatusCode::IM_A_TEAPOT, "json")),
+            query_config: QueryConfig::new()
+                .rejection_handler(|_| (StatusCode::IM_A_TEAPOT, "query")),
+        };
+
+        let (status, body) = call(app(state.clone()), "/?id=1", "{").await;
+        assert_eq!(status, StatusCode::IM_A_TEAPOT);
+        assert_eq!(body, "json");
+
+        let (status, body) = call(app(state), "/", "{\"id\":1}").await;
+        assert_eq!(status, StatusCode::IM_A_TEAPOT);
+        assert_eq!(body, "query");
+    }
+}
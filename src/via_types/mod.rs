@@ -1,5 +1,6 @@
 //! Extractors that are configured via types.
 
+use crate::via_extensions::{rejection::ConfiguredRejection, JsonConfig};
 use axum::{
     async_trait,
     body::{Bytes, HttpBody},
@@ -7,8 +8,9 @@ use axum::{
         rejection::{FormRejection, JsonRejection, QueryRejection},
         FromRequest, RequestParts,
     },
+    http::StatusCode,
     response::{IntoResponse, Response},
-    BoxError,
+    BoxError, Extension,
 };
 use serde::{de::DeserializeOwned, Serialize};
 use std::{convert::Infallible, fmt, marker::PhantomData};
@@ -28,6 +30,371 @@ pub trait IntoResponseFromRejection<T, B> {
     ) -> Self::Response;
 }
 
+/// Trait for converting rejections into custom responses using shared state.
+///
+/// Like [`IntoResponseFromRejection`] but also receives a state, such as a translation catalog,
+/// which is obtained from the request extensions. Use it with [`WithState`].
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_types::{IntoResponseFromRejectionWithState, Json, WithState};
+/// use axum::{
+///     async_trait,
+///     extract::{rejection::JsonRejection, RequestParts},
+///     http::StatusCode,
+///     routing::post,
+///     Extension, Router,
+/// };
+/// use serde::Deserialize;
+/// use std::sync::Arc;
+///
+/// #[derive(Clone)]
+/// struct Branding {
+///     product_name: Arc<str>,
+/// }
+///
+/// struct BrandedRejection;
+///
+/// #[async_trait]
+/// impl<B> IntoResponseFromRejectionWithState<JsonRejection, B> for BrandedRejection
+/// where
+///     B: Send,
+/// {
+///     type State = Branding;
+///     type Response = (StatusCode, String);
+///
+///     async fn into_response_from_rejection(
+///         rejection: JsonRejection,
+///         state: Branding,
+///         _req: &mut RequestParts<B>,
+///     ) -> Self::Response {
+///         (
+///             StatusCode::BAD_REQUEST,
+///             format!("{}: {}", state.product_name, rejection),
+///         )
+///     }
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Payload {}
+///
+/// async fn handler(Json(payload, _): Json<Payload, WithState<BrandedRejection>>) {}
+///
+/// let app = Router::new()
+///     .route("/", post(handler))
+///     .layer(Extension(Branding { product_name: "Acme".into() }));
+/// # let _: Router = app;
+/// ```
+#[async_trait]
+pub trait IntoResponseFromRejectionWithState<T, B> {
+    /// The state given to the handler.
+    type State: Clone + Send + Sync + 'static;
+
+    /// The response the rejection is converted into.
+    type Response: IntoResponse;
+
+    /// Create the response from a rejection and the state.
+    async fn into_response_from_rejection(
+        rejection: T,
+        state: Self::State,
+        req: &mut RequestParts<B>,
+    ) -> Self::Response;
+}
+
+/// Handler that obtains the state of an [`IntoResponseFromRejectionWithState`] from the request
+/// extensions.
+///
+/// The state must be added with [`Extension`] and requests without it are rejected with
+/// `500 Internal Server Error`. See [`IntoResponseFromRejectionWithState`] for an example.
+pub struct WithState<H>(PhantomData<fn() -> H>);
+
+impl<H> fmt::Debug for WithState<H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("WithState")
+            .field(&format_args!("{}", std::any::type_name::<H>()))
+            .finish()
+    }
+}
+
+#[async_trait]
+impl<T, B, H> IntoResponseFromRejection<T, B> for WithState<H>
+where
+    T: Send + 'static,
+    B: Send,
+    H: IntoResponseFromRejectionWithState<T, B>,
+{
+    type Response = Response;
+
+    async fn into_response_from_rejection(
+        rejection: T,
+        req: &mut RequestParts<B>,
+    ) -> Self::Response {
+        match Extension::<H::State>::from_request(req).await {
+            Ok(Extension(state)) => H::into_response_from_rejection(rejection, state, req)
+                .await
+                .into_response(),
+            Err(rejection) => rejection.into_response(),
+        }
+    }
+}
+
+/// Handler that uses the runtime config of [`via_extensions`](crate::via_extensions).
+///
+/// Rejections are handled by the [`JsonConfig`](crate::via_extensions::JsonConfig) or
+/// [`GlobalRejectionConfig`](crate::via_extensions::GlobalRejectionConfig) added to the request,
+/// the same way as [`via_extensions::Json`](crate::via_extensions::Json) handles them, and with
+/// the default response if neither was added. This allows libraries to use the `via_types`
+/// extractors while applications keep control over the rejections.
+///
+/// The type being extracted isn't known to handlers, so
+/// [`RejectionContext::target_type_name`](crate::via_extensions::RejectionContext::target_type_name)
+/// returns `"()"`.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::{
+///     via_extensions::JsonConfig,
+///     via_types::{FromConfig, Json},
+/// };
+/// use axum::{http::StatusCode, routing::post, Router};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Payload {}
+///
+/// // declared by a library
+/// async fn handler(Json(payload, _): Json<Payload, FromConfig>) {}
+///
+/// // configured by the application
+/// let app = Router::new()
+///     .route("/", post(handler))
+///     .layer(JsonConfig::new().override_status(StatusCode::BAD_REQUEST));
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FromConfig;
+
+#[async_trait]
+impl<B> IntoResponseFromRejection<JsonRejection, B> for FromConfig
+where
+    B: Send + 'static,
+{
+    type Response = ConfiguredRejection<JsonRejection>;
+
+    async fn into_response_from_rejection(
+        rejection: JsonRejection,
+        req: &mut RequestParts<B>,
+    ) -> Self::Response {
+        JsonConfig::<B>::reject_with_extensions::<()>(rejection, req)
+    }
+}
+
+/// Handler that responds with the default rejection but with the status code `N`.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::via_types::{Json, WithStatus};
+/// use axum::{routing::post, Router};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Payload {}
+///
+/// // respond with `400 Bad Request` instead of `422 Unprocessable Entity`
+/// async fn handler(Json(payload, _): Json<Payload, WithStatus<400>>) {}
+///
+/// let app = Router::new().route("/", post(handler));
+/// # let _: Router = app;
+/// ```
+///
+/// Using a status code outside of `100..=999` fails to compile.
+pub struct WithStatus<const N: u16>;
+
+impl<const N: u16> WithStatus<N> {
+    const VALID: () = assert!(N >= 100 && N <= 999, "invalid status code");
+}
+
+impl<const N: u16> fmt::Debug for WithStatus<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "WithStatus<{}>", N)
+    }
+}
+
+#[async_trait]
+impl<T, B, const N: u16> IntoResponseFromRejection<T, B> for WithStatus<N>
+where
+    T: IntoResponse + Send + 'static,
+    B: Send,
+{
+    type Response = Response;
+
+    async fn into_response_from_rejection(
+        rejection: T,
+        _req: &mut RequestParts<B>,
+    ) -> Self::Response {
+        let () = Self::VALID;
+
+        let mut res = rejection.into_response();
+        *res.status_mut() = StatusCode::from_u16(N).expect("status code checked by `VALID`");
+        res
+    }
+}
+
+/// Define a handler for the `C` parameter of the extractors from a function.
+///
+/// This generates a type implementing [`IntoResponseFromRejection`] for the listed rejections by
+/// calling the function with the rejection. The function can return anything that implements
+/// `IntoResponse` and can be generic to handle several rejections.
+///
+/// # Example
+///
+/// ```
+/// use axum_extractor_config::{
+///     rejection_fn,
+///     via_types::{Json, Query},
+/// };
+/// use axum::{
+///     extract::rejection::{JsonRejection, QueryRejection},
+///     http::StatusCode,
+///     routing::post,
+///     Router,
+/// };
+/// use serde::Deserialize;
+///
+/// fn bad_request<R>(rejection: R) -> (StatusCode, String)
+/// where
+///     R: std::fmt::Display,
+/// {
+///     (StatusCode::BAD_REQUEST, rejection.to_string())
+/// }
+///
+/// rejection_fn! {
+///     /// Responds with `400 Bad Request` and the rejection's message.
+///     BadRequest: JsonRejection, QueryRejection => bad_request
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Params {}
+///
+/// #[derive(Deserialize)]
+/// struct Payload {}
+///
+/// async fn handler(
+///     Query(params, _): Query<Params, BadRequest>,
+///     Json(payload, _): Json<Payload, BadRequest>,
+/// ) {}
+///
+/// let app = Router::new().route("/", post(handler));
+/// # let _: Router = app;
+/// ```
+#[macro_export]
+macro_rules! rejection_fn {
+    (
+        $(#[$m:meta])*
+        $vis:vis $name:ident: $($rejection:ty),+ => $f:expr $(,)?
+    ) => {
+        $(#[$m])*
+        #[derive(Debug, Clone, Copy, Default)]
+        $vis struct $name;
+
+        $(
+            #[$crate::__private::async_trait]
+            impl<B> $crate::via_types::IntoResponseFromRejection<$rejection, B> for $name
+            where
+                B: Send,
+            {
+                type Response = $crate::__private::Response;
+
+                async fn into_response_from_rejection(
+                    rejection: $rejection,
+                    _req: &mut $crate::__private::RequestParts<B>,
+                ) -> Self::Response {
+                    $crate::__private::IntoResponse::into_response(($f)(rejection))
+                }
+            }
+        )+
+    };
+}
+
+/// Rejections with a default response.
+///
+/// Used by [`DefaultRejection`], the handler of extractors whose `C` parameter is left out, such
+/// as `Json<Payload>`. Implemented for the rejections of [`Json`], [`Query`], and [`Form`], which
+/// respond the same way as the extractors in axum.
+///
+/// Implement `DefaultRejectionFor<YourRejection>` for [`DefaultRejection`] to support leaving out
+/// the handler in your own extractors.
+pub trait DefaultRejectionFor<Rejection> {
+    /// The response the rejection is converted into.
+    type Response: IntoResponse;
+
+    /// Create the default response from a rejection.
+    fn default_response(rejection: Rejection) -> Self::Response;
+}
+
+/// The handler used when the `C` parameter of an extractor is left out.
+///
+/// Responds with the default response of the rejection, see [`DefaultRejectionFor`].
+///
+/// To use a custom handler throughout an application without spelling it out in every handler,
+/// define type aliases with the handler as the default:
+///
+/// ```
+/// use axum_extractor_config::via_types::{self, IntoResponseFromRejection};
+/// use axum::{
+///     async_trait,
+///     extract::{rejection::JsonRejection, RequestParts},
+///     http::StatusCode,
+/// };
+/// use serde::Deserialize;
+///
+/// struct AppRejection;
+///
+/// #[async_trait]
+/// impl<B> IntoResponseFromRejection<JsonRejection, B> for AppRejection
+/// where
+///     B: Send,
+/// {
+///     type Response = (StatusCode, String);
+///
+///     async fn into_response_from_rejection(
+///         rejection: JsonRejection,
+///         _req: &mut RequestParts<B>,
+///     ) -> Self::Response {
+///         (StatusCode::BAD_REQUEST, rejection.to_string())
+///     }
+/// }
+///
+/// type Json<T, C = AppRejection> = via_types::Json<T, C>;
+///
+/// #[derive(Deserialize)]
+/// struct Payload {}
+///
+/// async fn handler(via_types::Json(payload, _): Json<Payload>) {}
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultRejection;
+
+#[async_trait]
+impl<R, B> IntoResponseFromRejection<R, B> for DefaultRejection
+where
+    R: Send + 'static,
+    B: Send,
+    Self: DefaultRejectionFor<R>,
+{
+    type Response = <Self as DefaultRejectionFor<R>>::Response;
+
+    async fn into_response_from_rejection(
+        rejection: R,
+        _req: &mut RequestParts<B>,
+    ) -> Self::Response {
+        Self::default_response(rejection)
+    }
+}
+
 macro_rules! make_deserialize_wrapper {
     (
         $(#[$m:meta])*
@@ -49,8 +416,16 @@ macro_rules! make_deserialize_wrapper {
             }
         }
 
+        impl DefaultRejectionFor<$rejection> for DefaultRejection {
+            type Response = $rejection;
+
+            fn default_response(rejection: $rejection) -> Self::Response {
+                rejection
+            }
+        }
+
         $(#[$m])*
-        pub struct $ident<T, C>(pub T, pub PhantomData<fn() -> C>);
+        pub struct $ident<T, C = DefaultRejection>(pub T, pub PhantomData<fn() -> C>);
 
         #[async_trait]
         impl<T, C, B> FromRequest<B> for $ident<T, C>
@@ -70,6 +445,61 @@ macro_rules! make_deserialize_wrapper {
             }
         }
 
+        impl<T, C> $ident<T, C> {
+            /// Consume the extractor and return the inner value.
+            pub fn into_inner(self) -> T {
+                self.0
+            }
+
+            /// Convert the inner value with `f`.
+            pub fn map<U, F>(self, f: F) -> $ident<U, C>
+            where
+                F: FnOnce(T) -> U,
+            {
+                $ident(f(self.0), PhantomData)
+            }
+
+            /// Convert the inner value with `f`, which can fail.
+            pub fn try_map<U, E, F>(self, f: F) -> Result<$ident<U, C>, E>
+            where
+                F: FnOnce(T) -> Result<U, E>,
+            {
+                f(self.0).map(|value| $ident(value, PhantomData))
+            }
+        }
+
+        impl<T, C> std::ops::Deref for $ident<T, C> {
+            type Target = T;
+
+            fn deref(&self) -> &T {
+                &self.0
+            }
+        }
+
+        impl<T, C> std::ops::DerefMut for $ident<T, C> {
+            fn deref_mut(&mut self) -> &mut T {
+                &mut self.0
+            }
+        }
+
+        impl<T, C> From<T> for $ident<T, C> {
+            fn from(value: T) -> Self {
+                Self(value, PhantomData)
+            }
+        }
+
+        impl<T, C> From<axum::extract::$ident<T>> for $ident<T, C> {
+            fn from(axum::extract::$ident(value): axum::extract::$ident<T>) -> Self {
+                Self(value, PhantomData)
+            }
+        }
+
+        impl<T, C> From<$ident<T, C>> for axum::extract::$ident<T> {
+            fn from($ident(value, _): $ident<T, C>) -> Self {
+                Self(value)
+            }
+        }
+
         impl<T, C> Clone for $ident<T, C>
         where
             T: Clone,
@@ -98,6 +528,50 @@ macro_rules! make_deserialize_wrapper {
                 Self(Default::default(), Default::default())
             }
         }
+
+        impl<T, C> PartialEq for $ident<T, C>
+        where
+            T: PartialEq,
+        {
+            fn eq(&self, other: &Self) -> bool {
+                self.0 == other.0
+            }
+        }
+
+        impl<T, C> Eq for $ident<T, C> where T: Eq {}
+
+        impl<T, C> std::hash::Hash for $ident<T, C>
+        where
+            T: std::hash::Hash,
+        {
+            fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                self.0.hash(state);
+            }
+        }
+
+        impl<T, C> Serialize for $ident<T, C>
+        where
+            T: Serialize,
+        {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                self.0.serialize(serializer)
+            }
+        }
+
+        impl<'de, T, C> serde::Deserialize<'de> for $ident<T, C>
+        where
+            T: serde::Deserialize<'de>,
+        {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                T::deserialize(deserializer).map(|value| Self(value, PhantomData))
+            }
+        }
     };
 }
 
@@ -231,6 +705,15 @@ make_deserialize_wrapper! {
     QueryRejection,
 }
 
+impl<T, C> Query<T, C> {
+    /// Create a new `Query`.
+    ///
+    /// This is mostly useful in tests, for example to call handlers directly.
+    pub fn new(value: T) -> Self {
+        Self(value, PhantomData)
+    }
+}
+
 make_deserialize_wrapper! {
     /// Extractor that wraps `axum::extract::Form` and supports compile time configuration.
     ///
@@ -385,6 +868,157 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn json_default_type_parameter() {
+        async fn handler(Json(_payload, _): Json<Payload>) {}
+
+        let mut app = Router::new().route("/", post(handler));
+
+        let body = json!({ "id": "foo" }).to_string();
+        let res = app
+            .call(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn json_rejection_with_state() {
+        struct StatefulRejection;
+
+        #[async_trait]
+        impl<B> IntoResponseFromRejectionWithState<JsonRejection, B> for StatefulRejection
+        where
+            B: Send,
+        {
+            type State = &'static str;
+            type Response = (StatusCode, &'static str);
+
+            async fn into_response_from_rejection(
+                _rejection: JsonRejection,
+                state: &'static str,
+                _req: &mut RequestParts<B>,
+            ) -> Self::Response {
+                (StatusCode::BAD_REQUEST, state)
+            }
+        }
+
+        let request = || {
+            Request::builder()
+                .method(Method::POST)
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(json!({ "id": "foo" }).to_string()))
+                .unwrap()
+        };
+
+        let mut with_state = app::<WithState<StatefulRejection>>().layer(Extension("from state"));
+        let res = with_state.call(request()).await.unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(body, "from state");
+
+        let mut without_state = app::<WithState<StatefulRejection>>();
+        let res = without_state.call(request()).await.unwrap();
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[tokio::test]
+    async fn json_rejection_fn() {
+        crate::rejection_fn! {
+            Teapot: JsonRejection => |_| StatusCode::IM_A_TEAPOT
+        }
+
+        let mut app = app::<Teapot>();
+
+        let body = json!({ "id": "foo" }).to_string();
+        let res = app
+            .call(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), StatusCode::IM_A_TEAPOT);
+    }
+
+    #[tokio::test]
+    async fn json_with_status() {
+        let mut app = app::<WithStatus<400>>();
+
+        let body = json!({ "id": "foo" }).to_string();
+        let res = app
+            .call(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert_eq!(
+            body,
+            "Failed to deserialize the JSON body into the target type: \
+            invalid type: string \"foo\", expected u32 at line 1 column 11"
+        );
+    }
+
+    #[test]
+    fn serde_passthrough() {
+        let json = serde_json::from_value::<Json<Vec<u32>>>(json!([1, 2])).unwrap();
+        assert_eq!(json, Json::from(vec![1, 2]));
+        assert_eq!(serde_json::to_value(json).unwrap(), json!([1, 2]));
+    }
+
+    #[tokio::test]
+    async fn query_new() {
+        async fn handler(query: Query<Payload>) -> String {
+            query.into_inner().id.to_string()
+        }
+
+        assert_eq!(handler(Query::new(Payload { id: 1 })).await, "1");
+    }
+
+    #[tokio::test]
+    async fn json_from_config() {
+        let request = || {
+            Request::builder()
+                .method(Method::POST)
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(json!({ "id": "foo" }).to_string()))
+                .unwrap()
+        };
+
+        let mut configured = app::<FromConfig>()
+            .layer(JsonConfig::new().rejection_handler(|_, _| StatusCode::IM_A_TEAPOT));
+        let res = configured.call(request()).await.unwrap();
+        assert_eq!(res.status(), StatusCode::IM_A_TEAPOT);
+
+        let mut unconfigured = app::<FromConfig>();
+        let res = unconfigured.call(request()).await.unwrap();
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
     #[tokio::test]
     async fn json_custom_rejection() {
         struct CustomRejection(Response);